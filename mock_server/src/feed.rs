@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+use models::ws_event::{RejectedFrame, WsEvent, WsFrame};
+use crate::Mock;
+
+// How many of our own broadcasts to remember when matching up the copies clients send back
+const RECENT_EVENTS: usize = 64;
+
+struct FeedState {
+    seq: u64,
+    recent: VecDeque<WsEvent>,
+}
+
+// The socket side of the mock. Every broadcast gets the next sequence number, the same as the
+// real server, so the gap detection and resync in the app can be exercised too.
+pub struct Feed {
+    state: Mutex<FeedState>,
+    sender: broadcast::Sender<String>,
+}
+
+impl Feed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Feed {
+            state: Mutex::new(FeedState { seq: 0, recent: VecDeque::new() }),
+            sender,
+        }
+    }
+
+    // Sends an event to every connected client after the API changed something
+    pub fn publish(&self, event: WsEvent) {
+        let mut state = self.state.lock().unwrap();
+        state.recent.push_back(event.clone());
+        if state.recent.len() > RECENT_EVENTS {
+            state.recent.pop_front();
+        }
+        self.send(&mut state, event);
+    }
+
+    // Passes on an event a client sent. The app sends one after every successful API call, and
+    // we've already broadcast those, so a matching copy is dropped instead of applied twice.
+    fn relay(&self, event: WsEvent) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(index) = state.recent.iter().position(|recent| *recent == event) {
+            state.recent.remove(index);
+            return;
+        }
+        self.send(&mut state, event);
+    }
+
+    fn send(&self, state: &mut FeedState, event: WsEvent) {
+        state.seq += 1;
+        let frame = WsFrame { seq: Some(state.seq), ..WsFrame::new(event) };
+        // Nobody listening isn't an error
+        let _ = self.sender.send(frame.to_json());
+    }
+
+    // Subscribes under the lock so the Hello and the first broadcast can't skip a number
+    fn subscribe(&self) -> (u64, broadcast::Receiver<String>) {
+        let state = self.state.lock().unwrap();
+        (state.seq, self.sender.subscribe())
+    }
+}
+
+pub async fn connect(req: HttpRequest, body: web::Payload, mock: web::Data<Mock>) -> Result<HttpResponse, actix_web::Error> {
+    let (response, mut session, mut stream) = actix_ws::handle(&req, body)?;
+    let (seq, mut receiver) = mock.feed.subscribe();
+    println!("Client connected at seq {}", seq);
+
+    actix_web::rt::spawn(async move {
+        if session.text(WsFrame::new(WsEvent::Hello { seq }).to_json()).await.is_err() {
+            return;
+        }
+        loop {
+            tokio::select! {
+                broadcast = receiver.recv() => match broadcast {
+                    Ok(frame) => {
+                        if session.text(frame).await.is_err() {
+                            break;
+                        }
+                    },
+                    // Fell behind. Closing makes the app reconnect, get a Hello and resync.
+                    Err(broadcast::error::RecvError::Lagged(_)) => break,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => match WsFrame::parse(&text) {
+                        Ok(WsFrame { event: WsEvent::Ping, .. }) => {
+                            if session.text(WsFrame::new(WsEvent::Pong).to_json()).await.is_err() {
+                                break;
+                            }
+                        },
+                        Ok(frame) => mock.feed.relay(frame.event),
+                        Err(RejectedFrame { reason, frame }) => println!("Rejected frame ({}): {}", reason, frame),
+                    },
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {},
+                },
+            }
+        }
+        let _ = session.close(None).await;
+        println!("Client disconnected");
+    });
+
+    Ok(response)
+}
@@ -0,0 +1,423 @@
+// A stand-in for the production API, upload service and WebSocket server, so the whole app
+// can be run and demoed on one machine. Ports match the "dev" profile in config.json.
+//
+//     cargo run -p mock-server
+//     trunk serve
+//
+// Log in as admin/admin, write/write or read/read. Data is seeded around today's date and
+// lives in memory, so restarting the mock resets it. Set MOCK_TOKEN_TTL (seconds) to
+// something short to exercise the token refresh.
+mod feed;
+mod store;
+
+use std::env;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use actix_cors::Cors;
+use actix_web::{body::MessageBody, dev::{ServiceRequest, ServiceResponse}, middleware::{from_fn, Next}, web, App, HttpResponse, HttpServer};
+use models::*;
+use models::carrier::{Carrier, CarrierRef};
+use models::shipment_status::ShipmentAction;
+use models::ws_event::WsEvent;
+use feed::Feed;
+use store::Store;
+
+const API_PORT: u16 = 8000;
+const UPLOAD_PORT: u16 = 8888;
+const WS_PORT: u16 = 9001;
+
+pub struct Mock {
+    store: Mutex<Store>,
+    pub feed: Feed,
+}
+
+impl Mock {
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().unwrap()
+    }
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let host = env::var("MOCK_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let token_ttl = env::var("MOCK_TOKEN_TTL").ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(15 * 60));
+    let mock = web::Data::new(Mock {
+        store: Mutex::new(Store::new(token_ttl)),
+        feed: Feed::new(),
+    });
+
+    let api = {
+        let mock = mock.clone();
+        HttpServer::new(move || {
+            App::new()
+                .app_data(mock.clone())
+                .wrap(Cors::permissive())
+                .route("/login", web::post().to(login))
+                .route("/register", web::post().to(register))
+                .route("/refresh", web::post().to(refresh))
+                .service(web::scope("/api")
+                    .wrap(from_fn(require_token))
+                    .route("/schedule_trailer", web::get().to(schedule_trailer))
+                    .route("/todays_trucks", web::post().to(todays_trucks))
+                    .route("/trucks_date_range", web::post().to(trucks_date_range))
+                    .route("/set_arrivalTime", web::post().to(set_arrival_time))
+                    .route("/hot_trailer", web::post().to(hot_trailer))
+                    .route("/set_schedule", web::post().to(set_schedule))
+                    .route("/get_load_info", web::post().to(get_load_info))
+                    .route("/trailers", web::post().to(trailers))
+                    .route("/get_shipments", web::get().to(get_shipments))
+                    .route("/get_todays_shipments", web::post().to(get_todays_shipments))
+                    .route("/new_shipment", web::post().to(new_shipment))
+                    .route("/shipment_hold", web::post().to(shipment_hold))
+                    .route("/shipment_pick_finish", web::post().to(shipment_pick_finish))
+                    .route("/shipment_begin_loading", web::post().to(shipment_begin_loading))
+                    .route("/set_shipment_pick_start", web::post().to(set_shipment_pick_start))
+                    .route("/set_shipment_trailer", web::post().to(set_shipment_trailer))
+                    .route("/shipment_door", web::post().to(shipment_door))
+                    .route("/set_shipment_departureTime", web::post().to(set_shipment_departure_time))
+                    .route("/shipment_verification", web::post().to(shipment_verification))
+                    .route("/get_shipment_details", web::post().to(get_shipment_details))
+                    .route("/shipment_lines", web::post().to(shipment_lines))
+                    .route("/carriers", web::get().to(carriers))
+                    .route("/set_carrier", web::post().to(set_carrier))
+                    .route("/delete_carrier", web::post().to(delete_carrier)))
+        })
+        .bind((host.as_str(), API_PORT))?
+        .run()
+    };
+
+    let upload = HttpServer::new(|| {
+        App::new()
+            .app_data(web::PayloadConfig::new(50 * 1024 * 1024))
+            .wrap(Cors::permissive())
+            .route("/upload", web::post().to(upload))
+    })
+    .bind((host.as_str(), UPLOAD_PORT))?
+    .run();
+
+    let ws = {
+        let mock = mock.clone();
+        HttpServer::new(move || {
+            App::new()
+                .app_data(mock.clone())
+                .route("/", web::get().to(feed::connect))
+        })
+        .bind((host.as_str(), WS_PORT))?
+        .run()
+    };
+
+    println!("Mock API on http://{}:{}, uploads on :{}, WebSocket on ws://{}:{}", host, API_PORT, UPLOAD_PORT, host, WS_PORT);
+    futures::try_join!(api, upload, ws)?;
+    Ok(())
+}
+
+async fn require_token(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let token = req.headers().get("Authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|token| token.to_string());
+    let valid = match (token, req.app_data::<web::Data<Mock>>()) {
+        (Some(token), Some(mock)) => mock.store().is_valid(&token),
+        _ => false,
+    };
+    if valid {
+        next.call(req).await.map(ServiceResponse::map_into_left_body)
+    } else {
+        Ok(req.into_response(HttpResponse::Unauthorized().body("Token expired or invalid")).map_into_right_body())
+    }
+}
+
+fn not_found(what: &str, id: &str) -> HttpResponse {
+    HttpResponse::NotFound().body(format!("{} {} not found", what, id))
+}
+
+// Applies a change to one trailer, broadcasts it and answers the way the API does
+fn update_trailer(mock: &Mock, trailer_id: &str, change: impl FnOnce(&mut TrailerResponse) -> WsEvent) -> HttpResponse {
+    let mut store = mock.store();
+    let Some(trailer) = store.trailer_mut(trailer_id) else {
+        return not_found("Trailer", trailer_id);
+    };
+    let event = change(trailer);
+    let schedule = TrailerSchedule { trailer_id: trailer.trailer_id.clone(), schedule: trailer.schedule.clone() };
+    drop(store);
+    mock.feed.publish(event);
+    HttpResponse::Ok().json(vec![schedule])
+}
+
+// Applies a change to one load, broadcasts it and returns the updated shipment. The change can
+// refuse with a reason, which goes back as a 409, e.g. for a load that already left.
+fn update_shipment(mock: &Mock, load_id: &str, change: impl FnOnce(&mut Shipment) -> Result<WsEvent, String>) -> HttpResponse {
+    let mut store = mock.store();
+    let Some(shipment) = store.shipment_mut(load_id) else {
+        return not_found("Load", load_id);
+    };
+    let event = match change(shipment) {
+        Ok(event) => event,
+        Err(reason) => return HttpResponse::Conflict().body(reason),
+    };
+    let shipment = shipment.clone();
+    drop(store);
+    mock.feed.publish(event);
+    HttpResponse::Ok().json(shipment)
+}
+
+// Moves a load along by one step, refusing anything out of order the way the API does. Sending
+// the step a load has just taken again is fine, that's how a picker gets changed.
+fn advance(shipment: &mut Shipment, action: ShipmentAction) -> Result<(), String> {
+    if shipment.status != action.target() {
+        shipment.status = shipment.check(action)?;
+    }
+    Ok(())
+}
+
+fn departed(shipment: &Shipment) -> Result<(), String> {
+    if shipment.depart_time.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Load {} already departed at {}", shipment.load_id, shipment.depart_time))
+    }
+}
+
+async fn login(mock: web::Data<Mock>, request: web::Json<LoginRequest>) -> HttpResponse {
+    match mock.store().login(&request.username, &request.password) {
+        Some(response) => HttpResponse::Ok().json(response),
+        None => HttpResponse::Unauthorized().body("Invalid username or password"),
+    }
+}
+
+async fn register(mock: web::Data<Mock>, request: web::Json<LoginRequest>) -> HttpResponse {
+    if mock.store().register(&request.username, &request.password) {
+        HttpResponse::Ok().json(format!("Registered {}", request.username))
+    } else {
+        HttpResponse::Conflict().body(format!("User {} already exists", request.username))
+    }
+}
+
+async fn refresh(mock: web::Data<Mock>, request: web::Json<RefreshRequest>) -> HttpResponse {
+    match mock.store().refresh(&request.refresh_token) {
+        Some(response) => HttpResponse::Ok().json(response),
+        None => HttpResponse::Unauthorized().body("Invalid refresh token"),
+    }
+}
+
+async fn schedule_trailer(mock: web::Data<Mock>) -> HttpResponse {
+    HttpResponse::Ok().json(&mock.store().trailers)
+}
+
+async fn todays_trucks(mock: web::Data<Mock>, request: web::Json<TodaysTrucksRequest>) -> HttpResponse {
+    let trailers: Vec<TrailerResponse> = mock.store().trailers.iter()
+        .filter(|trailer| trailer.schedule.schedule_date == request.date)
+        .cloned()
+        .collect();
+    HttpResponse::Ok().json(trailers)
+}
+
+async fn trucks_date_range(mock: web::Data<Mock>, request: web::Json<DateRangeTruckRequest>) -> HttpResponse {
+    let trailers: Vec<TrailerResponse> = mock.store().trailers.iter()
+        .filter(|trailer| trailer.schedule.schedule_date >= request.date1 && trailer.schedule.schedule_date <= request.date2)
+        .cloned()
+        .collect();
+    HttpResponse::Ok().json(trailers)
+}
+
+async fn set_arrival_time(mock: web::Data<Mock>, request: web::Json<Arrival>) -> HttpResponse {
+    let request = request.into_inner();
+    update_trailer(&mock, &request.trailer_id.clone(), |trailer| {
+        trailer.schedule.arrival_time = request.arrival_time;
+        trailer.schedule.load_status = if request.arrival_time.is_empty() { "Scheduled" } else { "Arrived" }.to_string();
+        WsEvent::TrailerArrived(request)
+    })
+}
+
+async fn hot_trailer(mock: web::Data<Mock>, request: web::Json<HotTrailer>) -> HttpResponse {
+    let request = request.into_inner();
+    update_trailer(&mock, &request.trailer_id.clone(), |trailer| {
+        trailer.schedule.is_hot = !trailer.schedule.is_hot;
+        WsEvent::HotTrailer(request)
+    })
+}
+
+async fn set_schedule(mock: web::Data<Mock>, request: web::Json<SetScheduleRequest>) -> HttpResponse {
+    let request = request.into_inner();
+    update_trailer(&mock, &request.trailer_id.clone(), |trailer| {
+        trailer.schedule.schedule_date = request.schedule_date;
+        trailer.schedule.request_date = request.request_date;
+        trailer.schedule.carrier_code = request.carrier_code.clone();
+        trailer.schedule.schedule_time = request.schedule_time;
+        trailer.schedule.last_free_date = request.last_free_date;
+        trailer.schedule.contact_email = request.contact_email.clone();
+        trailer.schedule.door_number = request.door.clone();
+        WsEvent::ScheduleTrailer(request)
+    })
+}
+
+async fn get_load_info(mock: web::Data<Mock>, request: web::Json<LoadInfoRequest>) -> HttpResponse {
+    HttpResponse::Ok().json(mock.store().sids.get(&request.param).cloned().unwrap_or_default())
+}
+
+async fn trailers(mock: web::Data<Mock>, request: web::Json<TodaysTrucksRequest>) -> HttpResponse {
+    let store = mock.store();
+    let sids: Vec<Sids> = store.trailers.iter()
+        .filter(|trailer| trailer.schedule.schedule_date == request.date)
+        .map(|trailer| Sids {
+            trailer_id: trailer.trailer_id.clone(),
+            sids: store.sids.get(&trailer.trailer_id).into_iter().flatten()
+                .flat_map(|sid| sid.parts.iter().map(|part| SidAndParts {
+                    sid: sid.sid.id.clone(),
+                    cisco: sid.sid.cisco_id.clone(),
+                    part: part.part_number.clone(),
+                    quantity: part.quantity,
+                }))
+                .collect(),
+        })
+        .collect();
+    HttpResponse::Ok().json(sids)
+}
+
+async fn carriers(mock: web::Data<Mock>) -> HttpResponse {
+    HttpResponse::Ok().json(&mock.store().carriers)
+}
+
+// Adds or replaces a carrier by SCAC and sends back the whole directory
+async fn set_carrier(mock: web::Data<Mock>, request: web::Json<Carrier>) -> HttpResponse {
+    let carrier = request.into_inner();
+    let errors = carrier.validate();
+    if !errors.is_empty() {
+        return HttpResponse::BadRequest().body(errors.join("; "));
+    }
+    let mut store = mock.store();
+    store.carriers.upsert(carrier);
+    HttpResponse::Ok().json(&store.carriers)
+}
+
+async fn delete_carrier(mock: web::Data<Mock>, request: web::Json<CarrierRef>) -> HttpResponse {
+    let mut store = mock.store();
+    if store.carriers.by_scac(&request.scac).is_none() {
+        return HttpResponse::NotFound().body(format!("No carrier {}", request.scac));
+    }
+    store.carriers.remove(&request.scac);
+    HttpResponse::Ok().json(&store.carriers)
+}
+
+async fn get_shipments(mock: web::Data<Mock>) -> HttpResponse {
+    HttpResponse::Ok().json(&mock.store().shipments)
+}
+
+async fn get_todays_shipments(mock: web::Data<Mock>, request: web::Json<TodaysTrucksRequest>) -> HttpResponse {
+    let shipments: Vec<Shipment> = mock.store().shipments.iter()
+        .filter(|shipment| shipment.schedule_date == request.date)
+        .cloned()
+        .collect();
+    HttpResponse::Ok().json(shipments)
+}
+
+async fn new_shipment(mock: web::Data<Mock>, request: web::Json<Shipment>) -> HttpResponse {
+    let shipment = request.into_inner();
+    let mut store = mock.store();
+    if store.shipment_mut(&shipment.load_id).is_some() {
+        return HttpResponse::Conflict().body(format!("Load {} already exists", shipment.load_id));
+    }
+    store.shipments.insert(0, shipment.clone());
+    drop(store);
+    mock.feed.publish(WsEvent::NewShipment(shipment.clone()));
+    HttpResponse::Ok().json(shipment)
+}
+
+async fn shipment_hold(mock: web::Data<Mock>, request: web::Json<ShipmentRef>) -> HttpResponse {
+    update_shipment(&mock, &request.load_id, |shipment| {
+        departed(shipment)?;
+        shipment.is_hold = !shipment.is_hold;
+        Ok(WsEvent::ShipmentHold(ShipmentRef { load_id: shipment.load_id.clone() }))
+    })
+}
+
+async fn shipment_pick_finish(mock: web::Data<Mock>, request: web::Json<PickFinish>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        advance(shipment, ShipmentAction::FinishPick)?;
+        shipment.pick_finish_time = request.finish_time;
+        Ok(WsEvent::FinishShipmentPick(request))
+    })
+}
+
+async fn shipment_begin_loading(mock: web::Data<Mock>, request: web::Json<ShipmentRef>) -> HttpResponse {
+    update_shipment(&mock, &request.load_id, |shipment| {
+        departed(shipment)?;
+        advance(shipment, ShipmentAction::StartLoading)?;
+        Ok(WsEvent::ShipmentStartLoading(ShipmentRef { load_id: shipment.load_id.clone() }))
+    })
+}
+
+async fn set_shipment_pick_start(mock: web::Data<Mock>, request: web::Json<PickStart>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        advance(shipment, ShipmentAction::StartPick)?;
+        shipment.picker = request.picker.clone();
+        shipment.pick_start_time = request.start_time;
+        Ok(WsEvent::StartShipmentPick(request))
+    })
+}
+
+async fn set_shipment_trailer(mock: web::Data<Mock>, request: web::Json<ShipmentTrailer>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        shipment.arrival_time = request.arrival_time;
+        shipment.trailer_num = request.trailer_num.clone();
+        Ok(WsEvent::ShipmentTrailerArrival(request))
+    })
+}
+
+async fn shipment_door(mock: web::Data<Mock>, request: web::Json<ShipmentDoor>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        shipment.door = request.door.clone();
+        Ok(WsEvent::SetShipmentDoor(request))
+    })
+}
+
+async fn set_shipment_departure_time(mock: web::Data<Mock>, request: web::Json<Departure>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        advance(shipment, ShipmentAction::Depart)?;
+        shipment.depart_time = request.depart_time;
+        shipment.seal = request.seal.clone();
+        Ok(WsEvent::ShipmentDepart(request))
+    })
+}
+
+async fn shipment_verification(mock: web::Data<Mock>, request: web::Json<Verification>) -> HttpResponse {
+    let request = request.into_inner();
+    update_shipment(&mock, &request.load_id.clone(), |shipment| {
+        departed(shipment)?;
+        advance(shipment, ShipmentAction::Verify)?;
+        shipment.verified_by = request.verified_by.clone();
+        Ok(WsEvent::VerifiedBy(request))
+    })
+}
+
+async fn get_shipment_details(mock: web::Data<Mock>, request: web::Json<ShipmentRef>) -> HttpResponse {
+    HttpResponse::Ok().json(mock.store().shipment_lines.get(&request.load_id).cloned().unwrap_or_default())
+}
+
+async fn shipment_lines(mock: web::Data<Mock>, request: web::Json<ShipmentLineUploadRequest>) -> HttpResponse {
+    let request = request.into_inner();
+    let mut store = mock.store();
+    if store.shipment_mut(&request.load_id).is_none() {
+        return not_found("Load", &request.load_id);
+    }
+    store.shipment_lines.insert(request.load_id, request.lines.clone());
+    HttpResponse::Ok().json(request.lines)
+}
+
+// The real upload service parses the spreadsheet into the item master. The mock only says it got it.
+async fn upload(body: web::Bytes) -> HttpResponse {
+    HttpResponse::Ok().body(format!("Received {} bytes", body.len()))
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use models::*;
+use models::carrier::{Carrier, CarrierDirectory};
+use models::shipment_status::ShipmentStatus;
+use models::time::{CalendarDate, Timestamp};
+
+// Everything the API would keep in its database, held in memory for as long as the mock runs
+pub struct Store {
+    users: HashMap<String, (String, String)>,
+    tokens: HashMap<String, (String, Instant)>,
+    refresh_tokens: HashMap<String, String>,
+    token_ttl: Duration,
+    next_token: u64,
+    pub trailers: Vec<TrailerResponse>,
+    pub sids: HashMap<String, Vec<SidParts>>,
+    pub shipments: Vec<Shipment>,
+    pub shipment_lines: HashMap<String, Vec<ShipmentLine>>,
+    pub carriers: CarrierDirectory,
+}
+
+impl Store {
+    pub fn new(token_ttl: Duration) -> Self {
+        let mut store = Store {
+            users: HashMap::new(),
+            tokens: HashMap::new(),
+            refresh_tokens: HashMap::new(),
+            token_ttl,
+            next_token: 0,
+            trailers: vec![],
+            sids: HashMap::new(),
+            shipments: vec![],
+            shipment_lines: HashMap::new(),
+            carriers: CarrierDirectory::default(),
+        };
+        store.seed();
+        store
+    }
+
+    pub fn register(&mut self, username: &str, password: &str) -> bool {
+        if self.users.contains_key(username) {
+            return false;
+        }
+        self.users.insert(username.to_string(), (password.to_string(), "read".to_string()));
+        true
+    }
+
+    pub fn login(&mut self, username: &str, password: &str) -> Option<LoginResponse> {
+        match self.users.get(username) {
+            Some((expected, _)) if expected == password => {
+                let refresh_token = self.new_token("refresh");
+                self.refresh_tokens.insert(refresh_token.clone(), username.to_string());
+                self.session(username, Some(refresh_token))
+            },
+            _ => None,
+        }
+    }
+
+    // Issues a new access token. The refresh token is left as it is, so the response leaves it out.
+    pub fn refresh(&mut self, refresh_token: &str) -> Option<LoginResponse> {
+        let username = self.refresh_tokens.get(refresh_token)?.clone();
+        self.session(&username, None)
+    }
+
+    pub fn is_valid(&self, token: &str) -> bool {
+        self.tokens.get(token).is_some_and(|(_, issued)| issued.elapsed() < self.token_ttl)
+    }
+
+    fn session(&mut self, username: &str, refresh_token: Option<String>) -> Option<LoginResponse> {
+        let (_, role) = self.users.get(username)?.clone();
+        let token = self.new_token("access");
+        self.tokens.insert(token.clone(), (username.to_string(), Instant::now()));
+        Some(LoginResponse {
+            token,
+            refresh_token,
+            user: UserResponse { username: username.to_string(), role },
+        })
+    }
+
+    fn new_token(&mut self, kind: &str) -> String {
+        self.next_token += 1;
+        format!("mock-{}-{}", kind, self.next_token)
+    }
+
+    pub fn trailer_mut(&mut self, trailer_id: &str) -> Option<&mut TrailerResponse> {
+        self.trailers.iter_mut().find(|trailer| trailer.trailer_id == trailer_id)
+    }
+
+    pub fn shipment_mut(&mut self, load_id: &str) -> Option<&mut Shipment> {
+        self.shipments.iter_mut().find(|shipment| shipment.load_id == load_id)
+    }
+
+    // A few days of trailers and loads around today, so every screen has something on it
+    fn seed(&mut self) {
+        for (username, role) in [("admin", "admin"), ("write", "write"), ("read", "read")] {
+            self.users.insert(username.to_string(), (username.to_string(), role.to_string()));
+        }
+
+        let today = CalendarDate::today();
+        let day = |offset: i64| today.days_from(offset);
+        // Seeded clock times land on the record's own day
+        let at = |offset: i64, time: &str| -> Timestamp {
+            if time.is_empty() {
+                Timestamp::default()
+            } else {
+                format!("{} {}", day(offset), time).parse().expect("seeded times are valid")
+            }
+        };
+
+        // The SCACs the seeded trailers use, less one so the directory's gaps show up too
+        let carriers = [
+            ("JBHT", "J.B. Hunt Transport", "dispatch@jbhunt.example.com", "800-555-0101", "08:00", "12", Some(150.0)),
+            ("KNXT", "Knight Transportation", "loads@knight.example.com", "800-555-0102", "", "", None),
+            ("ODFL", "Old Dominion Freight Line", "dispatch@odfl.example.com", "800-555-0103", "09:30", "", Some(90.0)),
+        ];
+        self.carriers = CarrierDirectory::new(carriers.into_iter().map(|(scac, name, email, phone, default_time, default_door, daily_rate)| Carrier {
+            scac: scac.to_string(),
+            name: name.to_string(),
+            dispatch_emails: vec![email.to_string()],
+            phone: phone.to_string(),
+            default_time: default_time.parse().expect("seeded times are valid"),
+            default_door: default_door.to_string(),
+            daily_rate,
+        }).collect());
+
+        let trailers = [
+            ("TRL1001", -1, "06:00", "07:12", "SCAC", "12", false),
+            ("TRL1002", -1, "09:30", "", "ODFL", "", true),
+            ("TRL1003", 0, "07:00", "06:48", "JBHT", "14", false),
+            ("TRL1004", 0, "10:00", "", "SCAC", "15", true),
+            ("TRL1005", 0, "13:30", "", "KNXT", "16", false),
+            ("TRL1006", 0, "15:00", "", "ODFL", "", false),
+            ("TRL1007", 1, "08:00", "", "JBHT", "12", false),
+            ("TRL1008", 2, "11:00", "", "KNXT", "", false),
+        ];
+        for (n, (trailer_id, offset, schedule_time, arrival_time, scac, door, hot)) in trailers.into_iter().enumerate() {
+            // The Cisco location IDs listed for the plants in config.json
+            let cisco_ids: Vec<String> = (0..2).map(|i| ["18008", "18044", "22010"][(n + i) % 3].to_string()).collect();
+            self.sids.insert(trailer_id.to_string(), cisco_ids.iter().enumerate().map(|(i, cisco_id)| SidParts {
+                sid: Sid { cisco_id: cisco_id.clone(), id: format!("SID{:05}", n * 10 + i) },
+                parts: (0..3).map(|p| Part {
+                    part_number: format!("{}-{:03}", 7100 + n, p * 10 + i),
+                    quantity: ((n + 1) * (p + 2) * 12) as i32,
+                }).collect(),
+            }).collect());
+            self.trailers.push(TrailerResponse {
+                trailer_id: trailer_id.to_string(),
+                schedule: Schedule {
+                    schedule_date: day(offset),
+                    schedule_time: schedule_time.parse().expect("seeded times are valid"),
+                    arrival_time: at(offset, arrival_time),
+                    carrier_code: scac.to_string(),
+                    contact_email: format!("dispatch@{}.example.com", scac.to_lowercase()),
+                    door_number: door.to_string(),
+                    is_hot: hot,
+                    last_free_date: day(offset + 3),
+                    load_status: if arrival_time.is_empty() { "Scheduled" } else { "Arrived" }.to_string(),
+                    request_date: day(offset - 2),
+                    is_stat6: false,
+                },
+                cisco_ids,
+            });
+        }
+
+        let shipments = [
+            ("L0001", -1, "06:00", ShipmentStatus::NotStarted, "", "", "", "", ""),
+            ("L0002", 0, "07:30", ShipmentStatus::Picking, "jsmith", "07:05", "", "", ""),
+            ("L0003", 0, "09:00", ShipmentStatus::Verification, "mlopez", "07:40", "08:35", "", "TRL2001"),
+            ("L0004", 0, "11:00", ShipmentStatus::ReadyToLoad, "jsmith", "08:10", "09:20", "akim", "TRL2002"),
+            ("L0005", 0, "14:00", ShipmentStatus::Loading, "mlopez", "09:00", "10:15", "akim", "TRL2003"),
+            ("L0006", 1, "08:00", ShipmentStatus::NotStarted, "", "", "", "", ""),
+        ];
+        for (n, (load_id, offset, schedule_time, status, picker, pick_start, pick_finish, verified_by, trailer_num)) in shipments.into_iter().enumerate() {
+            self.shipment_lines.insert(load_id.to_string(), (0..3).map(|i| ShipmentLine {
+                item: format!("{}-{:03}", 8200 + n, i * 5),
+                quantity: ((n + 1) * (i + 1) * 24) as u32,
+                ip: format!("IP{:02}", i + 1),
+            }).collect());
+            self.shipments.push(Shipment {
+                schedule_date: day(offset),
+                schedule_time: schedule_time.parse().expect("seeded times are valid"),
+                arrival_time: at(offset, if trailer_num.is_empty() { "" } else { "06:30" }),
+                depart_time: Timestamp::default(),
+                dock: if n % 2 == 0 { "uuu" } else { "73y" }.to_string(),
+                door: if trailer_num.is_empty() { "".to_string() } else { format!("{}", 20 + n) },
+                load_id: load_id.to_string(),
+                load_num: format!("{}", 5000 + n),
+                status,
+                picker: picker.to_string(),
+                pick_start_time: at(offset, pick_start),
+                verified_by: verified_by.to_string(),
+                trailer_num: trailer_num.to_string(),
+                pick_finish_time: at(offset, pick_finish),
+                is_hold: false,
+                seal: "".to_string(),
+            });
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use crate::TrailerResponse;
+use crate::door_board::same_door;
+use crate::time::{CalendarDate, TimeOfDay};
+
+// How far ahead to look for an open slot before giving up
+pub const SEARCH_DAYS: i64 = 14;
+
+// How many trailers receiving can take an hour, and when it can't take any. Comes from config.
+// A limit of 0 isn't enforced.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct CapacityRules {
+    // Across every plant and door
+    #[serde(default)]
+    pub per_hour: u32,
+    #[serde(default)]
+    pub plants: Vec<PlantCapacity>,
+    #[serde(default)]
+    pub door_groups: Vec<DoorGroup>,
+    #[serde(default)]
+    pub blackouts: Vec<Blackout>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlantCapacity {
+    // Plant code, e.g. "AR"
+    pub plant: String,
+    pub per_hour: u32,
+}
+
+// Doors that share a crew or a yard lane, so they fill up together
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DoorGroup {
+    pub name: String,
+    pub doors: Vec<String>,
+    pub per_hour: u32,
+}
+
+// A time nothing can be scheduled, e.g. shift change, a holiday or Sundays
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Blackout {
+    #[serde(default)]
+    pub reason: String,
+    // One day only. Empty means it repeats.
+    #[serde(default)]
+    pub date: CalendarDate,
+    // Which days it repeats on, e.g. ["Sat", "Sun"]. Empty means every day.
+    #[serde(default)]
+    pub weekdays: Vec<String>,
+    // Empty start and end cover the whole day
+    #[serde(default)]
+    pub start: TimeOfDay,
+    #[serde(default)]
+    pub end: TimeOfDay,
+    // Only for this plant. Empty means every plant.
+    #[serde(default)]
+    pub plant: String,
+}
+
+impl Blackout {
+    pub fn covers(&self, appointment: &Appointment) -> bool {
+        let date = appointment.at.date();
+        let time = appointment.at.time();
+        let on_day = match self.date.0 {
+            Some(day) => day == date,
+            None => self.weekdays.is_empty() || self.weekdays.iter().any(|day| day.parse::<Weekday>().is_ok_and(|day| day == date.weekday())),
+        };
+        let in_hours = self.start.0.is_none_or(|start| start <= time) && self.end.0.is_none_or(|end| time < end);
+        let for_plant = self.plant.is_empty() || appointment.plants.contains(&self.plant);
+        on_day && in_hours && for_plant
+    }
+}
+
+// One trailer's claim on an hour of receiving
+#[derive(Clone, Debug, PartialEq)]
+pub struct Appointment {
+    pub trailer_id: String,
+    pub at: NaiveDateTime,
+    pub door: String,
+    // Plant codes for the trailer's Cisco locations
+    pub plants: Vec<String>,
+}
+
+impl Appointment {
+    // Nothing for a trailer without a scheduled date. No time counts as the start of the day.
+    pub fn for_trailer(trailer: &TrailerResponse, plants: Vec<String>) -> Option<Appointment> {
+        let date = trailer.schedule.schedule_date.0?;
+        Some(Appointment {
+            trailer_id: trailer.trailer_id.clone(),
+            at: date.and_time(trailer.schedule.schedule_time.0.unwrap_or(NaiveTime::MIN)),
+            door: trailer.schedule.door_number.trim().to_string(),
+            plants,
+        })
+    }
+
+    pub fn date(&self) -> CalendarDate {
+        CalendarDate(Some(self.at.date()))
+    }
+
+    pub fn time(&self) -> TimeOfDay {
+        TimeOfDay(Some(self.at.time()))
+    }
+
+    fn same_hour(&self, other: &Appointment) -> bool {
+        self.at.date() == other.at.date() && self.at.hour() == other.at.hour()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    Blackout(String),
+    Site { booked: usize, limit: u32 },
+    Plant { plant: String, booked: usize, limit: u32 },
+    DoorGroup { group: String, booked: usize, limit: u32 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Blackout(reason) if reason.is_empty() => write!(f, "Receiving is closed then"),
+            Violation::Blackout(reason) => write!(f, "Receiving is closed then: {}", reason),
+            Violation::Site { booked, limit } => write!(f, "{} of {} slots that hour are already booked", booked, limit),
+            Violation::Plant { plant, booked, limit } => write!(f, "{} of {} slots that hour are already booked for {}", booked, limit, plant),
+            Violation::DoorGroup { group, booked, limit } => write!(f, "{} of {} slots that hour are already booked on {}", booked, limit, group),
+        }
+    }
+}
+
+impl CapacityRules {
+    pub fn is_empty(&self) -> bool {
+        *self == CapacityRules::default()
+    }
+
+    // Everything wrong with booking the candidate alongside what's already booked. The
+    // candidate's own existing booking, if it's being moved, doesn't count against it.
+    pub fn check(&self, booked: &[Appointment], candidate: &Appointment) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = self.blackouts.iter()
+            .filter(|blackout| blackout.covers(candidate))
+            .map(|blackout| Violation::Blackout(blackout.reason.clone()))
+            .collect();
+
+        let same_hour: Vec<&Appointment> = booked.iter()
+            .filter(|other| other.trailer_id != candidate.trailer_id && other.same_hour(candidate))
+            .collect();
+
+        if self.per_hour > 0 && same_hour.len() >= self.per_hour as usize {
+            violations.push(Violation::Site { booked: same_hour.len(), limit: self.per_hour });
+        }
+        for rule in self.plants.iter().filter(|rule| rule.per_hour > 0 && candidate.plants.contains(&rule.plant)) {
+            let count = same_hour.iter().filter(|other| other.plants.contains(&rule.plant)).count();
+            if count >= rule.per_hour as usize {
+                violations.push(Violation::Plant { plant: rule.plant.clone(), booked: count, limit: rule.per_hour });
+            }
+        }
+        let in_group = |group: &DoorGroup, door: &str| group.doors.iter().any(|d| same_door(d, door));
+        for group in self.door_groups.iter().filter(|group| group.per_hour > 0 && in_group(group, &candidate.door)) {
+            let count = same_hour.iter().filter(|other| in_group(group, &other.door)).count();
+            if count >= group.per_hour as usize {
+                violations.push(Violation::DoorGroup { group: group.name.clone(), booked: count, limit: group.per_hour });
+            }
+        }
+        violations
+    }
+
+    // The first slot from the candidate's time on that passes every rule: its own time if
+    // that's fine, otherwise the top of each hour after it
+    pub fn next_open(&self, booked: &[Appointment], candidate: &Appointment) -> Option<Appointment> {
+        let first_hour = candidate.at.date().and_hms_opt(candidate.at.hour(), 0, 0)?;
+        std::iter::once(candidate.at)
+            .chain((1..SEARCH_DAYS * 24).map(|hours| first_hour + Duration::hours(hours)))
+            .map(|at| Appointment { at, ..candidate.clone() })
+            .find(|slot| self.check(booked, slot).is_empty())
+    }
+}
+
+// Where one of a batch of trailers ended up
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub wanted: Appointment,
+    // Where it goes, or why it can't go where it was wanted
+    pub placed: Result<Appointment, Vec<Violation>>,
+}
+
+impl CapacityRules {
+    // Books a batch in order, each one counting against the ones placed before it. A trailer
+    // that doesn't fit either moves to the next open slot or is left out.
+    pub fn place(&self, booked: &[Appointment], wanted: Vec<Appointment>, move_when_full: bool) -> Vec<Placement> {
+        let mut booked = booked.to_vec();
+        wanted.into_iter().map(|wanted| {
+            booked.retain(|other| other.trailer_id != wanted.trailer_id);
+            let violations = self.check(&booked, &wanted);
+            let placed = if violations.is_empty() {
+                Ok(wanted.clone())
+            } else if move_when_full {
+                self.next_open(&booked, &wanted).ok_or(violations)
+            } else {
+                Err(violations)
+            };
+            if let Ok(slot) = &placed {
+                booked.push(slot.clone());
+            }
+            Placement { wanted, placed }
+        }).collect()
+    }
+}
+
+// The last day a search starting on `from` can land on, for loading what to check against
+pub fn search_end(from: NaiveDate) -> NaiveDate {
+    from + Duration::days(SEARCH_DAYS)
+}
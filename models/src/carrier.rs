@@ -0,0 +1,128 @@
+// The carrier directory: one entry per SCAC with who to contact, what to default a new
+// appointment to and what the carrier charges in demurrage. Schedules still store the SCAC and
+// email as plain strings, the directory is what they're checked against and filled in from.
+use serde::{Deserialize, Serialize};
+use crate::demurrage::{CarrierRate, DemurrageRates};
+use crate::time::TimeOfDay;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Carrier {
+    #[serde(rename = "SCAC")]
+    pub scac: String,
+    pub name: String,
+    // Where appointment confirmations go, the first being the main dispatch address
+    #[serde(default)]
+    pub dispatch_emails: Vec<String>,
+    #[serde(default)]
+    pub phone: String,
+    // Filled into a schedule that doesn't have its own yet
+    #[serde(default)]
+    pub default_time: TimeOfDay,
+    #[serde(default)]
+    pub default_door: String,
+    // Overrides the demurrage rate in config.json when set
+    #[serde(default)]
+    pub daily_rate: Option<f64>,
+}
+
+// Which carrier to remove
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct CarrierRef {
+    #[serde(rename = "SCAC")]
+    pub scac: String,
+}
+
+// SCACs are two to four letters, and people type them in any case
+pub fn normalize_scac(scac: &str) -> String {
+    scac.trim().to_uppercase()
+}
+
+pub fn scac_error(scac: &str) -> Option<String> {
+    let scac = normalize_scac(scac);
+    if !(2..=4).contains(&scac.len()) || !scac.chars().all(|c| c.is_ascii_uppercase()) {
+        return Some(format!("{:?} isn't a SCAC, they're 2 to 4 letters", scac));
+    }
+    None
+}
+
+impl Carrier {
+    // What goes in a schedule's contact email, every dispatch address
+    pub fn contact_email(&self) -> String {
+        self.dispatch_emails.join(", ")
+    }
+
+    // Anything that stops the carrier being saved
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = scac_error(&self.scac).into_iter().collect();
+        if self.name.trim().is_empty() {
+            errors.push("No carrier name".to_string());
+        }
+        errors.extend(self.dispatch_emails.iter()
+            .filter(|email| !email.contains('@'))
+            .map(|email| format!("{:?} isn't an email address", email)));
+        if self.daily_rate.is_some_and(|rate| rate < 0.0) {
+            errors.push("The daily rate can't be negative".to_string());
+        }
+        errors
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(transparent)]
+pub struct CarrierDirectory {
+    pub carriers: Vec<Carrier>,
+}
+
+impl CarrierDirectory {
+    pub fn new(mut carriers: Vec<Carrier>) -> Self {
+        carriers.sort_by(|a, b| a.scac.cmp(&b.scac));
+        CarrierDirectory { carriers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.carriers.is_empty()
+    }
+
+    pub fn by_scac(&self, scac: &str) -> Option<&Carrier> {
+        let scac = normalize_scac(scac);
+        self.carriers.iter().find(|carrier| carrier.scac == scac)
+    }
+
+    // What the SCAC box suggests: SCACs starting with what's typed, then names containing it
+    pub fn suggest(&self, typed: &str) -> Vec<&Carrier> {
+        let typed = typed.trim().to_lowercase();
+        let mut found: Vec<&Carrier> = self.carriers.iter()
+            .filter(|carrier| carrier.scac.to_lowercase().starts_with(&typed))
+            .collect();
+        found.extend(self.carriers.iter()
+            .filter(|carrier| !carrier.scac.to_lowercase().starts_with(&typed) && carrier.name.to_lowercase().contains(&typed)));
+        found
+    }
+
+    // Adds the carrier, or replaces the one with the same SCAC
+    pub fn upsert(&mut self, mut carrier: Carrier) {
+        carrier.scac = normalize_scac(&carrier.scac);
+        self.carriers.retain(|other| other.scac != carrier.scac);
+        self.carriers.push(carrier);
+        self.carriers.sort_by(|a, b| a.scac.cmp(&b.scac));
+    }
+
+    pub fn remove(&mut self, scac: &str) {
+        let scac = normalize_scac(scac);
+        self.carriers.retain(|carrier| carrier.scac != scac);
+    }
+
+    // The config's rates with the directory's on top, for anything that works out demurrage
+    pub fn demurrage_rates(&self, base: &DemurrageRates) -> DemurrageRates {
+        let mut rates = base.clone();
+        for carrier in &self.carriers {
+            if let Some(daily_rate) = carrier.daily_rate {
+                rates.carriers.retain(|rate| !rate.scac.eq_ignore_ascii_case(&carrier.scac));
+                rates.carriers.push(CarrierRate { scac: carrier.scac.clone(), daily_rate });
+            }
+        }
+        rates
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use crate::TrailerResponse;
+use crate::time::CalendarDate;
+
+// What carriers charge per day for a trailer kept past its last free date. Comes from config,
+// since rates are renegotiated far more often than the app is released.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DemurrageRates {
+    // For carriers without a rate of their own
+    #[serde(default)]
+    pub default_rate: f64,
+    #[serde(default)]
+    pub carriers: Vec<CarrierRate>,
+    // How close to its last free date an unarrived trailer has to be to count as at risk
+    #[serde(default = "default_warning_days")]
+    pub warning_days: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CarrierRate {
+    pub scac: String,
+    pub daily_rate: f64,
+}
+
+pub const DEFAULT_WARNING_DAYS: i64 = 3;
+
+fn default_warning_days() -> i64 {
+    DEFAULT_WARNING_DAYS
+}
+
+impl Default for DemurrageRates {
+    fn default() -> Self {
+        DemurrageRates {
+            default_rate: 0.0,
+            carriers: vec![],
+            warning_days: DEFAULT_WARNING_DAYS,
+        }
+    }
+}
+
+impl DemurrageRates {
+    pub fn is_empty(&self) -> bool {
+        self.default_rate == 0.0 && self.carriers.is_empty()
+    }
+
+    // SCACs are typed in by hand, so case doesn't matter
+    pub fn daily_rate(&self, scac: &str) -> f64 {
+        self.carriers.iter()
+            .find(|carrier| carrier.scac.eq_ignore_ascii_case(scac.trim()))
+            .map_or(self.default_rate, |carrier| carrier.daily_rate)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LfdStatus {
+    // Plenty of free time left
+    Clear,
+    // Inside the warning window
+    Approaching,
+    // Last free day is today
+    DueToday,
+    // Charges are running
+    Overdue,
+}
+
+// Where one unarrived trailer stands against its last free date
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exposure {
+    // Days left before charges start, negative once they have
+    pub days_left: i64,
+    pub daily_rate: f64,
+    // Charged so far, one daily rate for every day past the last free date
+    pub accrued: f64,
+    // What it will have cost by the time it's here, going by its scheduled date. A trailer with
+    // no date, or one already late for it, is taken as arriving today.
+    pub forecast: f64,
+}
+
+impl Exposure {
+    // Nothing for trailers that have arrived or have no last free date
+    pub fn for_trailer(trailer: &TrailerResponse, rates: &DemurrageRates, today: CalendarDate) -> Option<Exposure> {
+        let schedule = &trailer.schedule;
+        if !schedule.arrival_time.is_empty() {
+            return None;
+        }
+        let days_left = today.days_until(schedule.last_free_date)?;
+        let daily_rate = rates.daily_rate(&schedule.carrier_code);
+        let expected = schedule.schedule_date.max(today);
+        let days_over_at_arrival = schedule.last_free_date.days_until(expected)?;
+        Some(Exposure {
+            days_left,
+            daily_rate,
+            accrued: (-days_left).max(0) as f64 * daily_rate,
+            forecast: days_over_at_arrival.max(0) as f64 * daily_rate,
+        })
+    }
+
+    pub fn status(&self, rates: &DemurrageRates) -> LfdStatus {
+        match self.days_left {
+            days if days < 0 => LfdStatus::Overdue,
+            0 => LfdStatus::DueToday,
+            days if days <= rates.warning_days => LfdStatus::Approaching,
+            _ => LfdStatus::Clear,
+        }
+    }
+
+    // Close to or past its last free date, or scheduled to arrive after it
+    pub fn is_at_risk(&self, rates: &DemurrageRates) -> bool {
+        self.status(rates) != LfdStatus::Clear || self.forecast > 0.0
+    }
+}
+
+// Unarrived trailers that are costing or about to cost money, most urgent first, then the
+// most expensive
+pub fn at_risk<'a>(trailers: &'a [TrailerResponse], rates: &DemurrageRates, today: CalendarDate) -> Vec<(&'a TrailerResponse, Exposure)> {
+    let mut at_risk: Vec<(&TrailerResponse, Exposure)> = trailers.iter()
+        .filter_map(|trailer| Exposure::for_trailer(trailer, rates, today).map(|exposure| (trailer, exposure)))
+        .filter(|(_, exposure)| exposure.is_at_risk(rates))
+        .collect();
+    at_risk.sort_by(|(_, a), (_, b)| {
+        a.days_left.cmp(&b.days_left).then(b.forecast.total_cmp(&a.forecast))
+    });
+    at_risk
+}
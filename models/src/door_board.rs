@@ -0,0 +1,179 @@
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use crate::{Shipment, TrailerResponse};
+use crate::shipment_status::ShipmentStatus;
+use crate::time::{CalendarDate, TimeOfDay, Timestamp};
+
+// How long a door is held for a trailer or load that hasn't said when it'll be done. Inbound
+// trailers never do, so this is also how long an arrived trailer is taken to sit on its door.
+pub const DEFAULT_SLOT_MINUTES: i64 = 120;
+
+// What a door is booked for. Inbound doors come from Schedule.DoorNumber, outbound from
+// Shipment.Door, and this is the one place the two are looked at together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Occupant {
+    Inbound { trailer_id: String },
+    Outbound { load_id: String, trailer_num: String },
+}
+
+impl Occupant {
+    pub fn direction(&self) -> &'static str {
+        match self {
+            Occupant::Inbound { .. } => "Inbound",
+            Occupant::Outbound { .. } => "Outbound",
+        }
+    }
+
+    // e.g. "Trailer 53120" or "Load ARM131LY_05012024_1"
+    pub fn label(&self) -> String {
+        match self {
+            Occupant::Inbound { trailer_id } => format!("Trailer {}", trailer_id),
+            Occupant::Outbound { load_id, trailer_num } if trailer_num.is_empty() => format!("Load {}", load_id),
+            Occupant::Outbound { load_id, trailer_num } => format!("Load {} ({})", load_id, trailer_num),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DoorBooking {
+    pub door: String,
+    pub occupant: Occupant,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    // The trailer is physically at the door, not just expected
+    pub on_door: bool,
+}
+
+// Doors are typed in by hand, so " 07" and "7" are the same door
+pub fn same_door(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    match (a.parse::<u32>(), b.parse::<u32>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+fn scheduled_at(date: CalendarDate, time: TimeOfDay) -> Option<NaiveDateTime> {
+    Some(date.0?.and_time(time.0.unwrap_or(NaiveTime::MIN)))
+}
+
+impl DoorBooking {
+    // Nothing for a trailer with no door, or with neither an arrival nor a scheduled date. An
+    // old clock-only arrival says nothing about which day the door was used, so it's left off.
+    pub fn for_trailer(trailer: &TrailerResponse, slot: Duration, now: NaiveDateTime) -> Option<DoorBooking> {
+        let schedule = &trailer.schedule;
+        if schedule.door_number.trim().is_empty() {
+            return None;
+        }
+        let start = match schedule.arrival_time {
+            Timestamp::Empty => scheduled_at(schedule.schedule_date, schedule.schedule_time)?,
+            arrival => arrival.at()?,
+        };
+        let end = start + slot;
+        Some(DoorBooking {
+            door: schedule.door_number.trim().to_string(),
+            occupant: Occupant::Inbound { trailer_id: trailer.trailer_id.clone() },
+            start,
+            end,
+            on_door: !schedule.arrival_time.is_empty() && start <= now && now < end,
+        })
+    }
+
+    // A load holds its door from when its trailer arrives, or is due, until it departs
+    pub fn for_shipment(shipment: &Shipment, slot: Duration, now: NaiveDateTime) -> Option<DoorBooking> {
+        if shipment.door.trim().is_empty() {
+            return None;
+        }
+        let start = match shipment.arrival_time {
+            Timestamp::Empty => scheduled_at(shipment.schedule_date, shipment.schedule_time)?,
+            arrival => arrival.at()?,
+        };
+        let on_door = !shipment.arrival_time.is_empty() && shipment.depart_time.is_empty() && shipment.status != ShipmentStatus::Complete;
+        let end = match shipment.depart_time.at() {
+            Some(depart) => depart,
+            // Still loading past its slot, so it keeps the door until it goes
+            None if on_door => (start + slot).max(now + Duration::minutes(1)),
+            None => start + slot,
+        };
+        Some(DoorBooking {
+            door: shipment.door.trim().to_string(),
+            occupant: Occupant::Outbound { load_id: shipment.load_id.clone(), trailer_num: shipment.trailer_num.clone() },
+            start,
+            end,
+            on_door,
+        })
+    }
+
+    pub fn overlaps(&self, other: &DoorBooking) -> bool {
+        same_door(&self.door, &other.door) && self.start < other.end && other.start < self.end
+    }
+}
+
+// Every door booking we know about, inbound and outbound
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct DoorBoard {
+    pub bookings: Vec<DoorBooking>,
+}
+
+impl DoorBoard {
+    pub fn new(trailers: &[TrailerResponse], shipments: &[Shipment], slot: Duration, now: NaiveDateTime) -> DoorBoard {
+        let mut bookings: Vec<DoorBooking> = trailers.iter()
+            .filter_map(|trailer| DoorBooking::for_trailer(trailer, slot, now))
+            .chain(shipments.iter().filter_map(|shipment| DoorBooking::for_shipment(shipment, slot, now)))
+            .collect();
+        bookings.sort_by_key(|booking| booking.start);
+        DoorBoard { bookings }
+    }
+
+    // Bookings on one door, earliest first
+    pub fn for_door(&self, door: &str) -> Vec<&DoorBooking> {
+        self.bookings.iter().filter(|booking| same_door(&booking.door, door)).collect()
+    }
+
+    // Whatever is at the door now: a trailer that's there, or else a booking running now
+    pub fn current(&self, door: &str, now: NaiveDateTime) -> Option<&DoorBooking> {
+        let bookings = self.for_door(door);
+        bookings.iter().find(|booking| booking.on_door)
+            .or_else(|| bookings.iter().find(|booking| booking.start <= now && now < booking.end))
+            .copied()
+    }
+
+    // The next booking to start after now
+    pub fn next(&self, door: &str, now: NaiveDateTime) -> Option<&DoorBooking> {
+        self.for_door(door).into_iter().find(|booking| booking.start > now)
+    }
+
+    // Other bookings that would share the door with this one. A booking never conflicts with
+    // an older version of itself.
+    pub fn conflicts(&self, candidate: &DoorBooking) -> Vec<&DoorBooking> {
+        self.bookings.iter()
+            .filter(|booking| booking.occupant != candidate.occupant && booking.overlaps(candidate))
+            .collect()
+    }
+
+    // Pairs of bookings already sharing a door, each pair once
+    pub fn double_booked(&self) -> Vec<(&DoorBooking, &DoorBooking)> {
+        self.bookings.iter().enumerate()
+            .flat_map(|(i, a)| self.bookings[i + 1..].iter().filter(move |b| a.overlaps(b)).map(move |b| (a, b)))
+            .collect()
+    }
+
+    // Every door with a booking, numbered doors in number order
+    pub fn doors(&self) -> Vec<String> {
+        let mut doors: Vec<String> = vec![];
+        for booking in &self.bookings {
+            if !doors.iter().any(|door| same_door(door, &booking.door)) {
+                doors.push(booking.door.clone());
+            }
+        }
+        sort_doors(&mut doors);
+        doors
+    }
+}
+
+pub fn sort_doors(doors: &mut [String]) {
+    doors.sort_by(|a, b| {
+        a.trim().parse::<u32>().ok().map_or(u32::MAX, |n| n)
+            .cmp(&b.trim().parse::<u32>().ok().map_or(u32::MAX, |n| n))
+            .then(a.cmp(b))
+    });
+}
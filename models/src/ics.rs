@@ -0,0 +1,149 @@
+// iCalendar (RFC 5545) appointment confirmations for carriers. A trailer is always the same
+// event, so a rescheduled or cancelled appointment replaces the one already in the carrier's
+// calendar instead of showing up next to it.
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+pub const PRODID: &str = "-//Dock Scheduler//Appointments//EN";
+
+const UID_DOMAIN: &str = "dock-scheduler";
+
+// 2024-01-01T00:00:00Z, where SEQUENCE counts from
+const SEQUENCE_EPOCH: i64 = 1_704_067_200;
+
+// Lines longer than this many octets have to be folded
+const LINE_LIMIT: usize = 75;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IcsAppointment {
+    pub trailer_id: String,
+    // Local time at the plant. Written without a time zone so it reads the same wherever the
+    // carrier opens it.
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub door: String,
+    pub carrier_code: String,
+    // Plant names, e.g. "ARLINGTON"
+    pub plant: String,
+    pub address: String,
+    pub sequence: u32,
+    pub cancelled: bool,
+}
+
+impl IcsAppointment {
+    pub fn uid(&self) -> String {
+        uid(&self.trailer_id)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Trailer {} delivery", self.trailer_id);
+        if !self.plant.is_empty() {
+            summary = format!("{} to {}", summary, self.plant);
+        }
+        if !self.door.is_empty() {
+            summary = format!("{}, door {}", summary, self.door);
+        }
+        if self.cancelled { format!("Cancelled: {}", summary) } else { summary }
+    }
+
+    fn description(&self) -> String {
+        let mut lines = vec![format!("Trailer: {}", self.trailer_id)];
+        if !self.carrier_code.is_empty() {
+            lines.push(format!("SCAC: {}", self.carrier_code));
+        }
+        if !self.door.is_empty() {
+            lines.push(format!("Door: {}", self.door));
+        }
+        if !self.plant.is_empty() {
+            lines.push(format!("Plant: {}", self.plant));
+        }
+        if !self.address.is_empty() {
+            lines.push(format!("Address: {}", self.address));
+        }
+        if self.cancelled {
+            lines.push("This appointment has been cancelled.".to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn lines(&self, stamp: NaiveDateTime) -> Vec<String> {
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", self.uid()),
+            format!("SEQUENCE:{}", self.sequence),
+            format!("DTSTAMP:{}Z", stamp.format("%Y%m%dT%H%M%S")),
+            format!("DTSTART:{}", self.start.format("%Y%m%dT%H%M%S")),
+            format!("DTEND:{}", self.end.format("%Y%m%dT%H%M%S")),
+            format!("SUMMARY:{}", escape(&self.summary())),
+            format!("DESCRIPTION:{}", escape(&self.description())),
+        ];
+        let location = [self.plant.as_str(), self.address.as_str()].iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !location.is_empty() {
+            lines.push(format!("LOCATION:{}", escape(&location)));
+        }
+        lines.push(format!("STATUS:{}", if self.cancelled { "CANCELLED" } else { "CONFIRMED" }));
+        lines.push("TRANSP:OPAQUE".to_string());
+        lines.push("END:VEVENT".to_string());
+        lines
+    }
+}
+
+// The same for every version of a trailer's appointment
+pub fn uid(trailer_id: &str) -> String {
+    let id: String = trailer_id.trim().chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    format!("trailer-{}@{}", id, UID_DOMAIN)
+}
+
+// Seconds since the start of 2024, UTC. Anyone on any machine can send an update and it'll
+// always be newer than the last one sent, without keeping a counter anywhere. Seconds so a quick
+// second save isn't ignored as the same version, and UTC so it never goes back when the clocks do.
+pub fn sequence(at: DateTime<Utc>) -> u32 {
+    u32::try_from(at.timestamp() - SEQUENCE_EPOCH).unwrap_or(0)
+}
+
+// A whole .ics file, one event per appointment. `stamp` is when it was made, in UTC.
+pub fn calendar(appointments: &[IcsAppointment], stamp: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+    for appointment in appointments {
+        lines.extend(appointment.lines(stamp));
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("")
+}
+
+// Commas, semicolons and backslashes mean something in a TEXT value, and newlines are written \n
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Splits a line into 75-octet pieces, each after the first starting with a space, without
+// breaking a UTF-8 character in two
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
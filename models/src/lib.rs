@@ -1,34 +1,39 @@
+// Request, response and event types shared by the app, the mock server and any tool that
+// talks to the dock API. Fields are snake_case here and renamed to what the API sends.
 use serde::{Deserialize, Serialize};
 
+pub mod capacity;
+pub mod carrier;
+pub mod demurrage;
+pub mod door_board;
+pub mod ics;
+pub mod shipment_status;
+pub mod time;
+pub mod ws_event;
+
+use shipment_status::ShipmentStatus;
+use time::{CalendarDate, TimeOfDay, Timestamp};
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
-pub struct User {
-    pub username: String,
-    pub role: String,
-    pub token: String,
-    pub refresh_token: Option<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct SetArrivalTimeRequest {
-    pub TrailerID: String,
-    pub ArrivalTime: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct ArrivalMessage {
-    pub TrailerID: String,
-    pub ArrivalTime: String,
+// A trailer arriving at the dock, or its arrival being cleared with an empty time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Arrival {
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub arrival_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Stat6Message {
-    pub TailerID: String
+    // Older senders spelled this "TailerID"
+    #[serde(rename = "TrailerID", alias = "TailerID")]
+    pub trailer_id: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -38,157 +43,137 @@ pub struct LoadInfoRequest {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TodaysTrucksRequest {
-    pub date: String,
+    pub date: CalendarDate,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct UserResponse {
     pub username: String,
     pub role: String,
 }
 
-#[derive(Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
 pub struct SidParts {
-    pub Sid: Sid,
-    pub Parts: Vec<Part>,
+    pub sid: Sid,
+    pub parts: Vec<Part>,
 }
 
-#[derive(Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Sid {
-    pub CiscoID: String,
+    #[serde(rename = "CiscoID")]
+    pub cisco_id: String,
     pub id: String,
 }
 
-#[derive(Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct Part {
-    pub partNumber: String,
+    pub part_number: String,
     pub quantity: i32,
 }
 
-#[derive(Serialize, Clone, Debug)]
-pub struct ShipmentPickFinishRequest {
-    pub LoadId: String,
-    pub FinishTime: String,
-}
-
-#[derive(Serialize)]
-pub struct PickStartRequest {
-    pub StartTime: String,
-    pub LoadId: String,
-    pub Picker: String,
-}
-
-#[derive(Serialize)]
-pub struct TrailerArrivalRequest {
-    pub ArrivalTime: String,
-    pub LoadId: String,
-    pub TrailerNum: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct TrailerArrivalMessage {
-    pub ArrivalTime: String,
-    pub LoadId: String,
-    pub TrailerNum: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct PickFinishMessage {
-    pub LoadId: String,
-    pub FinishTime: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct VerifiedByRequest {
-    pub LoadId: String,
-    pub VerifiedBy: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct VerifiedByMessage {
-    pub LoadId: String,
-    pub VerifiedBy: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct StartLoadingMessage {
-    pub LoadId: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct ShipmentLoadingRequest {
-    pub LoadId: String,
+// Picking finished on a load
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PickFinish {
+    pub load_id: String,
+    pub finish_time: Timestamp,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ShipmentLoadingMessage {
-    pub LoadId: String,
+// Someone starting to pick a load
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PickStart {
+    pub load_id: String,
+    pub start_time: Timestamp,
+    pub picker: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SetShipmentDoorRequest {
-    pub LoadId: String,
-    pub Door: String,
+// The trailer a load will go out on, and when it arrived
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShipmentTrailer {
+    pub arrival_time: Timestamp,
+    pub load_id: String,
+    pub trailer_num: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SetShipmentDoorMessage {
-    pub LoadId: String,
-    pub Door: String,
+// Who checked a picked load before loading
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Verification {
+    pub load_id: String,
+    pub verified_by: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ShipmentDepartRequest {
-    pub LoadId: String,
-    pub DepartTime: String,
-    pub Seal: String,
+// Names a load for actions that need nothing else: hold, start loading, details
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShipmentRef {
+    pub load_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SetShipmentDepartMessage {
-    pub LoadId: String,
-    pub DepartTime: String,
+// The door a load is going out of
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShipmentDoor {
+    pub load_id: String,
+    pub door: String,
 }
 
-
-#[derive(Serialize, Deserialize)]
-pub struct PickStartMessage {
-    pub LoadId: String,
-    pub StartTime: String,
-    pub Picker: String,
+// A load leaving, with the seal on the trailer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Departure {
+    pub load_id: String,
+    pub depart_time: Timestamp,
+    // Departures broadcast before seals were recorded don't have one
+    #[serde(default)]
+    pub seal: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
 pub struct SidAndParts {
-    pub Sid: String,
-    pub Cisco: String,
-    pub Part: String,
-    pub Quantity: i32,
+    pub sid: String,
+    pub cisco: String,
+    pub part: String,
+    pub quantity: i32,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
 pub struct Sids {
-    pub TrailerID: String,
-    pub Sids: Vec<SidAndParts>,
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub sids: Vec<SidAndParts>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LoginResponse {
     pub token: String,
     pub refresh_token: Option<String>,
     pub user: UserResponse,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
 pub struct TrailerSchedule {
-    pub TrailerID: String,
-    pub Schedule: Schedule,
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub schedule: Schedule,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct DateRangeTruckRequest {
-    pub date1: String,
-    pub date2: String,
+    pub date1: CalendarDate,
+    pub date2: CalendarDate,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -261,23 +246,31 @@ pub struct LoadCountRequest {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
+#[serde(rename_all = "PascalCase")]
 pub struct Shipment {
-    pub ScheduleDate: String,
-    pub ScheduleTime: String,
-    pub ArrivalTime: String,
-    pub DepartTime: String,
-    pub Dock: String,
-    pub Door: String,
-    pub LoadId: String,
-    pub LoadNum: String,
-    pub Status: String,
-    pub Picker: String,
-    pub PickStartTime: String,
-    pub VerifiedBy: String,
-    pub TrailerNum: String,
-    pub PickFinishTime: String,
-    pub IsHold: bool,
-    pub Seal: String,
+    pub schedule_date: CalendarDate,
+    pub schedule_time: TimeOfDay,
+    pub arrival_time: Timestamp,
+    pub depart_time: Timestamp,
+    pub dock: String,
+    pub door: String,
+    pub load_id: String,
+    pub load_num: String,
+    pub status: ShipmentStatus,
+    pub picker: String,
+    pub pick_start_time: Timestamp,
+    pub verified_by: String,
+    pub trailer_num: String,
+    pub pick_finish_time: Timestamp,
+    pub is_hold: bool,
+    pub seal: String,
+}
+
+impl Shipment {
+    // How long picking took, once it's finished
+    pub fn pick_duration(&self) -> Option<chrono::Duration> {
+        self.pick_finish_time.since(self.pick_start_time)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -301,10 +294,11 @@ pub struct ShipmentLine {
     pub ip: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
 pub struct ShipmentLineUploadRequest {
-    pub LoadId: String,
-    pub Lines: Vec<ShipmentLine>,
+    pub load_id: String,
+    pub lines: Vec<ShipmentLine>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -492,18 +486,27 @@ pub struct RecentTrailers {
     pub date: String,
     pub time: String,
     pub scac: String,
+    // For the carrier's confirmation. Entries saved before these were kept don't have them.
+    #[serde(default)]
+    pub door: String,
+    #[serde(default)]
+    pub contact_email: String,
+    #[serde(default)]
+    pub cisco_ids: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
 pub struct SetScheduleRequest {
-    pub TrailerID: String,
-    pub ScheduleDate: String,
-    pub RequestDate: String,
-    pub CarrierCode: String,
-    pub ScheduleTime: String,
-    pub LastFreeDate: String,
-    pub ContactEmail: String,
-    pub Door: String,
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub schedule_date: CalendarDate,
+    pub request_date: CalendarDate,
+    pub carrier_code: String,
+    pub schedule_time: TimeOfDay,
+    pub last_free_date: CalendarDate,
+    pub contact_email: String,
+    pub door: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -530,28 +533,36 @@ pub struct ShipmentFormData {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
 pub struct Schedule {
-    pub ScheduleDate: String,
-    pub ScheduleTime: String,
-    pub ArrivalTime: String,
-    pub CarrierCode: String,
-    pub ContactEmail: String,
-    pub DoorNumber: String,
-    pub IsHot: bool,
-    pub LastFreeDate: String,
-    pub LoadStatus: String,
-    pub RequestDate: String,
-    pub IsStat6: bool,
+    pub schedule_date: CalendarDate,
+    pub schedule_time: TimeOfDay,
+    pub arrival_time: Timestamp,
+    pub carrier_code: String,
+    pub contact_email: String,
+    pub door_number: String,
+    pub is_hot: bool,
+    pub last_free_date: CalendarDate,
+    pub load_status: String,
+    pub request_date: CalendarDate,
+    pub is_stat6: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
 pub struct TrailerResponse {
-    pub TrailerID: String,
-    pub Schedule: Schedule,
-    pub CiscoIDs: Vec<String>,
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub schedule: Schedule,
+    #[serde(rename = "CiscoIDs")]
+    pub cisco_ids: Vec<String>,
+}
+
+// Flips a trailer between hot and normal priority
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct HotTrailer {
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct HotTrailerRequest {
-    pub TrailerID: String,
-}
\ No newline at end of file
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use crate::Shipment;
+
+// Where a load is on the dock floor. A load only ever moves forward through these, one
+// ShipmentAction at a time, so screens and the socket handlers all ask this type what may
+// happen next instead of comparing status strings.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ShipmentStatus {
+    #[default]
+    #[serde(rename = "NOT STARTED")]
+    NotStarted,
+    #[serde(rename = "PICKING")]
+    Picking,
+    #[serde(rename = "VERIFICATION")]
+    Verification,
+    #[serde(rename = "READY TO LOAD")]
+    ReadyToLoad,
+    #[serde(rename = "LOADING")]
+    Loading,
+    #[serde(rename = "COMPLETE")]
+    Complete,
+    // Anything else the backend sends, so one new or mistyped status can't sink the whole list.
+    // Nothing can be done to a load in it until it's a status we know.
+    #[serde(other, rename = "UNKNOWN")]
+    Unknown,
+}
+
+// The steps that move a load from one status to the next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShipmentAction {
+    StartPick,
+    FinishPick,
+    Verify,
+    StartLoading,
+    Depart,
+}
+
+impl ShipmentStatus {
+    pub const ALL: [ShipmentStatus; 6] = [
+        ShipmentStatus::NotStarted,
+        ShipmentStatus::Picking,
+        ShipmentStatus::Verification,
+        ShipmentStatus::ReadyToLoad,
+        ShipmentStatus::Loading,
+        ShipmentStatus::Complete,
+    ];
+
+    // The only place the legal transitions are written down
+    pub fn next(self, action: ShipmentAction) -> Option<ShipmentStatus> {
+        match (self, action) {
+            (ShipmentStatus::NotStarted, ShipmentAction::StartPick) => Some(ShipmentStatus::Picking),
+            (ShipmentStatus::Picking, ShipmentAction::FinishPick) => Some(ShipmentStatus::Verification),
+            (ShipmentStatus::Verification, ShipmentAction::Verify) => Some(ShipmentStatus::ReadyToLoad),
+            (ShipmentStatus::ReadyToLoad, ShipmentAction::StartLoading) => Some(ShipmentStatus::Loading),
+            (ShipmentStatus::Loading, ShipmentAction::Depart) => Some(ShipmentStatus::Complete),
+            _ => None,
+        }
+    }
+
+    pub fn actions(self) -> Vec<ShipmentAction> {
+        ShipmentAction::ALL.into_iter().filter(|action| self.next(*action).is_some()).collect()
+    }
+
+    // What the API sends and the tables show
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShipmentStatus::NotStarted => "NOT STARTED",
+            ShipmentStatus::Picking => "PICKING",
+            ShipmentStatus::Verification => "VERIFICATION",
+            ShipmentStatus::ReadyToLoad => "READY TO LOAD",
+            ShipmentStatus::Loading => "LOADING",
+            ShipmentStatus::Complete => "COMPLETE",
+            ShipmentStatus::Unknown => "UNKNOWN",
+        }
+    }
+
+    pub fn color(self) -> &'static str {
+        match self {
+            ShipmentStatus::NotStarted => "aqua",
+            ShipmentStatus::Picking => "yellow",
+            ShipmentStatus::Verification => "orange",
+            ShipmentStatus::ReadyToLoad => "blue",
+            ShipmentStatus::Loading => "green",
+            ShipmentStatus::Complete => "gray",
+            ShipmentStatus::Unknown => "white",
+        }
+    }
+}
+
+impl std::fmt::Display for ShipmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ShipmentAction {
+    pub const ALL: [ShipmentAction; 5] = [
+        ShipmentAction::StartPick,
+        ShipmentAction::FinishPick,
+        ShipmentAction::Verify,
+        ShipmentAction::StartLoading,
+        ShipmentAction::Depart,
+    ];
+
+    // The status a load is in once this step is done
+    pub fn target(self) -> ShipmentStatus {
+        match self {
+            ShipmentAction::StartPick => ShipmentStatus::Picking,
+            ShipmentAction::FinishPick => ShipmentStatus::Verification,
+            ShipmentAction::Verify => ShipmentStatus::ReadyToLoad,
+            ShipmentAction::StartLoading => ShipmentStatus::Loading,
+            ShipmentAction::Depart => ShipmentStatus::Complete,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShipmentAction::StartPick => "Add Picker",
+            ShipmentAction::FinishPick => "Finish Pick",
+            ShipmentAction::Verify => "Validate",
+            ShipmentAction::StartLoading => "Start Loading",
+            ShipmentAction::Depart => "Depart",
+        }
+    }
+}
+
+impl Shipment {
+    // Why the load can't take this step right now, if it can't. Beyond the status, a load
+    // can't be loaded until its trailer is at the dock.
+    pub fn check(&self, action: ShipmentAction) -> Result<ShipmentStatus, String> {
+        let next = self.status.next(action).ok_or_else(|| {
+            format!("Load {} is {}, so {} isn't allowed", self.load_id, self.status, action.label())
+        })?;
+        if action == ShipmentAction::StartLoading && self.arrival_time.is_empty() {
+            return Err(format!("Load {} has no trailer yet", self.load_id));
+        }
+        Ok(next)
+    }
+
+    // The steps the load can take next, for the screens to offer
+    pub fn available_actions(&self) -> Vec<ShipmentAction> {
+        self.status.actions().into_iter().filter(|action| self.check(*action).is_ok()).collect()
+    }
+}
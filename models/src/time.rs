@@ -0,0 +1,222 @@
+// Dates and times as the dock API sends them. Everything is still a string on the wire, with
+// an empty string for "not yet", so these wrap an Option and parse on the way in. Screens and
+// the mock server use the helpers here instead of formatting Local::now() themselves.
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_OF_DAY_FORMAT: &str = "%H:%M";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// A calendar day, e.g. a schedule date or last free date. "2024-05-01" on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate(pub Option<NaiveDate>);
+
+// A time on the schedule, with no day of its own. "07:30" on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay(pub Option<NaiveTime>);
+
+// When something happened on the floor: an arrival, a pick, a departure. Carries the date, so
+// a pick that runs past midnight still comes out the right way round.
+// "2024-05-01 23:40:12" on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Timestamp {
+    #[default]
+    Empty,
+    // Records written before timestamps carried a date only have the clock time, "10:02:11".
+    // Nothing says which day that was, so it stays without one and goes back out unchanged.
+    Clock(NaiveTime),
+    At(NaiveDateTime),
+}
+
+impl CalendarDate {
+    pub fn today() -> Self {
+        CalendarDate(Some(Local::now().date_naive()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    pub fn days_from(self, days: i64) -> Self {
+        CalendarDate(self.0.map(|date| date + Duration::days(days)))
+    }
+
+    // Whole days from this date to `later`, negative when `later` is before it
+    pub fn days_until(self, later: CalendarDate) -> Option<i64> {
+        Some((later.0? - self.0?).num_days())
+    }
+
+    // For the few places that show or export a date some other way, e.g. "%m/%d/%Y"
+    pub fn format(&self, format: &str) -> String {
+        self.0.map(|date| date.format(format).to_string()).unwrap_or_default()
+    }
+}
+
+impl TimeOfDay {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Timestamp::At(Local::now().naive_local())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Timestamp::Empty
+    }
+
+    // The date and time, for anything that needs to know the day. None for a clock-only record.
+    pub fn at(&self) -> Option<NaiveDateTime> {
+        match self {
+            Timestamp::At(stamp) => Some(*stamp),
+            _ => None,
+        }
+    }
+
+    pub fn date(&self) -> CalendarDate {
+        CalendarDate(self.at().map(|stamp| stamp.date()))
+    }
+
+    pub fn time(&self) -> Option<NaiveTime> {
+        match self {
+            Timestamp::Empty => None,
+            Timestamp::Clock(time) => Some(*time),
+            Timestamp::At(stamp) => Some(stamp.time()),
+        }
+    }
+
+    // Just the clock time, for tables where the day is already obvious
+    pub fn clock(&self) -> String {
+        self.time().map(|time| time.format("%H:%M:%S").to_string()).unwrap_or_default()
+    }
+
+    // How long after `earlier` this was, when both are set. Two clock-only times are taken to be
+    // under a day apart, which is all an old pick's start and finish ever were.
+    pub fn since(&self, earlier: Timestamp) -> Option<Duration> {
+        match (self, earlier) {
+            (Timestamp::At(later), Timestamp::At(earlier)) => Some(*later - earlier),
+            (Timestamp::Clock(later), Timestamp::Clock(earlier)) => {
+                let duration = *later - earlier;
+                Some(if duration < Duration::zero() { duration + Duration::days(1) } else { duration })
+            },
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CalendarDate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(CalendarDate(None));
+        }
+        // Request dates used to be sent month first
+        NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .or_else(|_| NaiveDate::parse_from_str(value, "%m/%d/%Y"))
+            .map(|date| CalendarDate(Some(date)))
+            .map_err(|e| format!("invalid date {:?}: {}", value, e))
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(TimeOfDay(None));
+        }
+        parse_clock(value)
+            .map(|time| TimeOfDay(Some(time)))
+            .ok_or_else(|| format!("invalid time {:?}", value))
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Timestamp::Empty);
+        }
+        if let Some(stamp) = [TIMESTAMP_FORMAT, "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        {
+            return Ok(Timestamp::At(stamp));
+        }
+        // e.g. "2024-05-01T10:00:00Z", shown in the plant's own time like everything else
+        if let Ok(stamp) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Timestamp::At(stamp.with_timezone(&Local).naive_local()));
+        }
+        parse_clock(value)
+            .map(Timestamp::Clock)
+            .ok_or_else(|| format!("invalid timestamp {:?}", value))
+    }
+}
+
+fn parse_clock(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, TIME_OF_DAY_FORMAT))
+        .ok()
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(date) => write!(f, "{}", date.format(DATE_FORMAT)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(time) => write!(f, "{}", time.format(TIME_OF_DAY_FORMAT)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timestamp::Empty => Ok(()),
+            Timestamp::Clock(time) => write!(f, "{}", time.format("%H:%M:%S")),
+            Timestamp::At(stamp) => write!(f, "{}", stamp.format(TIMESTAMP_FORMAT)),
+        }
+    }
+}
+
+// All three go over the wire as the same string they display as. One odd value from the API
+// reads as blank rather than losing the whole response it came in.
+macro_rules! wire_string {
+    ($($name:ident),*) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok(value.parse().unwrap_or_else(|error| {
+                    log::warn!("{}, read as blank", error);
+                    $name::default()
+                }))
+            }
+        }
+    )*};
+}
+
+wire_string!(CalendarDate, TimeOfDay, Timestamp);
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::*;
+use crate::shipment_status::ShipmentAction;
+
+// Bump this when a payload changes shape. Frames from another version are rejected, not guessed at.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Every message on the socket, in both directions. Serialized as
+// {"v": 1, "seq": 42, "type": "trailer_arrived", "data": {"TrailerID": "...", "ArrivalTime": "..."}}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum WsEvent {
+    HotTrailer(HotTrailer),
+    ScheduleTrailer(SetScheduleRequest),
+    TrailerArrived(Arrival),
+    NewShipment(Shipment),
+    #[serde(alias = "set_shipment_trailer")]
+    ShipmentTrailerArrival(ShipmentTrailer),
+    SetShipmentDoor(ShipmentDoor),
+    StartShipmentPick(PickStart),
+    FinishShipmentPick(PickFinish),
+    VerifiedBy(Verification),
+    ShipmentStartLoading(ShipmentRef),
+    ShipmentHold(ShipmentRef),
+    ShipmentDepart(Departure),
+    Ping,
+    Pong,
+    // Sent by the server when a client connects, carrying the last sequence number it issued
+    Hello { seq: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WsFrame {
+    pub v: u32,
+    // Stamped by the server on every broadcast, so clients can tell when they missed one.
+    // Frames we send don't have one yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    #[serde(flatten)]
+    pub event: WsEvent,
+}
+
+// A frame we couldn't use, kept so a bad sender can be tracked down without crashing every screen
+#[derive(Clone, Debug, PartialEq)]
+pub struct RejectedFrame {
+    pub reason: String,
+    pub frame: String,
+}
+
+impl WsEvent {
+    // The load and status step this event records, for events that move a load along
+    pub fn shipment_action(&self) -> Option<(&str, ShipmentAction)> {
+        match self {
+            WsEvent::StartShipmentPick(msg) => Some((&msg.load_id, ShipmentAction::StartPick)),
+            WsEvent::FinishShipmentPick(msg) => Some((&msg.load_id, ShipmentAction::FinishPick)),
+            WsEvent::VerifiedBy(msg) => Some((&msg.load_id, ShipmentAction::Verify)),
+            WsEvent::ShipmentStartLoading(msg) => Some((&msg.load_id, ShipmentAction::StartLoading)),
+            WsEvent::ShipmentDepart(msg) => Some((&msg.load_id, ShipmentAction::Depart)),
+            _ => None,
+        }
+    }
+}
+
+impl WsFrame {
+    pub fn new(event: WsEvent) -> Self {
+        WsFrame { v: PROTOCOL_VERSION, seq: None, event }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("WsFrame always serializes")
+    }
+
+    pub fn parse(txt: &str) -> Result<WsFrame, RejectedFrame> {
+        let reject = |reason: String| RejectedFrame { reason, frame: txt.to_string() };
+        let value: serde_json::Value = serde_json::from_str(txt)
+            .map_err(|e| reject(format!("Invalid JSON: {}", e)))?;
+        match value.get("v").and_then(|v| v.as_u64()) {
+            Some(v) if v == PROTOCOL_VERSION as u64 => {},
+            Some(v) => return Err(reject(format!("Unsupported protocol version {}", v))),
+            None => return Err(reject("Missing protocol version".to_string())),
+        }
+        serde_json::from_value(value).map_err(|e| reject(format!("Invalid event: {}", e)))
+    }
+}
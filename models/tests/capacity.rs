@@ -0,0 +1,89 @@
+mod common;
+
+use chrono::NaiveDateTime;
+use models::capacity::{Appointment, Blackout, CapacityRules, DoorGroup, PlantCapacity, Violation};
+use common::at;
+
+fn appointment(trailer_id: &str, when: &str, door: &str, plant: &str) -> Appointment {
+    Appointment {
+        trailer_id: trailer_id.to_string(),
+        at: at(when),
+        door: door.to_string(),
+        plants: vec![plant.to_string()],
+    }
+}
+
+fn rules() -> CapacityRules {
+    CapacityRules {
+        per_hour: 3,
+        plants: vec![PlantCapacity { plant: "AR".to_string(), per_hour: 2 }],
+        door_groups: vec![DoorGroup { name: "North".to_string(), doors: vec!["1".to_string(), "2".to_string()], per_hour: 1 }],
+        blackouts: vec![
+            Blackout { reason: "Shift change".to_string(), start: "14:00".parse().unwrap(), end: "15:00".parse().unwrap(), ..Default::default() },
+            Blackout { reason: "Closed".to_string(), weekdays: vec!["Sun".to_string()], ..Default::default() },
+        ],
+    }
+}
+
+#[test]
+fn no_rules_means_anything_goes() {
+    let booked: Vec<Appointment> = (0..20).map(|i| appointment(&format!("T{}", i), "2024-05-01 09:00", "1", "AR")).collect();
+    assert!(CapacityRules::default().check(&booked, &appointment("NEW", "2024-05-01 09:30", "1", "AR")).is_empty());
+}
+
+#[test]
+fn counts_the_hour_for_the_site_plant_and_door_group() {
+    let booked = vec![
+        appointment("T1", "2024-05-01 09:00", "1", "AR"),
+        appointment("T2", "2024-05-01 09:45", "7", "AR"),
+        appointment("T3", "2024-05-01 10:00", "8", "40"),
+    ];
+    let violations = rules().check(&booked, &appointment("NEW", "2024-05-01 09:15", "2", "AR"));
+    assert_eq!(violations, vec![
+        Violation::Plant { plant: "AR".to_string(), booked: 2, limit: 2 },
+        Violation::DoorGroup { group: "North".to_string(), booked: 1, limit: 1 },
+    ]);
+    assert!(rules().check(&booked, &appointment("NEW", "2024-05-01 09:15", "9", "40")).is_empty());
+}
+
+#[test]
+fn moving_a_trailer_does_not_count_its_old_slot() {
+    let booked = vec![appointment("T1", "2024-05-01 09:00", "1", "AR")];
+    assert!(rules().check(&booked, &appointment("T1", "2024-05-01 09:30", "1", "AR")).is_empty());
+}
+
+#[test]
+fn blackouts_by_time_and_weekday() {
+    assert_eq!(rules().check(&[], &appointment("NEW", "2024-05-01 14:30", "9", "40")), vec![Violation::Blackout("Shift change".to_string())]);
+    // 2024-05-05 was a Sunday
+    assert_eq!(rules().check(&[], &appointment("NEW", "2024-05-05 09:00", "9", "40")), vec![Violation::Blackout("Closed".to_string())]);
+}
+
+#[test]
+fn suggests_the_next_open_hour() {
+    let booked = vec![
+        appointment("T1", "2024-05-01 13:00", "1", "AR"),
+        appointment("T2", "2024-05-01 13:10", "7", "AR"),
+    ];
+    let wanted = appointment("NEW", "2024-05-01 13:20", "9", "AR");
+    assert_eq!(rules().next_open(&booked, &wanted).unwrap().at, at("2024-05-01 15:00"));
+
+    let free = appointment("NEW", "2024-05-01 11:20", "9", "AR");
+    assert_eq!(rules().next_open(&booked, &free).unwrap().at, free.at);
+}
+
+#[test]
+fn places_a_batch_against_itself() {
+    let rules = CapacityRules { per_hour: 2, ..Default::default() };
+    let wanted: Vec<Appointment> = ["A", "B", "C"].iter()
+        .map(|trailer_id| appointment(trailer_id, "2024-05-01 09:00", "9", "40"))
+        .collect();
+
+    let moved = rules.place(&[], wanted.clone(), true);
+    let times: Vec<NaiveDateTime> = moved.iter().map(|placement| placement.placed.as_ref().unwrap().at).collect();
+    assert_eq!(times, vec![at("2024-05-01 09:00"), at("2024-05-01 09:00"), at("2024-05-01 10:00")]);
+
+    let left_out = rules.place(&[], wanted, false);
+    assert!(left_out[1].placed.is_ok());
+    assert_eq!(left_out[2].placed, Err(vec![Violation::Site { booked: 2, limit: 2 }]));
+}
@@ -0,0 +1,83 @@
+use models::carrier::{normalize_scac, scac_error, Carrier, CarrierDirectory};
+use models::demurrage::{CarrierRate, DemurrageRates};
+
+fn carrier(scac: &str, name: &str, emails: &[&str]) -> Carrier {
+    Carrier {
+        scac: scac.to_string(),
+        name: name.to_string(),
+        dispatch_emails: emails.iter().map(|email| email.to_string()).collect(),
+        ..Carrier::default()
+    }
+}
+
+#[test]
+fn scacs_are_two_to_four_letters() {
+    assert_eq!(normalize_scac(" jbht "), "JBHT");
+    assert!(scac_error("jbht").is_none());
+    assert!(scac_error("UP").is_none());
+    assert!(scac_error("J").is_some());
+    assert!(scac_error("JBHTX").is_some());
+    assert!(scac_error("JB1T").is_some());
+    assert!(scac_error("").is_some());
+}
+
+#[test]
+fn validates_before_saving() {
+    assert!(carrier("ODFL", "Old Dominion", &["dispatch@odfl.com"]).validate().is_empty());
+    let errors = carrier("OD FL", "", &["dispatch"]).validate();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    let mut negative = carrier("ODFL", "Old Dominion", &[]);
+    negative.daily_rate = Some(-1.0);
+    assert_eq!(negative.validate().len(), 1);
+}
+
+#[test]
+fn finds_and_suggests_carriers() {
+    let directory = CarrierDirectory::new(vec![
+        carrier("KNXT", "Knight Transportation", &["loads@knight.com"]),
+        carrier("JBHT", "J.B. Hunt", &["dispatch@jbhunt.com", "after-hours@jbhunt.com"]),
+        carrier("ODFL", "Old Dominion", &[]),
+    ]);
+    assert_eq!(directory.carriers[0].scac, "JBHT");
+    assert_eq!(directory.by_scac("jbht ").map(|carrier| carrier.contact_email()), Some("dispatch@jbhunt.com, after-hours@jbhunt.com".to_string()));
+    assert!(directory.by_scac("SCAC").is_none());
+
+    let scacs = |typed: &str| directory.suggest(typed).iter().map(|carrier| carrier.scac.clone()).collect::<Vec<_>>();
+    assert_eq!(scacs("k"), vec!["KNXT"]);
+    // SCAC matches first, then names
+    assert_eq!(scacs("o"), vec!["ODFL", "KNXT"]);
+    assert_eq!(scacs("hunt"), vec!["JBHT"]);
+    assert_eq!(scacs("").len(), 3);
+}
+
+#[test]
+fn upserts_by_scac() {
+    let mut directory = CarrierDirectory::new(vec![carrier("JBHT", "J.B. Hunt", &[])]);
+    directory.upsert(carrier("jbht", "JB Hunt Transport", &["dispatch@jbhunt.com"]));
+    directory.upsert(carrier("ABFS", "ABF Freight", &[]));
+    assert_eq!(directory.carriers.len(), 2);
+    assert_eq!(directory.carriers[0].scac, "ABFS");
+    assert_eq!(directory.by_scac("JBHT").map(|carrier| carrier.name.as_str()), Some("JB Hunt Transport"));
+    directory.remove("abfs");
+    assert_eq!(directory.carriers.len(), 1);
+}
+
+#[test]
+fn directory_rates_override_config() {
+    let base = DemurrageRates {
+        default_rate: 75.0,
+        carriers: vec![CarrierRate { scac: "JBHT".to_string(), daily_rate: 150.0 }, CarrierRate { scac: "ODFL".to_string(), daily_rate: 90.0 }],
+        ..DemurrageRates::default()
+    };
+    let mut jbht = carrier("JBHT", "J.B. Hunt", &[]);
+    jbht.daily_rate = Some(125.0);
+    let mut knxt = carrier("KNXT", "Knight", &[]);
+    knxt.daily_rate = Some(100.0);
+    let directory = CarrierDirectory::new(vec![jbht, knxt, carrier("ABFS", "ABF Freight", &[])]);
+
+    let rates = directory.demurrage_rates(&base);
+    assert_eq!(rates.daily_rate("jbht"), 125.0);
+    assert_eq!(rates.daily_rate("KNXT"), 100.0);
+    assert_eq!(rates.daily_rate("ODFL"), 90.0);
+    assert_eq!(rates.daily_rate("ABFS"), 75.0);
+}
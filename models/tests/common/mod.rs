@@ -0,0 +1,60 @@
+// Fixtures shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use chrono::NaiveDateTime;
+use models::{Schedule, TrailerResponse};
+use models::time::CalendarDate;
+
+pub fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+}
+
+pub fn date(value: &str) -> CalendarDate {
+    value.parse().unwrap()
+}
+
+// A trailer with nothing scheduled, for the setters below to fill in as each test needs
+pub fn trailer(trailer_id: &str) -> TrailerResponse {
+    TrailerResponse {
+        trailer_id: trailer_id.to_string(),
+        schedule: Schedule::default(),
+        cisco_ids: vec![],
+    }
+}
+
+// Values as they'd come over the wire, e.g. "2024-05-01" and "08:00"
+pub trait TrailerFixture {
+    fn door(self, door: &str) -> Self;
+    fn carrier(self, carrier_code: &str) -> Self;
+    fn last_free(self, last_free_date: &str) -> Self;
+    fn scheduled(self, schedule_date: &str, schedule_time: &str) -> Self;
+    fn arrived(self, arrival_time: &str) -> Self;
+}
+
+impl TrailerFixture for TrailerResponse {
+    fn door(mut self, door: &str) -> Self {
+        self.schedule.door_number = door.to_string();
+        self
+    }
+
+    fn carrier(mut self, carrier_code: &str) -> Self {
+        self.schedule.carrier_code = carrier_code.to_string();
+        self
+    }
+
+    fn last_free(mut self, last_free_date: &str) -> Self {
+        self.schedule.last_free_date = date(last_free_date);
+        self
+    }
+
+    fn scheduled(mut self, schedule_date: &str, schedule_time: &str) -> Self {
+        self.schedule.schedule_date = date(schedule_date);
+        self.schedule.schedule_time = schedule_time.parse().unwrap();
+        self
+    }
+
+    fn arrived(mut self, arrival_time: &str) -> Self {
+        self.schedule.arrival_time = arrival_time.parse().unwrap();
+        self
+    }
+}
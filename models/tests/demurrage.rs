@@ -0,0 +1,66 @@
+mod common;
+
+use models::demurrage::{at_risk, CarrierRate, DemurrageRates, Exposure, LfdStatus};
+use common::{date, trailer, TrailerFixture};
+
+fn rates() -> DemurrageRates {
+    DemurrageRates {
+        default_rate: 100.0,
+        carriers: vec![CarrierRate { scac: "JBHT".to_string(), daily_rate: 150.0 }],
+        warning_days: 2,
+    }
+}
+
+#[test]
+fn carrier_rate_or_default() {
+    assert_eq!(rates().daily_rate("jbht "), 150.0);
+    assert_eq!(rates().daily_rate("SWFT"), 100.0);
+}
+
+#[test]
+fn counts_days_either_side_of_the_lfd() {
+    let today = date("2024-05-10");
+    let overdue = Exposure::for_trailer(&trailer("T1").carrier("JBHT").last_free("2024-05-07"), &rates(), today).unwrap();
+    assert_eq!(overdue.days_left, -3);
+    assert_eq!(overdue.accrued, 450.0);
+    assert_eq!(overdue.forecast, 450.0);
+    assert_eq!(overdue.status(&rates()), LfdStatus::Overdue);
+
+    let due = Exposure::for_trailer(&trailer("T2").carrier("SWFT").last_free("2024-05-10"), &rates(), today).unwrap();
+    assert_eq!(due.status(&rates()), LfdStatus::DueToday);
+    assert_eq!(due.accrued, 0.0);
+
+    let clear = Exposure::for_trailer(&trailer("T3").carrier("SWFT").last_free("2024-05-20").scheduled("2024-05-12", ""), &rates(), today).unwrap();
+    assert_eq!(clear.status(&rates()), LfdStatus::Clear);
+    assert!(!clear.is_at_risk(&rates()));
+}
+
+#[test]
+fn forecasts_from_the_scheduled_date() {
+    let today = date("2024-05-10");
+    let late = Exposure::for_trailer(&trailer("T1").carrier("SWFT").last_free("2024-05-20").scheduled("2024-05-23", ""), &rates(), today).unwrap();
+    assert_eq!(late.accrued, 0.0);
+    assert_eq!(late.forecast, 300.0);
+    assert!(late.is_at_risk(&rates()));
+}
+
+#[test]
+fn arrived_or_undated_trailers_are_not_exposed() {
+    let today = date("2024-05-10");
+    assert_eq!(Exposure::for_trailer(&trailer("T1").carrier("JBHT").last_free("2024-05-01").arrived("2024-05-09 08:00:00"), &rates(), today), None);
+    assert_eq!(Exposure::for_trailer(&trailer("T2").carrier("JBHT"), &rates(), today), None);
+}
+
+#[test]
+fn ranks_most_overdue_first() {
+    let trailers = vec![
+        trailer("DUE").carrier("SWFT").last_free("2024-05-11"),
+        trailer("CLEAR").carrier("SWFT").last_free("2024-06-01"),
+        trailer("OVER").carrier("SWFT").last_free("2024-05-05"),
+        trailer("ARRIVED").carrier("SWFT").last_free("2024-05-01").arrived("2024-05-02 09:00:00"),
+    ];
+    let ranked: Vec<&str> = at_risk(&trailers, &rates(), date("2024-05-10")).iter()
+        .map(|(trailer, _)| trailer.trailer_id.as_str())
+        .collect();
+    assert_eq!(ranked, vec!["OVER", "DUE"]);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use chrono::Duration;
+use models::Shipment;
+use models::door_board::{same_door, DoorBoard, DoorBooking, Occupant};
+use models::shipment_status::ShipmentStatus;
+use common::{at, trailer, TrailerFixture};
+
+fn slot() -> Duration {
+    Duration::hours(2)
+}
+
+fn shipment(load_id: &str, door: &str, schedule_time: &str, arrival_time: &str, depart_time: &str) -> Shipment {
+    Shipment {
+        load_id: load_id.to_string(),
+        door: door.to_string(),
+        schedule_date: "2024-05-01".parse().unwrap(),
+        schedule_time: schedule_time.parse().unwrap(),
+        arrival_time: arrival_time.parse().unwrap(),
+        depart_time: depart_time.parse().unwrap(),
+        status: if depart_time.is_empty() { ShipmentStatus::Loading } else { ShipmentStatus::Complete },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn doors_match_however_they_were_typed() {
+    assert!(same_door(" 07", "7"));
+    assert!(same_door("a3", "A3"));
+    assert!(!same_door("7", "17"));
+}
+
+#[test]
+fn shows_who_is_on_the_door_and_who_is_next() {
+    let now = at("2024-05-01 09:00");
+    let trailers = vec![
+        trailer("T1").door("7").scheduled("2024-05-01", "08:00").arrived("2024-05-01 08:30:00"),
+        trailer("T2").door("7").scheduled("2024-05-01", "13:00"),
+    ];
+    let board = DoorBoard::new(&trailers, &[], slot(), now);
+    let current = board.current("07", now).unwrap();
+    assert!(current.on_door);
+    assert_eq!(current.occupant, Occupant::Inbound { trailer_id: "T1".to_string() });
+    assert_eq!(board.next("7", now).unwrap().occupant, Occupant::Inbound { trailer_id: "T2".to_string() });
+    assert!(board.double_booked().is_empty());
+}
+
+#[test]
+fn leaves_off_arrivals_with_no_date() {
+    let now = at("2024-05-01 10:05");
+    let trailers = vec![
+        trailer("OLD").door("7").scheduled("2024-04-24", "10:00").arrived("10:02:11"),
+        trailer("T1").door("7").scheduled("2024-05-01", "10:00").arrived("2024-05-01 10:01:00"),
+    ];
+    assert_eq!(DoorBooking::for_trailer(&trailers[0], slot(), now), None);
+    let board = DoorBoard::new(&trailers, &[], slot(), now);
+    assert_eq!(board.current("7", now).unwrap().occupant, Occupant::Inbound { trailer_id: "T1".to_string() });
+    assert!(board.double_booked().is_empty());
+}
+
+#[test]
+fn an_outbound_load_keeps_its_door_until_it_departs() {
+    let now = at("2024-05-01 15:00");
+    let shipments = vec![shipment("L1", "12", "08:00", "2024-05-01 08:00:00", "")];
+    let board = DoorBoard::new(&[], &shipments, slot(), now);
+    let current = board.current("12", now).unwrap();
+    assert!(current.on_door);
+    assert!(current.end > now);
+
+    let departed = vec![shipment("L1", "12", "08:00", "2024-05-01 08:00:00", "2024-05-01 09:15:00")];
+    assert_eq!(DoorBoard::new(&[], &departed, slot(), now).current("12", now), None);
+}
+
+#[test]
+fn catches_inbound_and_outbound_on_the_same_door() {
+    let now = at("2024-05-01 06:00");
+    let trailers = vec![trailer("T1").door("12").scheduled("2024-05-01", "09:00")];
+    let shipments = vec![shipment("L1", "12", "10:00", "", "")];
+    let board = DoorBoard::new(&trailers, &shipments, slot(), now);
+    assert_eq!(board.double_booked().len(), 1);
+
+    // Moving the trailer to later in the day clears it, and a booking never clashes with itself
+    let moved = DoorBooking::for_trailer(&trailer("T1").door("12").scheduled("2024-05-01", "12:00"), slot(), now).unwrap();
+    assert!(board.conflicts(&moved).is_empty());
+    let clash = DoorBooking::for_shipment(&shipment("L2", "12", "09:30", "", ""), slot(), now).unwrap();
+    assert_eq!(board.conflicts(&clash).len(), 2);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use models::ics::{calendar, sequence, uid, IcsAppointment};
+use common::at;
+
+fn utc(value: &str) -> DateTime<Utc> {
+    at(value).and_utc()
+}
+
+fn appointment(start: &str, cancelled: bool) -> IcsAppointment {
+    IcsAppointment {
+        trailer_id: "TRL 100".to_string(),
+        start: at(start),
+        end: at(start) + chrono::Duration::hours(2),
+        door: "12".to_string(),
+        carrier_code: "JBHT".to_string(),
+        plant: "ARLINGTON".to_string(),
+        address: "1 Main St, Arlington, TX".to_string(),
+        sequence: sequence(utc("2024-05-01 08:00")),
+        cancelled,
+    }
+}
+
+#[test]
+fn writes_one_event_per_appointment() {
+    let ics = calendar(&[appointment("2024-05-01 09:00", false)], at("2024-05-01 13:00"));
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains("UID:trailer-TRL-100@dock-scheduler\r\n"));
+    assert!(ics.contains("DTSTAMP:20240501T130000Z\r\n"));
+    assert!(ics.contains("DTSTART:20240501T090000\r\n"));
+    assert!(ics.contains("DTEND:20240501T110000\r\n"));
+    assert!(ics.contains("LOCATION:ARLINGTON\\, 1 Main St\\, Arlington\\, TX\r\n"));
+    assert!(ics.contains("STATUS:CONFIRMED\r\n"));
+    assert!(!ics.replace("\r\n", "").contains('\n'));
+}
+
+#[test]
+fn updates_keep_the_uid_and_raise_the_sequence() {
+    let first = appointment("2024-05-01 09:00", false);
+    let mut moved = appointment("2024-05-02 10:00", false);
+    moved.sequence = sequence(utc("2024-05-01 08:30"));
+    let mut cancelled = appointment("2024-05-02 10:00", true);
+    cancelled.sequence = sequence(utc("2024-05-01 09:15"));
+
+    assert_eq!(first.uid(), moved.uid());
+    assert_eq!(moved.uid(), cancelled.uid());
+    assert!(moved.sequence > first.sequence);
+    assert!(cancelled.sequence > moved.sequence);
+    assert_eq!(uid(" TRL 100 "), first.uid());
+
+    let ics = calendar(&[cancelled], at("2024-05-01 13:15"));
+    assert!(ics.contains("STATUS:CANCELLED\r\n"));
+    assert!(ics.contains("SUMMARY:Cancelled: Trailer TRL 100 delivery to ARLINGTON\\, door 12\r\n"));
+}
+
+#[test]
+fn saves_seconds_apart_are_still_newer() {
+    let saved = utc("2024-11-03 06:59");
+    assert!(sequence(saved + Duration::seconds(5)) > sequence(saved));
+    assert!(sequence(saved + Duration::seconds(1)) > sequence(saved));
+    assert_eq!(sequence(utc("2024-01-01 00:00")), 0);
+    assert_eq!(sequence(utc("2023-12-31 23:00")), 0);
+}
+
+#[test]
+fn combines_appointments_into_one_file() {
+    let mut other = appointment("2024-05-01 11:00", false);
+    other.trailer_id = "TRL200".to_string();
+    let ics = calendar(&[appointment("2024-05-01 09:00", false), other], at("2024-05-01 13:00"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert_eq!(ics.matches("BEGIN:VCALENDAR").count(), 1);
+    assert!(ics.contains("UID:trailer-TRL200@dock-scheduler\r\n"));
+}
+
+#[test]
+fn folds_long_lines_at_75_octets() {
+    let mut long = appointment("2024-05-01 09:00", false);
+    long.address = "Ünit 4, ".repeat(20);
+    let ics = calendar(&[long], at("2024-05-01 13:00"));
+    for line in ics.split("\r\n") {
+        assert!(line.len() <= 75, "{:?} is {} octets", line, line.len());
+    }
+    assert!(ics.contains("\r\n "));
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(&format!("Address: {}", "Ünit 4\\, ".repeat(20))));
+}
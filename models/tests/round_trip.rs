@@ -0,0 +1,305 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use models::*;
+use models::carrier::Carrier;
+use models::shipment_status::ShipmentStatus;
+use models::time::Timestamp;
+use models::ws_event::{WsEvent, WsFrame, PROTOCOL_VERSION};
+
+// Parses what the API sends and checks it serializes back to exactly the same JSON, so a
+// renamed field can't silently drift from the wire name
+fn round_trip<T: Serialize + DeserializeOwned>(wire: Value) -> T {
+    let parsed: T = serde_json::from_value(wire.clone()).expect("wire JSON should parse");
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), wire);
+    parsed
+}
+
+fn schedule_json() -> Value {
+    json!({
+        "ScheduleDate": "2024-05-01",
+        "ScheduleTime": "07:00",
+        "ArrivalTime": "2024-05-01 06:48:00",
+        "CarrierCode": "JBHT",
+        "ContactEmail": "dispatch@jbht.example.com",
+        "DoorNumber": "14",
+        "IsHot": true,
+        "LastFreeDate": "2024-05-04",
+        "LoadStatus": "Arrived",
+        "RequestDate": "2024-04-29",
+        "IsStat6": false
+    })
+}
+
+fn shipment_json() -> Value {
+    json!({
+        "ScheduleDate": "2024-05-01",
+        "ScheduleTime": "09:00",
+        "ArrivalTime": "2024-05-01 06:30:00",
+        "DepartTime": "",
+        "Dock": "A",
+        "Door": "22",
+        "LoadId": "L0003",
+        "LoadNum": "5002",
+        "Status": "VERIFICATION",
+        "Picker": "mlopez",
+        "PickStartTime": "2024-05-01 07:40:00",
+        "VerifiedBy": "",
+        "TrailerNum": "TRL2001",
+        "PickFinishTime": "2024-05-01 08:35:00",
+        "IsHold": false,
+        "Seal": ""
+    })
+}
+
+#[test]
+fn trailer_response() {
+    let trailer: TrailerResponse = round_trip(json!({
+        "TrailerID": "TRL1003",
+        "Schedule": schedule_json(),
+        "CiscoIDs": ["CSC0004", "CSC0005"]
+    }));
+    assert_eq!(trailer.trailer_id, "TRL1003");
+    assert_eq!(trailer.schedule.door_number, "14");
+    assert!(trailer.schedule.is_hot);
+    assert_eq!(trailer.cisco_ids.len(), 2);
+}
+
+#[test]
+fn trailer_schedule() {
+    let schedule: TrailerSchedule = round_trip(json!({ "TrailerID": "TRL1003", "Schedule": schedule_json() }));
+    assert_eq!(schedule.schedule.load_status, "Arrived");
+}
+
+#[test]
+fn shipment() {
+    let shipment: Shipment = round_trip(shipment_json());
+    assert_eq!(shipment.load_id, "L0003");
+    assert_eq!(shipment.pick_finish_time.clock(), "08:35:00");
+    assert_eq!(shipment.schedule_time.to_string(), "09:00");
+    assert_eq!(shipment.trailer_num, "TRL2001");
+}
+
+#[test]
+fn set_schedule_request() {
+    let request: SetScheduleRequest = round_trip(json!({
+        "TrailerID": "TRL1004",
+        "ScheduleDate": "2024-05-01",
+        "RequestDate": "2024-04-29",
+        "CarrierCode": "SCAC",
+        "ScheduleTime": "10:00",
+        "LastFreeDate": "2024-05-04",
+        "ContactEmail": "dispatch@scac.example.com",
+        "Door": "15"
+    }));
+    assert_eq!(request.carrier_code, "SCAC");
+}
+
+#[test]
+fn carrier() {
+    let carriers: Vec<Carrier> = round_trip(json!([{
+        "SCAC": "JBHT",
+        "Name": "J.B. Hunt",
+        "DispatchEmails": ["dispatch@jbhunt.example.com"],
+        "Phone": "800-555-0100",
+        "DefaultTime": "08:00",
+        "DefaultDoor": "12",
+        "DailyRate": 150.0
+    }, {
+        "SCAC": "ODFL",
+        "Name": "Old Dominion",
+        "DispatchEmails": [],
+        "Phone": "",
+        "DefaultTime": "",
+        "DefaultDoor": "",
+        "DailyRate": null
+    }]));
+    assert_eq!(carriers[0].daily_rate, Some(150.0));
+    assert!(carriers[1].default_time.is_empty());
+}
+
+#[test]
+fn load_info() {
+    let sid_parts: Vec<SidParts> = round_trip(json!([{
+        "Sid": { "CiscoID": "CSC0004", "id": "SID00020" },
+        "Parts": [{ "partNumber": "7102-000", "quantity": 36 }]
+    }]));
+    assert_eq!(sid_parts[0].sid.cisco_id, "CSC0004");
+    assert_eq!(sid_parts[0].parts[0].part_number, "7102-000");
+
+    let sids: Sids = round_trip(json!({
+        "TrailerID": "TRL1003",
+        "Sids": [{ "Sid": "SID00020", "Cisco": "CSC0004", "Part": "7102-000", "Quantity": 36 }]
+    }));
+    assert_eq!(sids.sids[0].quantity, 36);
+}
+
+#[test]
+fn shipment_payloads() {
+    round_trip::<Arrival>(json!({ "TrailerID": "TRL1004", "ArrivalTime": "2024-05-01 10:02:11" }));
+    round_trip::<HotTrailer>(json!({ "TrailerID": "TRL1004" }));
+    round_trip::<PickStart>(json!({ "LoadId": "L0002", "StartTime": "2024-05-01 07:05:00", "Picker": "jsmith" }));
+    round_trip::<PickFinish>(json!({ "LoadId": "L0002", "FinishTime": "2024-05-01 08:00:00" }));
+    round_trip::<ShipmentTrailer>(json!({ "ArrivalTime": "2024-05-01 06:30:00", "LoadId": "L0002", "TrailerNum": "TRL2004" }));
+    round_trip::<Verification>(json!({ "LoadId": "L0003", "VerifiedBy": "akim" }));
+    round_trip::<ShipmentRef>(json!({ "LoadId": "L0005" }));
+    round_trip::<ShipmentDoor>(json!({ "LoadId": "L0005", "Door": "24" }));
+    round_trip::<Departure>(json!({ "LoadId": "L0005", "DepartTime": "2024-05-01 15:40:00", "Seal": "S123" }));
+    round_trip::<ShipmentLineUploadRequest>(json!({
+        "LoadId": "L0005",
+        "Lines": [{ "item": "8204-000", "quantity": 120, "ip": "IP01" }]
+    }));
+}
+
+#[test]
+fn departure_without_seal() {
+    let departure: Departure = serde_json::from_value(json!({ "LoadId": "L0005", "DepartTime": "2024-05-01 15:40:00" })).unwrap();
+    assert_eq!(departure.seal, "");
+}
+
+#[test]
+fn stat6_accepts_old_spelling() {
+    let message: Stat6Message = serde_json::from_value(json!({ "TailerID": "TRL1002" })).unwrap();
+    assert_eq!(message.trailer_id, "TRL1002");
+    assert_eq!(serde_json::to_value(&message).unwrap(), json!({ "TrailerID": "TRL1002" }));
+}
+
+#[test]
+fn login_response() {
+    let response: LoginResponse = round_trip(json!({
+        "token": "abc",
+        "refresh_token": "def",
+        "user": { "username": "admin", "role": "admin" }
+    }));
+    assert_eq!(response.user.role, "admin");
+}
+
+#[test]
+fn ws_frames() {
+    let wire = json!({
+        "v": PROTOCOL_VERSION,
+        "seq": 42,
+        "type": "shipment_depart",
+        "data": { "LoadId": "L0005", "DepartTime": "2024-05-01 15:40:00", "Seal": "S123" }
+    });
+    let frame: WsFrame = round_trip(wire.clone());
+    assert_eq!(frame.seq, Some(42));
+    assert_eq!(WsFrame::parse(&wire.to_string()).unwrap(), frame);
+    match frame.event {
+        WsEvent::ShipmentDepart(departure) => assert_eq!(departure.seal, "S123"),
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let trailer: WsFrame = round_trip(json!({
+        "v": PROTOCOL_VERSION,
+        "type": "trailer_arrived",
+        "data": { "TrailerID": "TRL1004", "ArrivalTime": "2024-05-01 10:02:11" }
+    }));
+    assert_eq!(trailer.seq, None);
+
+    round_trip::<WsFrame>(json!({ "v": PROTOCOL_VERSION, "type": "new_shipment", "data": shipment_json() }));
+    round_trip::<WsFrame>(json!({ "v": PROTOCOL_VERSION, "type": "hello", "data": { "seq": 7 } }));
+    round_trip::<WsFrame>(json!({ "v": PROTOCOL_VERSION, "type": "ping" }));
+}
+
+#[test]
+fn ws_frame_accepts_old_event_name() {
+    let frame = WsFrame::parse(&json!({
+        "v": PROTOCOL_VERSION,
+        "type": "set_shipment_trailer",
+        "data": { "ArrivalTime": "2024-05-01 06:30:00", "LoadId": "L0002", "TrailerNum": "TRL2004" }
+    }).to_string()).unwrap();
+    assert!(matches!(frame.event, WsEvent::ShipmentTrailerArrival(_)));
+}
+
+#[test]
+fn ws_frame_rejects_other_versions() {
+    let rejected = WsFrame::parse(r#"{"v":2,"type":"ping"}"#).unwrap_err();
+    assert!(rejected.reason.contains("version"));
+}
+
+#[test]
+fn shipment_status_names() {
+    for status in ShipmentStatus::ALL {
+        round_trip::<ShipmentStatus>(json!(status.as_str()));
+    }
+    assert_eq!(serde_json::from_value::<ShipmentStatus>(json!("SHIPPED")).unwrap(), ShipmentStatus::Unknown);
+
+    // One load in a status we don't know doesn't lose the rest
+    let mut odd = shipment_json();
+    odd["Status"] = json!("ON HOLD AT YARD");
+    let shipments: Vec<Shipment> = serde_json::from_value(json!([shipment_json(), odd])).unwrap();
+    assert_eq!(shipments[1].status, ShipmentStatus::Unknown);
+}
+
+#[test]
+fn legacy_clock_times() {
+    // Older records carry only the clock time. No day is made up for them, and they go back out
+    // exactly as they came in.
+    let arrival: Arrival = round_trip(json!({ "TrailerID": "TRL1004", "ArrivalTime": "10:02:11" }));
+    assert_eq!(arrival.arrival_time.clock(), "10:02:11");
+    assert!(!arrival.arrival_time.is_empty());
+    assert_eq!(arrival.arrival_time.at(), None);
+    assert!(arrival.arrival_time.date().is_empty());
+
+    let schedule: SetScheduleRequest = serde_json::from_value(json!({
+        "TrailerID": "TRL1004",
+        "ScheduleDate": "2024-05-01",
+        "RequestDate": "4/29/2024",
+        "CarrierCode": "SCAC",
+        "ScheduleTime": "10:00:00",
+        "LastFreeDate": "",
+        "ContactEmail": "",
+        "Door": ""
+    })).unwrap();
+    assert_eq!(schedule.request_date.to_string(), "2024-04-29");
+    assert_eq!(schedule.schedule_time.to_string(), "10:00");
+    assert!(schedule.last_free_date.is_empty());
+}
+
+#[test]
+fn pick_across_midnight() {
+    let shipment = Shipment {
+        pick_start_time: "2024-05-01 23:40:00".parse().unwrap(),
+        pick_finish_time: "2024-05-02 00:25:00".parse().unwrap(),
+        ..Default::default()
+    };
+    assert_eq!(shipment.pick_duration().unwrap().num_minutes(), 45);
+    assert_eq!(Shipment::default().pick_duration(), None);
+
+    let legacy = Shipment {
+        pick_start_time: "23:40:00".parse().unwrap(),
+        pick_finish_time: "00:25:00".parse().unwrap(),
+        ..Default::default()
+    };
+    assert_eq!(legacy.pick_duration().unwrap().num_minutes(), 45);
+}
+
+#[test]
+fn rejects_garbage_times() {
+    assert!("25:00".parse::<Timestamp>().is_err());
+    assert!("soon".parse::<Timestamp>().is_err());
+}
+
+#[test]
+fn garbage_times_from_the_api_read_as_blank() {
+    let arrival: Arrival = serde_json::from_value(json!({ "TrailerID": "TRL1004", "ArrivalTime": "soon" })).unwrap();
+    assert!(arrival.arrival_time.is_empty());
+
+    // One bad row doesn't lose the rest of the list
+    let mut odd = schedule_json();
+    odd["ScheduleDate"] = json!("31/31/2024");
+    odd["ScheduleTime"] = json!("noonish");
+    let schedules: Vec<Schedule> = serde_json::from_value(json!([schedule_json(), odd])).unwrap();
+    assert_eq!(schedules.len(), 2);
+    assert!(schedules[1].schedule_date.is_empty());
+    assert!(schedules[1].schedule_time.is_empty());
+    assert_eq!(schedules[1].carrier_code, "JBHT");
+}
+
+#[test]
+fn reads_rfc_3339_with_an_offset() {
+    let utc: Timestamp = "2024-05-01T10:00:00Z".parse().unwrap();
+    let offset: Timestamp = "2024-05-01T05:00:00-05:00".parse().unwrap();
+    assert!(utc.at().is_some());
+    assert_eq!(utc, offset);
+}
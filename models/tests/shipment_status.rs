@@ -0,0 +1,52 @@
+use models::Shipment;
+use models::shipment_status::{ShipmentAction, ShipmentStatus};
+
+fn shipment(status: ShipmentStatus, arrival_time: &str) -> Shipment {
+    Shipment {
+        load_id: "L0001".to_string(),
+        arrival_time: arrival_time.parse().unwrap(),
+        status,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn walks_the_whole_way() {
+    let mut status = ShipmentStatus::default();
+    for action in ShipmentAction::ALL {
+        status = status.next(action).expect("each step follows the last");
+        assert_eq!(status, action.target());
+    }
+    assert_eq!(status, ShipmentStatus::Complete);
+    assert!(status.actions().is_empty());
+}
+
+#[test]
+fn one_step_at_a_time() {
+    for status in ShipmentStatus::ALL {
+        assert!(status.actions().len() <= 1, "{} offers more than one step", status);
+    }
+    assert_eq!(ShipmentStatus::NotStarted.next(ShipmentAction::Depart), None);
+    assert_eq!(ShipmentStatus::Loading.next(ShipmentAction::StartPick), None);
+    assert_eq!(ShipmentStatus::Complete.next(ShipmentAction::Depart), None);
+}
+
+#[test]
+fn nothing_to_do_in_an_unknown_status() {
+    assert!(ShipmentStatus::Unknown.actions().is_empty());
+    assert!(shipment(ShipmentStatus::Unknown, "2024-05-01 06:30:00").available_actions().is_empty());
+    assert!(shipment(ShipmentStatus::Unknown, "").check(ShipmentAction::StartPick).is_err());
+}
+
+#[test]
+fn loading_waits_for_the_trailer() {
+    assert!(shipment(ShipmentStatus::ReadyToLoad, "").available_actions().is_empty());
+    assert!(shipment(ShipmentStatus::ReadyToLoad, "").check(ShipmentAction::StartLoading).is_err());
+    assert_eq!(shipment(ShipmentStatus::ReadyToLoad, "2024-05-01 06:30:00").available_actions(), vec![ShipmentAction::StartLoading]);
+}
+
+#[test]
+fn refusal_names_the_load() {
+    let reason = shipment(ShipmentStatus::Complete, "2024-05-01 06:30:00").check(ShipmentAction::FinishPick).unwrap_err();
+    assert!(reason.contains("L0001") && reason.contains("COMPLETE"), "{}", reason);
+}
@@ -0,0 +1,381 @@
+use std::{cell::RefCell, fmt, future::Future};
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+use gloo::console::log;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::{state::{AppStateAction, AppStateContext}, user_local_storage::{load_user_from_local_storage, save_user_to_local_storage}};
+use models::*;
+use models::carrier::{Carrier, CarrierRef};
+use crate::user::User;
+
+type RefreshFuture = Shared<LocalBoxFuture<'static, Result<User, ApiError>>>;
+
+thread_local! {
+    // The refresh currently on the wire, if any. Every request that hits a 401 while it is
+    // pending awaits this same future instead of spending the refresh token again.
+    static REFRESH: RefCell<Option<RefreshFuture>> = RefCell::new(None);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    // The server rejected the token. This is the only error that ends the session.
+    Unauthorized,
+    // The request never got a response (offline, DNS, CORS, refused connection)
+    Network(String),
+    // The server answered with a non-success status other than 401
+    Server { status: u16, message: String },
+    // The server answered but the body wasn't what we expected
+    Decode(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Unauthorized => write!(f, "Session expired, please log in again"),
+            ApiError::Network(message) => write!(f, "Network error: {}", message),
+            ApiError::Server { status, message } => write!(f, "Server error {}: {}", status, message),
+            ApiError::Decode(message) => write!(f, "Unexpected response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ApiError {
+    // Logs the error and logs the user out only when the server actually said 401
+    pub fn handle(self, app_state: &AppStateContext) {
+        log!(format!("{}", self));
+        if self == ApiError::Unauthorized {
+            app_state.dispatch(AppStateAction::ClearUser);
+        }
+    }
+}
+
+// The retry behind every authorized request. A 401 can come back after some other request has
+// already refreshed, and with rotating refresh tokens the one this request started with is
+// spent by then, so it picks up the newer token instead. Only a token nobody has replaced yet
+// gets refreshed.
+async fn with_refresh<T, Sent, Refreshed>(
+    current: impl Fn() -> Result<User, ApiError>,
+    send: impl Fn(String) -> Sent,
+    refresh: impl FnOnce(User) -> Refreshed,
+) -> Result<T, ApiError>
+where
+    Sent: Future<Output = Result<T, ApiError>>,
+    Refreshed: Future<Output = Result<User, ApiError>>,
+{
+    let sent = current()?;
+    match send(sent.token.clone()).await {
+        Err(ApiError::Unauthorized) => {
+            let latest = current()?;
+            let user = if latest.token != sent.token { latest } else { refresh(latest).await? };
+            send(user.token).await
+        },
+        result => result,
+    }
+}
+
+#[derive(Clone)]
+pub struct Api {
+    client: Client,
+    app_state: AppStateContext,
+}
+
+impl Api {
+    pub fn new(app_state: &AppStateContext) -> Self {
+        Api {
+            client: Client::new(),
+            app_state: app_state.clone(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        self.app_state.api_url(path)
+    }
+
+    // The user in app_state can be a stale snapshot captured by a callback, so prefer the
+    // copy in local storage, which a refresh updates immediately.
+    fn user(&self) -> Result<User, ApiError> {
+        let user = self.app_state.user.as_ref().ok_or(ApiError::Unauthorized)?;
+        Ok(load_user_from_local_storage()
+            .filter(|stored| stored.username == user.username)
+            .unwrap_or_else(|| user.clone()))
+    }
+
+    async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, ApiError> {
+        let resp = request.send().await.map_err(|e| ApiError::Network(e.to_string()))?;
+        let status = resp.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(ApiError::Unauthorized);
+        }
+        if !status.is_success() {
+            let message = resp.text().await.unwrap_or_default();
+            return Err(ApiError::Server { status: status.as_u16(), message });
+        }
+        resp.json::<T>().await.map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    // Sends a request with the current token. On a 401 the token is refreshed and the
+    // request is rebuilt and replayed once; a second 401 is final.
+    async fn authorized<T: DeserializeOwned>(&self, build: impl Fn(&str) -> RequestBuilder) -> Result<T, ApiError> {
+        with_refresh(|| self.user(), |token| Self::send(build(&token)), |user| self.refresh(user)).await
+    }
+
+    async fn refresh(&self, user: User) -> Result<User, ApiError> {
+        let pending = REFRESH.with(|refresh| refresh.borrow().clone());
+        let refresh = match pending {
+            Some(refresh) => refresh,
+            None => {
+                let refresh = Self::refresh_token(self.client.clone(), self.url("/refresh"), user).boxed_local().shared();
+                REFRESH.with(|pending| *pending.borrow_mut() = Some(refresh.clone()));
+                refresh
+            },
+        };
+        let result = refresh.await;
+        if let Ok(user) = &result {
+            self.app_state.dispatch(AppStateAction::SetUser(user.clone()));
+        }
+        result
+    }
+
+    async fn refresh_token(client: Client, url: String, user: User) -> Result<User, ApiError> {
+        let result = match user.refresh_token.clone() {
+            Some(refresh_token) => {
+                let request = client.post(url).json(&RefreshRequest { refresh_token });
+                Self::send::<LoginResponse>(request).await.map(|login_response| User {
+                    username: login_response.user.username,
+                    role: login_response.user.role,
+                    token: login_response.token,
+                    // Servers that don't rotate refresh tokens leave this out
+                    refresh_token: login_response.refresh_token.or(user.refresh_token),
+                })
+            },
+            None => Err(ApiError::Unauthorized),
+        };
+        if let Ok(user) = &result {
+            if let Err(e) = save_user_to_local_storage(user) {
+                log!(format!("Failed to save refreshed user: {:?}", e));
+            }
+        }
+        REFRESH.with(|pending| *pending.borrow_mut() = None);
+        result
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        let url = self.url(path);
+        self.authorized(|token| self.client.get(&url)
+            .header("Authorization", format!("Bearer {}", token))).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T, ApiError> {
+        let url = self.url(path);
+        self.authorized(|token| self.client.post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(body)).await
+    }
+
+    pub async fn login(&self, request: &LoginRequest) -> Result<LoginResponse, ApiError> {
+        Self::send(self.client.post(self.url("/login")).json(request)).await
+    }
+
+    pub async fn register(&self, request: &LoginRequest) -> Result<String, ApiError> {
+        Self::send(self.client.post(self.url("/register")).json(request)).await
+    }
+
+    pub async fn schedule_trailer(&self) -> Result<Vec<TrailerResponse>, ApiError> {
+        self.get("/api/schedule_trailer").await
+    }
+
+    pub async fn todays_trucks(&self, request: &TodaysTrucksRequest) -> Result<Vec<TrailerResponse>, ApiError> {
+        self.post("/api/todays_trucks", request).await
+    }
+
+    pub async fn trucks_date_range(&self, request: &DateRangeTruckRequest) -> Result<Vec<TrailerResponse>, ApiError> {
+        self.post("/api/trucks_date_range", request).await
+    }
+
+    pub async fn set_arrival_time(&self, request: &Arrival) -> Result<Vec<TrailerSchedule>, ApiError> {
+        self.post("/api/set_arrivalTime", request).await
+    }
+
+    pub async fn hot_trailer(&self, request: &HotTrailer) -> Result<Vec<TrailerSchedule>, ApiError> {
+        self.post("/api/hot_trailer", request).await
+    }
+
+    pub async fn set_schedule(&self, request: &SetScheduleRequest) -> Result<Vec<TrailerSchedule>, ApiError> {
+        self.post("/api/set_schedule", request).await
+    }
+
+    pub async fn get_load_info(&self, request: &LoadInfoRequest) -> Result<Vec<SidParts>, ApiError> {
+        self.post("/api/get_load_info", request).await
+    }
+
+    pub async fn trailers(&self, request: &TodaysTrucksRequest) -> Result<Vec<Sids>, ApiError> {
+        self.post("/api/trailers", request).await
+    }
+
+    pub async fn get_shipments(&self) -> Result<Vec<Shipment>, ApiError> {
+        self.get("/api/get_shipments").await
+    }
+
+    pub async fn get_todays_shipments(&self, request: &TodaysTrucksRequest) -> Result<Vec<Shipment>, ApiError> {
+        self.post("/api/get_todays_shipments", request).await
+    }
+
+    pub async fn new_shipment(&self, request: &Shipment) -> Result<Shipment, ApiError> {
+        self.post("/api/new_shipment", request).await
+    }
+
+    pub async fn shipment_hold(&self, request: &ShipmentRef) -> Result<Shipment, ApiError> {
+        self.post("/api/shipment_hold", request).await
+    }
+
+    pub async fn shipment_pick_finish(&self, request: &PickFinish) -> Result<Shipment, ApiError> {
+        self.post("/api/shipment_pick_finish", request).await
+    }
+
+    pub async fn shipment_begin_loading(&self, request: &ShipmentRef) -> Result<Shipment, ApiError> {
+        self.post("/api/shipment_begin_loading", request).await
+    }
+
+    pub async fn set_shipment_pick_start(&self, request: &PickStart) -> Result<Shipment, ApiError> {
+        self.post("/api/set_shipment_pick_start", request).await
+    }
+
+    pub async fn set_shipment_trailer(&self, request: &ShipmentTrailer) -> Result<Shipment, ApiError> {
+        self.post("/api/set_shipment_trailer", request).await
+    }
+
+    pub async fn shipment_door(&self, request: &ShipmentDoor) -> Result<Shipment, ApiError> {
+        self.post("/api/shipment_door", request).await
+    }
+
+    pub async fn set_shipment_departure_time(&self, request: &Departure) -> Result<Shipment, ApiError> {
+        self.post("/api/set_shipment_departureTime", request).await
+    }
+
+    pub async fn shipment_verification(&self, request: &Verification) -> Result<Shipment, ApiError> {
+        self.post("/api/shipment_verification", request).await
+    }
+
+    pub async fn get_shipment_details(&self, request: &ShipmentRef) -> Result<Vec<ShipmentLine>, ApiError> {
+        self.post("/api/get_shipment_details", request).await
+    }
+
+    pub async fn shipment_lines(&self, request: &ShipmentLineUploadRequest) -> Result<Vec<ShipmentLine>, ApiError> {
+        self.post("/api/shipment_lines", request).await
+    }
+
+    pub async fn carriers(&self) -> Result<Vec<Carrier>, ApiError> {
+        self.get("/api/carriers").await
+    }
+
+    pub async fn set_carrier(&self, request: &Carrier) -> Result<Vec<Carrier>, ApiError> {
+        self.post("/api/set_carrier", request).await
+    }
+
+    pub async fn delete_carrier(&self, request: &CarrierRef) -> Result<Vec<Carrier>, ApiError> {
+        self.post("/api/delete_carrier", request).await
+    }
+
+    // The upload service lives on its own port, returns plain text and doesn't check the token
+    pub async fn upload(&self, file_name: String, bytes: Vec<u8>) -> Result<String, ApiError> {
+        let upload_url = self.app_state.config.as_ref().map(|config| config.upload_url.clone()).unwrap_or_default();
+        let part = reqwest::multipart::Part::bytes(bytes).file_name(file_name);
+        let form = reqwest::multipart::Form::new().part("file", part);
+        let resp = self.client.post(upload_url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(|e| ApiError::Decode(e.to_string()))?;
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(ApiError::Server { status: status.as_u16(), message: text })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, task::{Context, Poll}};
+    use super::*;
+
+    // Lets the other request run, the way waiting on the network would
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    // A server that rotates refresh tokens, and the user as local storage has it
+    struct Session {
+        token: RefCell<String>,
+        refresh_token: RefCell<String>,
+        refreshes: RefCell<u32>,
+        stored: RefCell<User>,
+    }
+
+    impl Session {
+        fn expired() -> Self {
+            Session {
+                token: RefCell::new("t2".to_string()),
+                refresh_token: RefCell::new("r1".to_string()),
+                refreshes: RefCell::new(0),
+                stored: RefCell::new(User {
+                    username: "dock".to_string(),
+                    token: "t1".to_string(),
+                    refresh_token: Some("r1".to_string()),
+                    ..User::default()
+                }),
+            }
+        }
+
+        async fn send(&self, token: String) -> Result<String, ApiError> {
+            YieldOnce(false).await;
+            if token == *self.token.borrow() { Ok(token) } else { Err(ApiError::Unauthorized) }
+        }
+
+        async fn refresh(&self, user: User) -> Result<User, ApiError> {
+            if user.refresh_token.as_deref() != Some(self.refresh_token.borrow().as_str()) {
+                return Err(ApiError::Unauthorized);
+            }
+            *self.refreshes.borrow_mut() += 1;
+            *self.refresh_token.borrow_mut() = "r2".to_string();
+            let user = User { token: self.token.borrow().clone(), refresh_token: Some("r2".to_string()), ..user };
+            *self.stored.borrow_mut() = user.clone();
+            Ok(user)
+        }
+
+        async fn request(&self) -> Result<String, ApiError> {
+            with_refresh(|| Ok(self.stored.borrow().clone()), |token| self.send(token), |user| self.refresh(user)).await
+        }
+    }
+
+    #[test]
+    fn two_requests_share_one_refresh() {
+        let session = Session::expired();
+        let (first, second) = futures::executor::block_on(async { futures::join!(session.request(), session.request()) });
+        assert_eq!(first, Ok("t2".to_string()));
+        assert_eq!(second, Ok("t2".to_string()));
+        assert_eq!(*session.refreshes.borrow(), 1);
+    }
+
+    #[test]
+    fn a_refused_refresh_is_final() {
+        let session = Session::expired();
+        *session.refresh_token.borrow_mut() = "revoked".to_string();
+        assert_eq!(futures::executor::block_on(session.request()), Err(ApiError::Unauthorized));
+    }
+}
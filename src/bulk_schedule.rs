@@ -0,0 +1,594 @@
+use std::rc::Rc;
+use chrono::{Duration, NaiveDateTime};
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use models::*;
+use models::capacity::search_end;
+use models::carrier::{normalize_scac, scac_error};
+use models::time::{CalendarDate, TimeOfDay};
+use models::ws_event::{WsEvent, WsFrame};
+use crate::{api::{Api, ApiError}, capacity::{appointment, booked_between}, edit_trailer::save_schedule, permissions::Permission, route::Route, state::AppStateContext, AppStateAction};
+use crate::export::{download_csv, CsvExport, CsvOptions};
+use crate::import::{CsvImport, CsvImportWizard};
+use crate::carriers::{scac_options, scac_warning, SCAC_LIST};
+
+// One line of a bulk schedule file. Blank SCAC or door keeps what the trailer already has.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduleRow {
+    #[serde(rename = "TrailerID")]
+    pub trailer_id: String,
+    pub date: CalendarDate,
+    pub time: TimeOfDay,
+    pub scac: String,
+    pub door: String,
+}
+
+impl CsvImport for ScheduleRow {
+    const FIELDS: &'static [&'static str] = &["TrailerID", "date", "time", "scac", "door"];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = vec![];
+        if self.trailer_id.is_empty() {
+            errors.push(("TrailerID", "no trailer ID".to_string()));
+        }
+        if self.date.is_empty() {
+            errors.push(("date", "no date".to_string()));
+        }
+        if self.time.is_empty() {
+            errors.push(("time", "no time".to_string()));
+        }
+        errors
+    }
+}
+
+// So the template download has the same columns the import expects
+impl CsvExport for ScheduleRow {
+    type Context = ();
+
+    const COLUMNS: &'static [&'static str] = <ScheduleRow as CsvImport>::FIELDS;
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        vec![self.trailer_id.clone(), self.date.to_string(), self.time.to_string(), self.scac.clone(), self.door.clone()]
+    }
+}
+
+// The spread form. Blank SCAC, contact or door keeps each trailer's own.
+#[derive(Clone, Debug, PartialEq)]
+struct SpreadForm {
+    date: String,
+    start: String,
+    interval: String,
+    scac: String,
+    contact_email: String,
+    door: String,
+}
+
+impl Default for SpreadForm {
+    fn default() -> Self {
+        SpreadForm {
+            date: CalendarDate::today().to_string(),
+            start: "07:00".to_string(),
+            interval: "30".to_string(),
+            scac: String::new(),
+            contact_email: String::new(),
+            door: String::new(),
+        }
+    }
+}
+
+// One trailer of the batch as it will be sent, or why it won't be
+#[derive(Clone, Debug, PartialEq)]
+struct PlanRow {
+    trailer_id: String,
+    request: Option<SetScheduleRequest>,
+    note: String,
+}
+
+// A trailer to send, or its ID and why it can't be
+type Wanted = Result<SetScheduleRequest, (String, String)>;
+
+fn or_keep(value: &str, current: &str) -> String {
+    if value.trim().is_empty() { current.to_string() } else { value.trim().to_string() }
+}
+
+// EditTrailer's request, with the new slot and whatever the batch overrides
+fn request_for(trailer: &TrailerResponse, at: NaiveDateTime, scac: &str, contact_email: &str, door: &str) -> SetScheduleRequest {
+    SetScheduleRequest {
+        trailer_id: trailer.trailer_id.clone(),
+        schedule_date: CalendarDate(Some(at.date())),
+        request_date: CalendarDate::today(),
+        carrier_code: normalize_scac(&or_keep(scac, &trailer.schedule.carrier_code)),
+        schedule_time: TimeOfDay(Some(at.time())),
+        last_free_date: trailer.schedule.last_free_date,
+        contact_email: or_keep(contact_email, &trailer.schedule.contact_email),
+        door: or_keep(door, &trailer.schedule.door_number),
+    }
+}
+
+fn spread(trailers: &[TrailerResponse], form: &SpreadForm) -> Result<Vec<Wanted>, String> {
+    let date: CalendarDate = form.date.parse()?;
+    let start: TimeOfDay = form.start.parse()?;
+    let (Some(date), Some(start)) = (date.0, start.0) else {
+        return Err("Pick a date and a start time".to_string());
+    };
+    if let Some(error) = scac_error(&form.scac).filter(|_| !form.scac.trim().is_empty()) {
+        return Err(error);
+    }
+    let interval: i64 = form.interval.trim().parse().map_err(|_| format!("invalid interval {:?}", form.interval))?;
+    if interval < 0 {
+        return Err("Minutes apart can't be negative".to_string());
+    }
+    let first = date.and_time(start);
+    Ok(trailers.iter().enumerate()
+        .map(|(i, trailer)| Ok(request_for(trailer, first + Duration::minutes(interval * i as i64), &form.scac, &form.contact_email, &form.door)))
+        .collect())
+}
+
+fn from_rows(trailers: &[TrailerResponse], rows: &[ScheduleRow]) -> Vec<Wanted> {
+    rows.iter().enumerate().map(|(i, row)| {
+        // Only the first row for a trailer is booked, so one file can't book it twice
+        if rows[..i].iter().any(|earlier| earlier.trailer_id == row.trailer_id) {
+            return Err((row.trailer_id.clone(), "Listed more than once".to_string()));
+        }
+        let Some(trailer) = trailers.iter().find(|trailer| trailer.trailer_id == row.trailer_id) else {
+            return Err((row.trailer_id.clone(), "Not in the trailer list".to_string()));
+        };
+        if let Some(error) = scac_error(&row.scac).filter(|_| !row.scac.trim().is_empty()) {
+            return Err((row.trailer_id.clone(), error));
+        }
+        let (Some(date), Some(time)) = (row.date.0, row.time.0) else {
+            return Err((row.trailer_id.clone(), if row.date.is_empty() { "no date" } else { "no time" }.to_string()));
+        };
+        let at = date.and_time(time);
+        Ok(request_for(trailer, at, &row.scac, "", &row.door))
+    }).collect()
+}
+
+// Runs the batch past the capacity rules, moving or leaving out trailers that don't fit
+async fn plan(app_state: &AppStateContext, wanted: Vec<Wanted>, move_when_full: bool) -> Result<Vec<PlanRow>, ApiError> {
+    let scheduled = |request: &SetScheduleRequest| {
+        let mut trailer = app_state.trailers.iter().find(|trailer| trailer.trailer_id == request.trailer_id).cloned().unwrap_or_default();
+        trailer.trailer_id = request.trailer_id.clone();
+        trailer.schedule.schedule_date = request.schedule_date;
+        trailer.schedule.schedule_time = request.schedule_time;
+        trailer.schedule.door_number = request.door.clone();
+        appointment(&trailer, app_state.plants())
+    };
+    let requests: Vec<&SetScheduleRequest> = wanted.iter().filter_map(|row| row.as_ref().ok()).collect();
+    let rules = app_state.capacity();
+    let mut placements = if rules.is_empty() || requests.is_empty() {
+        vec![]
+    } else {
+        let first = requests.iter().filter_map(|request| request.schedule_date.0).min().unwrap_or_default();
+        let last = requests.iter().filter_map(|request| request.schedule_date.0).max().unwrap_or_default();
+        let booked = booked_between(app_state, CalendarDate(Some(first)), CalendarDate(Some(search_end(last)))).await?;
+        rules.place(&booked, requests.iter().filter_map(|request| scheduled(request)).collect(), move_when_full)
+    }.into_iter();
+
+    Ok(wanted.into_iter().map(|row| match row {
+        Err((trailer_id, note)) => PlanRow { trailer_id, request: None, note },
+        Ok(request) if rules.is_empty() => PlanRow { trailer_id: request.trailer_id.clone(), request: Some(request), note: String::new() },
+        Ok(request) if scheduled(&request).is_none() => PlanRow { trailer_id: request.trailer_id, request: None, note: "No scheduled date".to_string() },
+        Ok(request) => match placements.next().map(|placement| placement.placed) {
+            Some(Ok(slot)) => {
+                let moved = slot.date() != request.schedule_date || slot.time() != request.schedule_time;
+                let note = if moved { format!("Moved from {} {}, that hour is full", request.schedule_date, request.schedule_time) } else { String::new() };
+                PlanRow {
+                    trailer_id: request.trailer_id.clone(),
+                    request: Some(SetScheduleRequest { schedule_date: slot.date(), schedule_time: slot.time(), ..request }),
+                    note,
+                }
+            },
+            Some(Err(violations)) => PlanRow {
+                trailer_id: request.trailer_id,
+                request: None,
+                note: violations.iter().map(|violation| violation.to_string()).collect::<Vec<_>>().join("; "),
+            },
+            None => PlanRow { trailer_id: request.trailer_id, request: None, note: "Couldn't be placed".to_string() },
+        },
+    }).collect())
+}
+
+// A checkbox cell for the trailer tables. Only schedulers get the column at all.
+pub fn select_cell(app_state: &AppStateContext, trailer_id: &str) -> Html {
+    if !app_state.user.as_ref().is_some_and(|user| user.can(Permission::ScheduleTrailer)) {
+        return html! {<></>};
+    }
+    let onclick = {
+        let app_state = app_state.clone();
+        let trailer_id = trailer_id.to_string();
+        Callback::from(move |_: MouseEvent| app_state.dispatch(AppStateAction::ToggleTrailerSelected(trailer_id.clone())))
+    };
+    html! {
+        <td><input type="checkbox" checked={app_state.selected_trailers.iter().any(|selected| selected == trailer_id)} {onclick} /></td>
+    }
+}
+
+// The header cell over select_cell, ticking or clearing every trailer shown
+pub fn select_all_cell(app_state: &AppStateContext, trailer_ids: Vec<String>) -> Html {
+    if !app_state.user.as_ref().is_some_and(|user| user.can(Permission::ScheduleTrailer)) {
+        return html! {<></>};
+    }
+    let all = !trailer_ids.is_empty() && trailer_ids.iter().all(|id| app_state.selected_trailers.contains(id));
+    let onclick = {
+        let app_state = app_state.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut selected = app_state.selected_trailers.clone();
+            if all {
+                selected.retain(|id| !trailer_ids.contains(id));
+            } else {
+                selected.extend(trailer_ids.iter().filter(|id| !app_state.selected_trailers.contains(id)).cloned());
+            }
+            app_state.dispatch(AppStateAction::SetSelectedTrailers(selected));
+        })
+    };
+    html! {
+        <th><input type="checkbox" checked={all} {onclick} /></th>
+    }
+}
+
+// Shown above the trailer tables once something is ticked
+pub fn bulk_schedule_bar(app_state: &AppStateContext) -> Html {
+    if app_state.selected_trailers.is_empty() {
+        return html! {<></>};
+    }
+    let open = {
+        let app_state = app_state.clone();
+        Callback::from(move |_: MouseEvent| app_state.dispatch(AppStateAction::Navigate(Route::BulkSchedule)))
+    };
+    let clear = {
+        let app_state = app_state.clone();
+        Callback::from(move |_: MouseEvent| app_state.dispatch(AppStateAction::SetSelectedTrailers(vec![])))
+    };
+    html! {
+        <div style="margin: 1%; display: flex; flex-direction: row; justify-content: center; gap: 2rem; align-items: center;">
+            <button style="background-color: blue; color: white; padding: 10px 16px; border: none; cursor: pointer; border-radius: 4px;" onclick={open}>
+                {format!("Schedule {} Selected", app_state.selected_trailers.len())}
+            </button>
+            <a onclick={clear}>{"Clear Selection"}</a>
+        </div>
+    }
+}
+
+#[function_component(BulkSchedule)]
+pub fn bulk_schedule() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+    let form = use_state(SpreadForm::default);
+    let move_when_full = use_state(|| true);
+    let plan_rows = use_state(|| None::<Rc<Vec<PlanRow>>>);
+    let results = use_state(|| None::<Rc<Vec<(String, Result<(), String>)>>>);
+    let error = use_state(String::new);
+    let busy = use_state(|| false);
+
+    // Opened from a link, there's nothing to pick from yet
+    {
+        let app_state = app_state.clone();
+        use_effect_with((), move |_| {
+            if app_state.trailers.is_empty() {
+                let app_state = app_state.clone();
+                spawn_local(async move {
+                    match Api::new(&app_state).schedule_trailer().await {
+                        Ok(trailer_response) => app_state.dispatch(AppStateAction::SetTrailers(trailer_response)),
+                        Err(error) => error.handle(&app_state),
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let selected: Vec<TrailerResponse> = app_state.selected_trailers.iter()
+        .filter_map(|trailer_id| app_state.trailers.iter().find(|trailer| trailer.trailer_id == *trailer_id))
+        .cloned()
+        .collect();
+
+    let build_plan = {
+        let app_state = app_state.clone();
+        let move_when_full = move_when_full.clone();
+        let plan_rows = plan_rows.clone();
+        let results = results.clone();
+        let busy = busy.clone();
+        Callback::from(move |wanted: Vec<Wanted>| {
+            let app_state = app_state.clone();
+            let move_when_full = *move_when_full;
+            let plan_rows = plan_rows.clone();
+            let results = results.clone();
+            let busy = busy.clone();
+            busy.set(true);
+            spawn_local(async move {
+                match plan(&app_state, wanted, move_when_full).await {
+                    Ok(rows) => {
+                        plan_rows.set(Some(Rc::new(rows)));
+                        results.set(None);
+                    },
+                    Err(error) => error.handle(&app_state),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let preview = {
+        let form = form.clone();
+        let error = error.clone();
+        let build_plan = build_plan.clone();
+        let selected = selected.clone();
+        Callback::from(move |_: MouseEvent| {
+            match spread(&selected, &form) {
+                Ok(wanted) => {
+                    error.set(String::new());
+                    build_plan.emit(wanted);
+                },
+                Err(message) => error.set(message),
+            }
+        })
+    };
+
+    let on_import = {
+        let app_state = app_state.clone();
+        let build_plan = build_plan.clone();
+        Callback::from(move |rows: Vec<ScheduleRow>| build_plan.emit(from_rows(&app_state.trailers, &rows)))
+    };
+
+    let on_change = {
+        let form = form.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let mut form_data = (*form).clone();
+            match input.id().as_str() {
+                "date" => form_data.date = input.value(),
+                "start" => form_data.start = input.value(),
+                "interval" => form_data.interval = input.value(),
+                "scac" => form_data.scac = input.value(),
+                "contact_email" => form_data.contact_email = input.value(),
+                "door" => form_data.door = input.value(),
+                _ => (),
+            }
+            form.set(form_data);
+        })
+    };
+
+    let toggle_move = {
+        let move_when_full = move_when_full.clone();
+        Callback::from(move |_: MouseEvent| move_when_full.set(!*move_when_full))
+    };
+
+    let download_template = Callback::from(|_: MouseEvent| {
+        download_csv::<ScheduleRow>("bulk_schedule_template.csv", &[], &(), &CsvOptions::default());
+    });
+
+    // One /api/set_schedule and broadcast per trailer, in order, carrying on past failures
+    let run = {
+        let app_state = app_state.clone();
+        let plan_rows = plan_rows.clone();
+        let results = results.clone();
+        let busy = busy.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(rows) = (*plan_rows).clone() else {
+                return;
+            };
+            let app_state = app_state.clone();
+            let plan_rows = plan_rows.clone();
+            let results = results.clone();
+            let busy = busy.clone();
+            busy.set(true);
+            spawn_local(async move {
+                let mut outcome = vec![];
+                for (i, row) in rows.iter().enumerate() {
+                    let Some(request) = row.request.clone() else {
+                        outcome.push((row.trailer_id.clone(), Err(format!("Skipped: {}", row.note))));
+                        continue;
+                    };
+                    match save_schedule(&app_state, request.clone()).await {
+                        Ok(()) => {
+                            app_state.dispatch(AppStateAction::HandleWsFrame(WsFrame::new(WsEvent::ScheduleTrailer(request))));
+                            outcome.push((row.trailer_id.clone(), Ok(())));
+                        },
+                        Err(ApiError::Unauthorized) => {
+                            ApiError::Unauthorized.handle(&app_state);
+                            outcome.push((row.trailer_id.clone(), Err(ApiError::Unauthorized.to_string())));
+                            outcome.extend(rows[i + 1..].iter().map(|row| (row.trailer_id.clone(), Err("Not sent".to_string()))));
+                            break;
+                        },
+                        Err(error) => outcome.push((row.trailer_id.clone(), Err(error.to_string()))),
+                    }
+                }
+                let saved: Vec<&String> = outcome.iter().filter(|(_, result)| result.is_ok()).map(|(trailer_id, _)| trailer_id).collect();
+                let still_selected = app_state.selected_trailers.iter().filter(|id| !saved.contains(id)).cloned().collect();
+                app_state.dispatch(AppStateAction::SetSelectedTrailers(still_selected));
+                plan_rows.set(None);
+                results.set(Some(Rc::new(outcome)));
+                busy.set(false);
+            });
+        })
+    };
+
+    let ready = plan_rows.as_ref().map_or(0, |rows| rows.iter().filter(|row| row.request.is_some()).count());
+
+    html! {
+        <div style="margin-top: 7vh; width: 90vw;">
+            <h1 style="text-align: center;">{"Bulk Schedule"}</h1>
+            <div style="display: flex; flex-direction: row; justify-content: space-evenly; align-items: flex-start;">
+                <div style="text-align: center;">
+                    <h3>{format!("Spread {} Selected Trailers", selected.len())}</h3>
+                    <p style="font-size: 0.8em;">{ selected.iter().map(|trailer| trailer.trailer_id.clone()).collect::<Vec<_>>().join(", ") }</p>
+                    <div>
+                        <label for="date">{"Date:"}</label>
+                        <input style="text-align: center;" id="date" type="date" value={form.date.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="start">{"First Time:"}</label>
+                        <input style="text-align: center;" id="start" type="time" value={form.start.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="interval">{"Minutes Apart:"}</label>
+                        <input style="text-align: center;" id="interval" type="number" min="0" value={form.interval.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="scac">{"SCAC:"}</label>
+                        <input style="text-align: center;" id="scac" type="text" list={SCAC_LIST} placeholder="Keep each trailer's" value={form.scac.clone()} oninput={on_change.clone()} />
+                        { scac_options(&app_state) }
+                        { scac_warning(&app_state, &form.scac) }
+                    </div>
+                    <div>
+                        <label for="contact_email">{"Email:"}</label>
+                        <input style="text-align: center;" id="contact_email" type="text" placeholder="Keep each trailer's" value={form.contact_email.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="door">{"Door:"}</label>
+                        <input style="text-align: center;" id="door" type="text" placeholder="Keep each trailer's" value={form.door.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <button style="background-color: blue; color: white; padding: 10px 16px; border: none; cursor: pointer; border-radius: 4px;" disabled={selected.is_empty() || *busy} onclick={preview}>{"Preview"}</button>
+                    { if error.is_empty() {
+                        html! {<></>}
+                    } else {
+                        html! { <p style="color: red;">{(*error).clone()}</p> }
+                    }}
+                </div>
+                <div style="text-align: center;">
+                    <h3>{"Or From a CSV"}</h3>
+                    <a onclick={download_template}>{"Download Template"}</a>
+                    <CsvImportWizard<ScheduleRow> title="Bulk Schedule" on_import={on_import} />
+                </div>
+            </div>
+            <div style="text-align: center; margin: 1%;">
+                <label>
+                    <input type="checkbox" checked={*move_when_full} onclick={toggle_move} />
+                    {" Move trailers to the next open slot when their hour is full"}
+                </label>
+            </div>
+            { match plan_rows.as_ref() {
+                Some(rows) => html! {
+                    <>
+                        <table>
+                            <thead>
+                                <tr style="text-align: center;">
+                                    <th>{"#"}</th>
+                                    <th>{"Trailer ID"}</th>
+                                    <th>{"Date"}</th>
+                                    <th>{"Time"}</th>
+                                    <th>{"SCAC"}</th>
+                                    <th>{"Door"}</th>
+                                    <th>{"Note"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                            { for rows.iter().enumerate().map(|(index, row)| match &row.request {
+                                Some(request) => html! {
+                                    <tr style="text-align: center;">
+                                        <td>{index + 1}</td>
+                                        <td>{row.trailer_id.clone()}</td>
+                                        <td>{request.schedule_date.to_string()}</td>
+                                        <td>{request.schedule_time.to_string()}</td>
+                                        <td>{request.carrier_code.clone()}</td>
+                                        <td>{request.door.clone()}</td>
+                                        <td style="color: orange;">{row.note.clone()}</td>
+                                    </tr>
+                                },
+                                None => html! {
+                                    <tr style="text-align: center; color: red;">
+                                        <td>{index + 1}</td>
+                                        <td>{row.trailer_id.clone()}</td>
+                                        <td colspan="4">{"Won't be scheduled"}</td>
+                                        <td>{row.note.clone()}</td>
+                                    </tr>
+                                },
+                            }) }
+                            </tbody>
+                        </table>
+                        <div style="text-align: center; margin: 1%;">
+                            <button style="background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" disabled={ready == 0 || *busy} onclick={run}>
+                                {format!("Schedule {} Trailers", ready)}
+                            </button>
+                        </div>
+                    </>
+                },
+                None => html! {<></>},
+            }}
+            { match results.as_ref() {
+                Some(outcome) => {
+                    let saved = outcome.iter().filter(|(_, result)| result.is_ok()).count();
+                    html! {
+                        <>
+                            <h3 style="text-align: center;">{format!("{} scheduled, {} failed", saved, outcome.len() - saved)}</h3>
+                            <table>
+                                <thead>
+                                    <tr style="text-align: center;">
+                                        <th>{"Trailer ID"}</th>
+                                        <th>{"Result"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                { for outcome.iter().map(|(trailer_id, result)| match result {
+                                    Ok(()) => html! {
+                                        <tr style="text-align: center;"><td>{trailer_id.clone()}</td><td style="color: green;">{"Scheduled"}</td></tr>
+                                    },
+                                    Err(message) => html! {
+                                        <tr style="text-align: center;"><td>{trailer_id.clone()}</td><td style="color: red;">{message.clone()}</td></tr>
+                                    },
+                                }) }
+                                </tbody>
+                            </table>
+                        </>
+                    }
+                },
+                None => html! {<></>},
+            }}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailers() -> Vec<TrailerResponse> {
+        ["TRL1", "TRL2"].iter()
+            .map(|trailer_id| TrailerResponse { trailer_id: trailer_id.to_string(), ..TrailerResponse::default() })
+            .collect()
+    }
+
+    fn row(trailer_id: &str, date: &str, time: &str) -> ScheduleRow {
+        ScheduleRow {
+            trailer_id: trailer_id.to_string(),
+            date: date.parse().unwrap(),
+            time: time.parse().unwrap(),
+            scac: String::new(),
+            door: String::new(),
+        }
+    }
+
+    fn failures(wanted: &[Wanted]) -> Vec<(String, String)> {
+        wanted.iter().filter_map(|wanted| wanted.clone().err()).collect()
+    }
+
+    #[test]
+    fn rows_without_a_time_are_rejected() {
+        assert_eq!(row("TRL1", "2024-05-01", "").validate(), vec![("time", "no time".to_string())]);
+        let wanted = from_rows(&trailers(), &[row("TRL1", "2024-05-01", ""), row("TRL2", "", "08:00")]);
+        assert_eq!(failures(&wanted), vec![
+            ("TRL1".to_string(), "no time".to_string()),
+            ("TRL2".to_string(), "no date".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn a_trailer_listed_twice_is_only_booked_once() {
+        let wanted = from_rows(&trailers(), &[row("TRL1", "2024-05-01", "08:00"), row("TRL1", "2024-05-02", "09:00")]);
+        assert_eq!(wanted[0].as_ref().map(|request| request.schedule_date.to_string()), Ok("2024-05-01".to_string()));
+        assert_eq!(failures(&wanted), vec![("TRL1".to_string(), "Listed more than once".to_string())]);
+    }
+
+    #[test]
+    fn spread_refuses_a_negative_interval() {
+        let form = SpreadForm { date: "2024-05-01".to_string(), interval: "-15".to_string(), ..SpreadForm::default() };
+        assert!(spread(&trailers(), &form).is_err());
+        let form = SpreadForm { interval: "15".to_string(), ..form };
+        let times: Vec<String> = spread(&trailers(), &form).unwrap().into_iter()
+            .map(|wanted| wanted.unwrap().schedule_time.to_string())
+            .collect();
+        assert_eq!(times, vec!["07:00", "07:15"]);
+    }
+}
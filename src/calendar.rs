@@ -0,0 +1,290 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use models::*;
+use models::capacity::Appointment;
+use models::time::{CalendarDate, TimeOfDay};
+use models::ws_event::{WsEvent, WsFrame};
+use crate::{api::Api, capacity::{capacity_warning, check_capacity, CapacityProblem}, edit_trailer::save_schedule, permissions::Permission, route::Route, state::AppStateContext, AppStateAction};
+
+// The grid always covers a normal receiving day, and stretches for anything booked outside it
+const FIRST_HOUR: u32 = 6;
+const LAST_HOUR: u32 = 18;
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+// Hot beats Stat6 beats arrived, since hot is the one that needs acting on
+fn block_color(trailer: &TrailerResponse) -> &'static str {
+    if trailer.schedule.is_hot {
+        "#F44336"
+    } else if trailer.schedule.is_stat6 {
+        "#9C27B0"
+    } else if !trailer.schedule.arrival_time.is_empty() {
+        "#4CAF50"
+    } else {
+        "#2196F3"
+    }
+}
+
+// The same request EditTrailer sends, with only the date and time moved
+fn reschedule_request(trailer: &TrailerResponse, at: NaiveDateTime) -> SetScheduleRequest {
+    SetScheduleRequest {
+        trailer_id: trailer.trailer_id.clone(),
+        schedule_date: CalendarDate(Some(at.date())),
+        request_date: CalendarDate::today(),
+        carrier_code: trailer.schedule.carrier_code.clone(),
+        schedule_time: TimeOfDay(Some(at.time())),
+        last_free_date: trailer.schedule.last_free_date,
+        contact_email: trailer.schedule.contact_email.clone(),
+        door: trailer.schedule.door_number.clone(),
+    }
+}
+
+// Drags carry the trailer ID. Firefox won't start a drag without some data anyway.
+const DRAG_TYPE: &str = "text/plain";
+
+#[derive(Properties, PartialEq)]
+struct BlockProps {
+    trailer: TrailerResponse,
+    draggable: bool,
+    on_open: Callback<TrailerResponse>,
+}
+
+#[function_component(TrailerBlock)]
+fn trailer_block(props: &BlockProps) -> Html {
+    let trailer = &props.trailer;
+    let ondragstart = {
+        let trailer_id = trailer.trailer_id.clone();
+        Callback::from(move |e: DragEvent| {
+            if let Some(data) = e.data_transfer() {
+                let _ = data.set_data(DRAG_TYPE, &trailer_id);
+            }
+        })
+    };
+    let onclick = {
+        let trailer = trailer.clone();
+        props.on_open.reform(move |_: MouseEvent| trailer.clone())
+    };
+    html! {
+        <div
+            draggable={props.draggable.to_string()}
+            {ondragstart}
+            {onclick}
+            title={format!("{} {} door {}", trailer.schedule.carrier_code, trailer.schedule.schedule_time, trailer.schedule.door_number)}
+            style={format!("background-color: {}; color: white; border-radius: 4px; padding: 2px 4px; margin: 2px 0; font-size: 0.8em; cursor: {};",
+                block_color(trailer), if props.draggable { "grab" } else { "pointer" })}
+        >
+            {trailer.trailer_id.clone()}
+            { if trailer.schedule.schedule_time.is_empty() {
+                html! {<></>}
+            } else {
+                html! { <span>{format!(" {}", trailer.schedule.schedule_time)}</span> }
+            }}
+        </div>
+    }
+}
+
+#[function_component(Calendar)]
+pub fn calendar() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+    let week = use_state(|| week_start(CalendarDate::today().0.unwrap_or_default()));
+
+    {
+        let app_state = app_state.clone();
+        use_effect_with(app_state.sync_generation, move |_| {
+            let app_state = app_state.clone();
+            spawn_local(async move {
+                match Api::new(&app_state).schedule_trailer().await {
+                    Ok(trailer_response) => app_state.dispatch(AppStateAction::SetTrailers(trailer_response)),
+                    Err(error) => error.handle(&app_state),
+                }
+            });
+            || ()
+        });
+    }
+
+    let can_schedule = app_state.user.as_ref().is_some_and(|user| user.can(Permission::ScheduleTrailer));
+
+    let on_open = {
+        let app_state = app_state.clone();
+        Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::LoadDetails(trailer.trailer_id.clone());
+            app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
+            app_state.dispatch(AppStateAction::Navigate(route));
+        })
+    };
+
+    // A drop the capacity rules refused, kept so its suggestion can be taken
+    let capacity_problem = use_state(|| None::<(TrailerResponse, CapacityProblem)>);
+
+    let reschedule = {
+        let app_state = app_state.clone();
+        Callback::from(move |request: SetScheduleRequest| {
+            let app_state = app_state.clone();
+            spawn_local(async move {
+                match save_schedule(&app_state, request.clone()).await {
+                    // Move the block now instead of waiting for the broadcast to come back round
+                    Ok(()) => app_state.dispatch(AppStateAction::HandleWsFrame(WsFrame::new(WsEvent::ScheduleTrailer(request)))),
+                    Err(error) => error.handle(&app_state),
+                }
+            });
+        })
+    };
+
+    let on_drop = {
+        let app_state = app_state.clone();
+        let capacity_problem = capacity_problem.clone();
+        let reschedule = reschedule.clone();
+        Callback::from(move |(trailer_id, date, hour): (String, NaiveDate, u32)| {
+            let Some(trailer) = app_state.trailers.iter().find(|trailer| trailer.trailer_id == trailer_id) else {
+                return;
+            };
+            // Keep the minutes, so a 07:30 dropped on 09:00 comes out at 09:30
+            let minute = trailer.schedule.schedule_time.0.map_or(0, |time| time.minute());
+            let Some(at) = date.and_hms_opt(hour, minute, 0) else {
+                return;
+            };
+            let request = reschedule_request(trailer, at);
+            if request.schedule_date == trailer.schedule.schedule_date && request.schedule_time == trailer.schedule.schedule_time {
+                return;
+            }
+            let mut moved = trailer.clone();
+            moved.schedule.schedule_date = request.schedule_date;
+            moved.schedule.schedule_time = request.schedule_time;
+            let app_state = app_state.clone();
+            let capacity_problem = capacity_problem.clone();
+            let reschedule = reschedule.clone();
+            spawn_local(async move {
+                match check_capacity(&app_state, &moved).await {
+                    Ok(None) => {
+                        capacity_problem.set(None);
+                        reschedule.emit(request);
+                    },
+                    Ok(Some(problem)) => capacity_problem.set(Some((moved, problem))),
+                    Err(error) => error.handle(&app_state),
+                }
+            });
+        })
+    };
+
+    let use_slot = {
+        let capacity_problem = capacity_problem.clone();
+        Callback::from(move |slot: Appointment| {
+            if let Some((trailer, _)) = &*capacity_problem {
+                reschedule.emit(reschedule_request(trailer, slot.at));
+            }
+            capacity_problem.set(None);
+        })
+    };
+
+    let move_week = {
+        let week = week.clone();
+        move |days: i64| {
+            let week = week.clone();
+            Callback::from(move |_: MouseEvent| week.set(*week + Duration::days(days)))
+        }
+    };
+    let this_week = {
+        let week = week.clone();
+        Callback::from(move |_: MouseEvent| week.set(week_start(CalendarDate::today().0.unwrap_or_default())))
+    };
+
+    let days: Vec<NaiveDate> = (0..7).map(|offset| *week + Duration::days(offset)).collect();
+    let in_week: Vec<&TrailerResponse> = app_state.trailers.iter()
+        .filter(|trailer| trailer.schedule.schedule_date.0.is_some_and(|date| days.contains(&date)))
+        .collect();
+    let hour_of = |trailer: &TrailerResponse| trailer.schedule.schedule_time.0.map_or(FIRST_HOUR, |time| time.hour());
+    let first_hour = in_week.iter().map(|trailer| hour_of(trailer)).min().unwrap_or(FIRST_HOUR).min(FIRST_HOUR);
+    let last_hour = in_week.iter().map(|trailer| hour_of(trailer)).max().unwrap_or(LAST_HOUR).max(LAST_HOUR);
+
+    // Trailers still waiting for a date, and not already here
+    let unscheduled: Vec<&TrailerResponse> = app_state.trailers.iter()
+        .filter(|trailer| trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty())
+        .collect();
+
+    let block = |trailer: &TrailerResponse| html! {
+        <TrailerBlock
+            trailer={trailer.clone()}
+            draggable={can_schedule && trailer.schedule.arrival_time.is_empty()}
+            on_open={on_open.clone()}
+        />
+    };
+
+    let today = CalendarDate::today().0;
+
+    html! {
+        <div style="margin-top: 7vh; width: 95vw; display: flex; flex-direction: row;">
+            <div style="width: 12vw; margin-right: 1vw;">
+                <h3 style="text-align: center;">{format!("Unscheduled ({})", unscheduled.len())}</h3>
+                { for unscheduled.iter().map(|trailer| block(trailer)) }
+            </div>
+            <div style="flex-grow: 1;">
+                <h1 style="text-align: center;">{format!("Week of {}", week.format("%m/%d/%Y"))}</h1>
+                <div style="display: flex; flex-direction: row; justify-content: space-evenly; align-items: center; margin-bottom: 1%;">
+                    <a onclick={move_week(-7)}>{"< Previous Week"}</a>
+                    <a onclick={this_week}>{"This Week"}</a>
+                    <a onclick={move_week(7)}>{"Next Week >"}</a>
+                </div>
+                <div style="display: flex; flex-direction: row; justify-content: center; gap: 1rem; margin-bottom: 1%; font-size: 0.8em;">
+                    <span style="color: #F44336;">{"\u{25A0} Hot"}</span>
+                    <span style="color: #9C27B0;">{"\u{25A0} Stat6"}</span>
+                    <span style="color: #4CAF50;">{"\u{25A0} Arrived"}</span>
+                    <span style="color: #2196F3;">{"\u{25A0} Scheduled"}</span>
+                </div>
+                { match &*capacity_problem {
+                    Some((trailer, problem)) => html! {
+                        <div style="text-align: center;">
+                            <p style="font-weight: bold;">{format!("Can't move {} to {} {}", trailer.trailer_id, trailer.schedule.schedule_date, trailer.schedule.schedule_time)}</p>
+                            { capacity_warning(problem, use_slot) }
+                        </div>
+                    },
+                    None => html! {<></>},
+                }}
+                <table style="table-layout: fixed; width: 100%;">
+                    <thead>
+                        <tr style="text-align: center;">
+                            <th style="width: 5%;"></th>
+                            { for days.iter().map(|day| html! {
+                                <th style={if Some(*day) == today { "background-color: #333; color: limegreen;" } else { "" }}>
+                                    {day.format("%a %m/%d").to_string()}
+                                </th>
+                            }) }
+                        </tr>
+                    </thead>
+                    <tbody>
+                    { for (first_hour..=last_hour).map(|hour| html! {
+                        <tr>
+                            <td style="text-align: right; vertical-align: top;">{format!("{:02}:00", hour)}</td>
+                            { for days.iter().map(|day| {
+                                let day = *day;
+                                let ondrop = {
+                                    let on_drop = on_drop.clone();
+                                    Callback::from(move |e: DragEvent| {
+                                        e.prevent_default();
+                                        if let Some(trailer_id) = e.data_transfer().and_then(|data| data.get_data(DRAG_TYPE).ok()) {
+                                            on_drop.emit((trailer_id, day, hour));
+                                        }
+                                    })
+                                };
+                                html! {
+                                    <td
+                                        style="vertical-align: top; height: 2.5em; border: 1px solid #ddd;"
+                                        ondragover={Callback::from(|e: DragEvent| e.prevent_default())}
+                                        {ondrop}
+                                    >
+                                        { for in_week.iter()
+                                            .filter(|trailer| trailer.schedule.schedule_date.0 == Some(day) && hour_of(trailer) == hour)
+                                            .map(|trailer| block(trailer)) }
+                                    </td>
+                                }
+                            }) }
+                        </tr>
+                    }) }
+                    </tbody>
+                </table>
+            </div>
+        </div>
+    }
+}
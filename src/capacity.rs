@@ -0,0 +1,71 @@
+use yew::prelude::*;
+use models::*;
+use models::capacity::{search_end, Appointment, Violation, SEARCH_DAYS};
+use models::time::CalendarDate;
+use crate::{api::{Api, ApiError}, plants::PlantRegistry, state::AppStateContext};
+
+// What's wrong with a schedule, and where it could go instead
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapacityProblem {
+    pub violations: Vec<Violation>,
+    pub suggestion: Option<Appointment>,
+}
+
+pub fn appointment(trailer: &TrailerResponse, plants: &PlantRegistry) -> Option<Appointment> {
+    let codes = trailer.cisco_ids.iter()
+        .map(|cisco_id| plants.location(cisco_id))
+        .filter(|code| !code.is_empty())
+        .collect();
+    Appointment::for_trailer(trailer, codes)
+}
+
+// Everything scheduled between two days, from the same date range lookup the Date Range screen uses
+pub async fn booked_between(app_state: &AppStateContext, from: CalendarDate, to: CalendarDate) -> Result<Vec<Appointment>, ApiError> {
+    let request = DateRangeTruckRequest { date1: from, date2: to };
+    Ok(Api::new(app_state).trucks_date_range(&request).await?
+        .iter()
+        .filter_map(|trailer| appointment(trailer, app_state.plants()))
+        .collect())
+}
+
+// Checks a trailer's schedule against the capacity rules and what's booked from that day to as
+// far as a suggestion could reach
+pub async fn check_capacity(app_state: &AppStateContext, trailer: &TrailerResponse) -> Result<Option<CapacityProblem>, ApiError> {
+    let rules = app_state.capacity();
+    let Some(candidate) = appointment(trailer, app_state.plants()) else {
+        return Ok(None);
+    };
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    let booked = booked_between(app_state, candidate.date(), CalendarDate(Some(search_end(candidate.at.date())))).await?;
+    let violations = rules.check(&booked, &candidate);
+    if violations.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(CapacityProblem {
+        suggestion: rules.next_open(&booked, &candidate),
+        violations,
+    }))
+}
+
+// Why a schedule was refused, with a button to take the suggested slot instead
+pub fn capacity_warning(problem: &CapacityProblem, on_use: Callback<Appointment>) -> Html {
+    html! {
+        <div style="color: red; font-weight: bold; margin: 1%;">
+            { for problem.violations.iter().map(|violation| html! { <p>{violation.to_string()}</p> }) }
+            { match &problem.suggestion {
+                Some(slot) => {
+                    let label = format!("Use next open slot: {} {}", slot.date(), slot.time());
+                    let slot = slot.clone();
+                    html! {
+                        <button style="background-color: orange; color: black; padding: 8px 14px; border: none; cursor: pointer; border-radius: 4px;" onclick={on_use.reform(move |_| slot.clone())}>
+                            {label}
+                        </button>
+                    }
+                },
+                None => html! { <p>{format!("No open slot in the next {} days", SEARCH_DAYS)}</p> },
+            }}
+        </div>
+    }
+}
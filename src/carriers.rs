@@ -0,0 +1,304 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use models::carrier::{normalize_scac, scac_error, Carrier, CarrierRef};
+use crate::{api::Api, permissions::Permission, state::AppStateContext, AppStateAction};
+
+// The id of the datalist the SCAC boxes autocomplete from
+pub const SCAC_LIST: &str = "carrier-scacs";
+
+pub fn fetch_carriers(app_state: &AppStateContext) {
+    let app_state = app_state.clone();
+    spawn_local(async move {
+        match Api::new(&app_state).carriers().await {
+            Ok(carriers) => app_state.dispatch(AppStateAction::SetCarriers(carriers)),
+            Err(error) => error.handle(&app_state),
+        }
+    });
+}
+
+// Suggestions for any input with list={SCAC_LIST}, each SCAC labelled with the carrier's name
+pub fn scac_options(app_state: &AppStateContext) -> Html {
+    html! {
+        <datalist id={SCAC_LIST}>
+            { for app_state.carriers.carriers.iter().map(|carrier| html! {
+                <option value={carrier.scac.clone()} label={carrier.name.clone()} />
+            }) }
+        </datalist>
+    }
+}
+
+// Shown under a SCAC box. A SCAC that isn't one stops a save, one we just don't know is a heads-up.
+pub fn scac_warning(app_state: &AppStateContext, scac: &str) -> Html {
+    if scac.trim().is_empty() {
+        return html! {<></>};
+    }
+    if let Some(error) = scac_error(scac) {
+        return html! { <p style="color: red;">{error}</p> };
+    }
+    match app_state.carriers.by_scac(scac) {
+        Some(carrier) => html! { <p style="color: green;">{carrier.name.clone()}</p> },
+        None if app_state.carriers.is_empty() => html! {<></>},
+        None => html! {
+            <p style="color: orange;">{format!("{} isn't in the carrier directory", normalize_scac(scac))}</p>
+        },
+    }
+}
+
+// The directory form, kept as typed until it's saved
+#[derive(Clone, Debug, PartialEq, Default)]
+struct CarrierForm {
+    scac: String,
+    name: String,
+    dispatch_emails: String,
+    phone: String,
+    default_time: String,
+    default_door: String,
+    daily_rate: String,
+}
+
+impl CarrierForm {
+    fn from_carrier(carrier: &Carrier) -> Self {
+        CarrierForm {
+            scac: carrier.scac.clone(),
+            name: carrier.name.clone(),
+            dispatch_emails: carrier.dispatch_emails.join(", "),
+            phone: carrier.phone.clone(),
+            default_time: carrier.default_time.to_string(),
+            default_door: carrier.default_door.clone(),
+            daily_rate: carrier.daily_rate.map(|rate| rate.to_string()).unwrap_or_default(),
+        }
+    }
+
+    fn carrier(&self) -> Result<Carrier, Vec<String>> {
+        let mut errors = vec![];
+        let default_time = self.default_time.parse().unwrap_or_else(|error: String| {
+            errors.push(error);
+            Default::default()
+        });
+        let daily_rate = match self.daily_rate.trim() {
+            "" => None,
+            rate => rate.parse().map(Some).unwrap_or_else(|_| {
+                errors.push(format!("{:?} isn't a daily rate", rate));
+                None
+            }),
+        };
+        let carrier = Carrier {
+            scac: normalize_scac(&self.scac),
+            name: self.name.trim().to_string(),
+            dispatch_emails: self.dispatch_emails.split([',', ';'])
+                .map(|email| email.trim().to_string())
+                .filter(|email| !email.is_empty())
+                .collect(),
+            phone: self.phone.trim().to_string(),
+            default_time,
+            default_door: self.default_door.trim().to_string(),
+            daily_rate,
+        };
+        errors.extend(carrier.validate());
+        if errors.is_empty() { Ok(carrier) } else { Err(errors) }
+    }
+}
+
+#[function_component(Carriers)]
+pub fn carriers() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+    let form = use_state(CarrierForm::default);
+    let errors = use_state(Vec::<String>::new);
+    let search = use_state(String::new);
+    let can_edit = app_state.user.as_ref().is_some_and(|user| user.can(Permission::ManageCarriers));
+
+    {
+        let app_state = app_state.clone();
+        use_effect_with((), move |_| {
+            fetch_carriers(&app_state);
+            || ()
+        });
+    }
+
+    let on_change = {
+        let form = form.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let mut form_data = (*form).clone();
+            match input.id().as_str() {
+                "scac" => form_data.scac = input.value(),
+                "name" => form_data.name = input.value(),
+                "dispatch_emails" => form_data.dispatch_emails = input.value(),
+                "phone" => form_data.phone = input.value(),
+                "default_time" => form_data.default_time = input.value(),
+                "default_door" => form_data.default_door = input.value(),
+                "daily_rate" => form_data.daily_rate = input.value(),
+                _ => (),
+            }
+            form.set(form_data);
+        })
+    };
+
+    let on_search = {
+        let search = search.clone();
+        Callback::from(move |e: InputEvent| search.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+
+    let save = {
+        let app_state = app_state.clone();
+        let form = form.clone();
+        let errors = errors.clone();
+        Callback::from(move |_: MouseEvent| {
+            let carrier = match form.carrier() {
+                Ok(carrier) => carrier,
+                Err(problems) => {
+                    errors.set(problems);
+                    return;
+                },
+            };
+            let app_state = app_state.clone();
+            let form = form.clone();
+            let errors = errors.clone();
+            spawn_local(async move {
+                match Api::new(&app_state).set_carrier(&carrier).await {
+                    Ok(carriers) => {
+                        app_state.dispatch(AppStateAction::SetCarriers(carriers));
+                        form.set(CarrierForm::default());
+                        errors.set(vec![]);
+                    },
+                    Err(error) => {
+                        errors.set(vec![error.to_string()]);
+                        error.handle(&app_state);
+                    },
+                }
+            });
+        })
+    };
+
+    let edit = {
+        let form = form.clone();
+        let errors = errors.clone();
+        Callback::from(move |carrier: Carrier| {
+            form.set(CarrierForm::from_carrier(&carrier));
+            errors.set(vec![]);
+        })
+    };
+
+    let delete = {
+        let app_state = app_state.clone();
+        let errors = errors.clone();
+        Callback::from(move |scac: String| {
+            let app_state = app_state.clone();
+            let errors = errors.clone();
+            spawn_local(async move {
+                match Api::new(&app_state).delete_carrier(&CarrierRef { scac }).await {
+                    Ok(carriers) => app_state.dispatch(AppStateAction::SetCarriers(carriers)),
+                    Err(error) => {
+                        errors.set(vec![error.to_string()]);
+                        error.handle(&app_state);
+                    },
+                }
+            });
+        })
+    };
+
+    let clear = {
+        let form = form.clone();
+        let errors = errors.clone();
+        Callback::from(move |_: MouseEvent| {
+            form.set(CarrierForm::default());
+            errors.set(vec![]);
+        })
+    };
+
+    let shown = if search.trim().is_empty() {
+        app_state.carriers.carriers.iter().collect()
+    } else {
+        app_state.carriers.suggest(&search)
+    };
+    let editing = app_state.carriers.by_scac(&form.scac).is_some();
+
+    html! {
+        <div style="margin-top: 7vh; width: 90vw;">
+            <h1 style="text-align: center;">{"Carriers"}</h1>
+            <div style="margin: 2%; text-align: center;">
+                <input type="search" placeholder="SCAC or name" value={(*search).clone()} oninput={on_search} />
+            </div>
+            <table>
+                <thead>
+                    <tr style="text-align: center;">
+                        <th>{"SCAC"}</th>
+                        <th>{"Name"}</th>
+                        <th>{"Dispatch Emails"}</th>
+                        <th>{"Phone"}</th>
+                        <th>{"Default Time"}</th>
+                        <th>{"Default Door"}</th>
+                        <th>{"Daily Rate"}</th>
+                        if can_edit {
+                            <th></th>
+                        }
+                    </tr>
+                </thead>
+                <tbody>
+                { for shown.into_iter().map(|carrier| {
+                    let rate = carrier.daily_rate.unwrap_or_else(|| app_state.demurrage().daily_rate(&carrier.scac));
+                    html! {
+                        <tr style="text-align: center;">
+                            <td>{carrier.scac.clone()}</td>
+                            <td>{carrier.name.clone()}</td>
+                            <td>{carrier.contact_email()}</td>
+                            <td>{carrier.phone.clone()}</td>
+                            <td>{carrier.default_time.to_string()}</td>
+                            <td>{carrier.default_door.clone()}</td>
+                            // Rates that aren't the carrier's own come from config.json
+                            <td style={if carrier.daily_rate.is_some() { "" } else { "color: gray;" }}>{format!("${:.2}", rate)}</td>
+                            if can_edit {
+                                <td>
+                                    <a onclick={edit.reform({ let carrier = carrier.clone(); move |_| carrier.clone() })}>{"Edit"}</a>
+                                    {" "}
+                                    <a style="color: red;" onclick={delete.reform({ let scac = carrier.scac.clone(); move |_| scac.clone() })}>{"Delete"}</a>
+                                </td>
+                            }
+                        </tr>
+                    }
+                }) }
+                </tbody>
+            </table>
+            if can_edit {
+                <div style="margin-top: 3%; text-align: center;">
+                    <h3>{ if editing { format!("Edit {}", normalize_scac(&form.scac)) } else { "Add a Carrier".to_string() } }</h3>
+                    <div>
+                        <label for="scac">{"SCAC:"}</label>
+                        <input style="text-align: center;" id="scac" type="text" maxlength="4" value={form.scac.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="name">{"Name:"}</label>
+                        <input style="text-align: center;" id="name" type="text" value={form.name.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="dispatch_emails">{"Dispatch Emails:"}</label>
+                        <input style="text-align: center;" id="dispatch_emails" type="text" placeholder="Separated by commas" value={form.dispatch_emails.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="phone">{"Phone:"}</label>
+                        <input style="text-align: center;" id="phone" type="tel" value={form.phone.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="default_time">{"Default Time:"}</label>
+                        <input style="text-align: center;" id="default_time" type="time" value={form.default_time.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="default_door">{"Default Door:"}</label>
+                        <input style="text-align: center;" id="default_door" type="text" value={form.default_door.clone()} oninput={on_change.clone()} />
+                    </div>
+                    <div>
+                        <label for="daily_rate">{"Demurrage per Day:"}</label>
+                        <input style="text-align: center;" id="daily_rate" type="number" min="0" step="0.01" placeholder="From config" value={form.daily_rate.clone()} oninput={on_change.clone()} />
+                    </div>
+                    { for errors.iter().map(|error| html! { <p style="color: red;">{error.clone()}</p> }) }
+                    <button style="background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={save}>
+                        { if editing { "Save Carrier" } else { "Add Carrier" } }
+                    </button>
+                    {" "}
+                    <a onclick={clear}>{"Clear"}</a>
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use web_sys::{window, UrlSearchParams};
+use models::capacity::CapacityRules;
+use models::demurrage::DemurrageRates;
+use crate::plants::PlantRegistry;
+
+const CONFIG_FILE: &str = "config.json";
+const PROFILE_KEY: &str = "profile";
+
+// Endpoints for a single site. Every request the app makes is built from one of these.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub profile: String,
+    pub api_base: String,
+    pub upload_url: String,
+    pub ws_url: String,
+    // Only set when a site needs something other than the plants listed for every profile
+    #[serde(default)]
+    pub plants: PlantRegistry,
+    // Likewise for carrier demurrage rates
+    #[serde(default)]
+    pub demurrage: DemurrageRates,
+    // And for receiving capacity
+    #[serde(default)]
+    pub capacity: CapacityRules,
+}
+
+impl AppConfig {
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base.trim_end_matches('/'), path)
+    }
+}
+
+// Shape of config.json, served next to index.html
+#[derive(Deserialize, Clone, Debug)]
+pub struct ConfigFile {
+    pub default_profile: String,
+    pub profiles: HashMap<String, AppConfig>,
+    #[serde(default)]
+    pub plants: PlantRegistry,
+    #[serde(default)]
+    pub demurrage: DemurrageRates,
+    #[serde(default)]
+    pub capacity: CapacityRules,
+}
+
+impl ConfigFile {
+    // Picks the profile from ?profile=, then the last one used on this browser, then the file default
+    pub fn select(&self) -> Result<AppConfig, String> {
+        let requested = profile_from_query()
+            .or_else(load_profile_from_local_storage)
+            .filter(|name| self.profiles.contains_key(name))
+            .unwrap_or_else(|| self.default_profile.clone());
+        match self.profiles.get(&requested) {
+            Some(config) => {
+                save_profile_to_local_storage(&requested);
+                let plants = if config.plants.plants.is_empty() { self.plants.clone() } else { config.plants.clone() };
+                let demurrage = if config.demurrage.is_empty() { self.demurrage.clone() } else { config.demurrage.clone() };
+                let capacity = if config.capacity.is_empty() { self.capacity.clone() } else { config.capacity.clone() };
+                Ok(AppConfig { profile: requested, plants, demurrage, capacity, ..config.clone() })
+            },
+            None => Err(format!("Profile {} not found in {}", requested, CONFIG_FILE)),
+        }
+    }
+}
+
+pub async fn load_config() -> Result<AppConfig, String> {
+    let origin = window().unwrap().location().origin().map_err(|e| format!("{:?}", e))?;
+    let client = Client::new();
+    let config_file = client.get(format!("{}/{}", origin, CONFIG_FILE))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {:?}", CONFIG_FILE, e))?
+        .json::<ConfigFile>()
+        .await
+        .map_err(|e| format!("Failed to parse {}: {:?}", CONFIG_FILE, e))?;
+    config_file.select()
+}
+
+fn profile_from_query() -> Option<String> {
+    let search = window()?.location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()?.get(PROFILE_KEY)
+}
+
+fn save_profile_to_local_storage(profile: &str) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(PROFILE_KEY, profile);
+    }
+}
+
+fn load_profile_from_local_storage() -> Option<String> {
+    let storage = window()?.local_storage().ok()??;
+    storage.get_item(PROFILE_KEY).ok().flatten()
+}
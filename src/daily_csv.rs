@@ -1,48 +1,55 @@
-use crate::models::*;
+use crate::{api::Api, export::{download_csv, CsvExport, CsvOptions}, plants::PlantRegistry};
+use models::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{js_sys, window};
 use yew::prelude::*;
-use reqwest::Client;
-use gloo::console::log;
-use std::{fmt::Write, rc::Rc};
-use chrono::prelude::*;
+use std::rc::Rc;
+use models::time::CalendarDate;
 
 use crate::state::*;
 
-fn render_location(location: &String) -> String {
-    match location.as_str() {
-        "18008" => "AR".to_string(),
-        "18044" => "FF".to_string(),
-        "22010" => "40".to_string(),
-        _ => "".to_string()  
-    }
+// One line of the receipt file the WMS imports: a part on one of a trailer's SIDs
+pub struct ReceiptLine {
+    pub trailer_id: String,
+    pub cisco_id: String,
+    pub part: String,
+    pub quantity: i32,
 }
 
-fn format_current_date() -> String {
-    let local: DateTime<Local> = Local::now();
-    let year = local.year();
-    let month = format!("{:02}", local.month());
-    let day = format!("{:02}", local.day());
-    format!("{}{}{}", year, month, day)
+impl ReceiptLine {
+    pub fn from_sids(data: &[Sids]) -> Vec<ReceiptLine> {
+        data.iter()
+            .flat_map(|trailer_sid| trailer_sid.sids.iter().map(|sid_part| ReceiptLine {
+                trailer_id: trailer_sid.trailer_id.clone(),
+                cisco_id: sid_part.cisco.clone(),
+                part: sid_part.part.clone(),
+                quantity: sid_part.quantity,
+            }))
+            .collect()
+    }
 }
 
-fn format_date() -> String {
-    let local: DateTime<Local> = Local::now();
-    let year = local.year();
-    let month = format!("{:02}", local.month());
-    let day = format!("{:02}", local.day());
-    format!("{}-{}-{}", year, month, day)
-}
+impl CsvExport for ReceiptLine {
+    type Context = PlantRegistry;
+
+    const COLUMNS: &'static [&'static str] = &[
+        "Receipt", "Part", "Quantity", "Warehouse", "Type", "Reference", "Plant", "Date", "Trailer", "Count",
+    ];
 
-fn create_csv(data: &Vec<Sids>) -> String {
-    let mut csv_string = String::new();
-    for trailer_sid in data {
-        for sid_part in trailer_sid.Sids.iter() {
-            let s = format!("{}{}", trailer_sid.TrailerID, render_location(&sid_part.Cisco));
-            let _ = writeln!(csv_string, "{},{},{},DAL,P, ,{},{},{},1", s, sid_part.Part, sid_part.Quantity, render_location(&sid_part.Cisco), format_current_date(), trailer_sid.TrailerID);
-        }
+    fn fields(&self, plants: &PlantRegistry) -> Vec<String> {
+        let plant = plants.location(&self.cisco_id);
+        vec![
+            format!("{}{}", self.trailer_id, plant),
+            self.part.clone(),
+            self.quantity.to_string(),
+            "DAL".to_string(),
+            "P".to_string(),
+            " ".to_string(),
+            plant,
+            CalendarDate::today().format("%Y%m%d"),
+            self.trailer_id.clone(),
+            "1".to_string(),
+        ]
     }
-    csv_string
 }
 
 #[function_component(DailyCsv)]
@@ -57,29 +64,14 @@ pub fn daily_csv() -> Html {
             let details = details.clone();
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
                 let request = TodaysTrucksRequest {
-                    date: format_date()
+                    date: CalendarDate::today(),
                 };
-                if let Some(user) = &app_state.user {
-                    match client.post("http://192.168.4.172:8000/api/trailers")
-                        .json(&request)
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<Sids>>().await {
-                                    Ok(csv_response) => {
-                                        details.set(Rc::new(csv_response));
-                                    },
-                                    Err(error) => log!(format!("{:?}", error))
-                                }
-                            },
-                            Err(error) => {
-                                log!(format!("Failed to login: {:?}", error));
-                                app_state.dispatch(AppStateAction::ClearUser);
-                            },
-                        }
+                match Api::new(&app_state).trailers(&request).await {
+                    Ok(csv_response) => {
+                        details.set(Rc::new(csv_response));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
             || ()
@@ -88,20 +80,9 @@ pub fn daily_csv() -> Html {
 
     let download_csv = {
         let data = details.clone();
+        let plants = app_state.plants().clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data);
-            let filename = "daily.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("daily.csv", &ReceiptLine::from_sids(&data), &plants, &CsvOptions::default().without_header());
         })
     };
 
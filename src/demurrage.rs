@@ -0,0 +1,187 @@
+use yew::prelude::*;
+use models::*;
+use models::demurrage::{at_risk, DemurrageRates, Exposure, LfdStatus};
+use models::time::CalendarDate;
+use crate::{AppStateAction, AppStateContext, route::Route, plants::PlantRegistry};
+use crate::export::{download_csv, CsvExport, CsvOptions};
+use crate::xlsx::{download_xlsx, Cell, XlsxExport};
+use rust_xlsxwriter::Color;
+
+fn money(amount: f64) -> String {
+    format!("${:.2}", amount)
+}
+
+fn days(count: i64) -> String {
+    if count == 1 { "1 day".to_string() } else { format!("{} days", count) }
+}
+
+// Short description and colours for a trailer's badge, or nothing when it isn't at risk
+fn badge(exposure: &Exposure, rates: &DemurrageRates) -> Option<(String, &'static str, &'static str)> {
+    match exposure.status(rates) {
+        LfdStatus::Overdue => Some((format!("{} over, {}", days(-exposure.days_left), money(exposure.accrued)), "#B71C1C", "white")),
+        LfdStatus::DueToday => Some(("LFD today".to_string(), "#FF5722", "white")),
+        LfdStatus::Approaching => Some((format!("{} left", days(exposure.days_left)), "#FFC107", "black")),
+        LfdStatus::Clear if exposure.forecast > 0.0 => Some((format!("{} if on schedule", money(exposure.forecast)), "#FFC107", "black")),
+        LfdStatus::Clear => None,
+    }
+}
+
+// The Last Free Day cell for the trailer tables, badged when the trailer is at risk
+pub fn lfd_cell(trailer: &TrailerResponse, rates: &DemurrageRates) -> Html {
+    let badge = Exposure::for_trailer(trailer, rates, CalendarDate::today())
+        .and_then(|exposure| badge(&exposure, rates));
+    html! {
+        <td>
+            {trailer.schedule.last_free_date.to_string()}
+            { if let Some((text, background, color)) = badge {
+                html! {
+                    <span style={format!("display: block; margin-top: 2px; padding: 2px 6px; border-radius: 4px; font-size: 0.8em; background-color: {}; color: {};", background, color)}>{text}</span>
+                }
+            } else {
+                html! {<></>}
+            }}
+        </td>
+    }
+}
+
+// One line of the at-risk report
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtRisk {
+    pub trailer: TrailerResponse,
+    pub exposure: Exposure,
+}
+
+impl CsvExport for AtRisk {
+    type Context = PlantRegistry;
+
+    const COLUMNS: &'static [&'static str] = &["Trailer", "SCAC", "Plant", "Last Free Date", "Scheduled Date", "Days Left", "Daily Rate", "Accrued", "Forecast"];
+
+    fn fields(&self, plants: &PlantRegistry) -> Vec<String> {
+        self.cells(plants).iter().map(|cell| cell.to_string()).collect()
+    }
+}
+
+impl XlsxExport for AtRisk {
+    fn cells(&self, plants: &PlantRegistry) -> Vec<Cell> {
+        let schedule = &self.trailer.schedule;
+        vec![
+            self.trailer.trailer_id.clone().into(),
+            schedule.carrier_code.clone().into(),
+            plants.locations(&self.trailer.cisco_ids).into(),
+            schedule.last_free_date.into(),
+            schedule.schedule_date.into(),
+            Cell::Number(self.exposure.days_left as f64),
+            self.exposure.daily_rate.into(),
+            self.exposure.accrued.into(),
+            self.exposure.forecast.into(),
+        ]
+    }
+
+    fn fill(&self, column: &str, _: &PlantRegistry) -> Option<Color> {
+        match column {
+            "Days Left" if self.exposure.days_left < 0 => Some(Color::RGB(0xFFC7CE)),
+            "Days Left" if self.exposure.days_left == 0 => Some(Color::RGB(0xFFEB9C)),
+            _ => None,
+        }
+    }
+}
+
+#[function_component(Demurrage)]
+pub fn demurrage() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+
+    let rates = app_state.demurrage().clone();
+    let rows: Vec<AtRisk> = at_risk(&app_state.trailers, &rates, CalendarDate::today())
+        .into_iter()
+        .map(|(trailer, exposure)| AtRisk { trailer: trailer.clone(), exposure })
+        .collect();
+    let accrued: f64 = rows.iter().map(|row| row.exposure.accrued).sum();
+    let forecast: f64 = rows.iter().map(|row| row.exposure.forecast).sum();
+
+    let load = {
+        let app_state = app_state.clone();
+        Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::LoadDetails(trailer.trailer_id.clone());
+            app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
+            app_state.dispatch(AppStateAction::Navigate(route));
+        })
+    };
+
+    let download_csv = {
+        let rows = rows.clone();
+        let plants = app_state.plants().clone();
+        Callback::from(move |_: MouseEvent| {
+            download_csv(&format!("demurrage_{}.csv", CalendarDate::today()), &rows, &plants, &CsvOptions::default().with_bom());
+        })
+    };
+
+    let download_xlsx = {
+        let rows = rows.clone();
+        let plants = app_state.plants().clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx(&format!("demurrage_{}.xlsx", CalendarDate::today()), "Demurrage", &rows, &plants, &CsvOptions::default());
+        })
+    };
+
+    html! {
+        <div style="margin-top: 7vh; width: 90vw;">
+            <h1 style="text-align: center;">{"Demurrage"}</h1>
+            <div style="margin: 2%; display: flex; flex-direction: row; justify-content: space-evenly; align-items: center;">
+                <p>{format!("{} trailers at risk", rows.len())}</p>
+                <p>{format!("Accrued so far: {}", money(accrued))}</p>
+                <p>{format!("Forecast by arrival: {}", money(forecast))}</p>
+                { if rows.is_empty() {
+                    html! {<></>}
+                } else {
+                    html! {
+                        <>
+                            <a onclick={download_csv}>{"Download CSV"}</a>
+                            <a onclick={download_xlsx}>{"Download Excel"}</a>
+                        </>
+                    }
+                }}
+            </div>
+            <p style="text-align: center;">{format!("Unarrived trailers past their last free date, within {} of it, or scheduled to arrive after it", days(rates.warning_days))}</p>
+            { if rates.is_empty() {
+                html! { <p style="text-align: center; color: orange;">{"No demurrage rates in config.json, so charges show as $0.00"}</p> }
+            } else {
+                html! {<></>}
+            }}
+            <table>
+                <thead>
+                    <tr style="text-align: center;">
+                        <th>{"#"}</th>
+                        <th>{"Trailer ID"}</th>
+                        <th>{"SCAC"}</th>
+                        <th>{"Plant"}</th>
+                        <th>{"Last Free Day"}</th>
+                        <th>{"Scheduled Date"}</th>
+                        <th>{"Days Left"}</th>
+                        <th>{"Daily Rate"}</th>
+                        <th>{"Accrued"}</th>
+                        <th>{"Forecast"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                { for rows.iter().enumerate().map(|(index, row)| {
+                    let tr = row.trailer.clone();
+                    html! {
+                        <tr style="text-align: center;">
+                            <td>{index + 1}</td>
+                            <td><a onclick={load.reform(move |_| tr.clone())}>{row.trailer.trailer_id.clone()}</a></td>
+                            <td>{row.trailer.schedule.carrier_code.clone()}</td>
+                            <td>{app_state.plants().locations(&row.trailer.cisco_ids)}</td>
+                            { lfd_cell(&row.trailer, &rates) }
+                            <td>{row.trailer.schedule.schedule_date.to_string()}</td>
+                            <td>{row.exposure.days_left}</td>
+                            <td>{money(row.exposure.daily_rate)}</td>
+                            <td>{money(row.exposure.accrued)}</td>
+                            <td>{money(row.exposure.forecast)}</td>
+                        </tr>
+                    }
+                }) }
+                </tbody>
+            </table>
+        </div>
+    }
+}
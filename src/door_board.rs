@@ -0,0 +1,190 @@
+use chrono::{Duration, NaiveDateTime};
+use gloo::timers::callback::Interval;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use models::{Shipment, TrailerResponse};
+use models::door_board::{sort_doors, same_door, DoorBoard, DoorBooking, DEFAULT_SLOT_MINUTES};
+use models::time::Timestamp;
+use crate::{api::Api, state::{AppState, AppStateContext}, AppStateAction};
+
+// The board only changes on its own as time passes, so a minute is fine
+const REFRESH_MS: u32 = 60_000;
+
+pub fn now() -> NaiveDateTime {
+    Timestamp::now().at().unwrap_or_default()
+}
+
+pub fn door_board(app_state: &AppState) -> DoorBoard {
+    DoorBoard::new(&app_state.trailers, &app_state.shipments, Duration::minutes(DEFAULT_SLOT_MINUTES), now())
+}
+
+// Conflicts are only as good as what's loaded, and the trailer and shipment screens each only
+// load their own half. Fetches whichever half isn't here yet.
+pub fn load_door_data(app_state: &AppStateContext) {
+    if app_state.trailers.is_empty() {
+        fetch_trailers(app_state);
+    }
+    if app_state.shipments.is_empty() {
+        fetch_shipments(app_state);
+    }
+}
+
+fn fetch_trailers(app_state: &AppStateContext) {
+    let app_state = app_state.clone();
+    spawn_local(async move {
+        match Api::new(&app_state).schedule_trailer().await {
+            Ok(trailers) => app_state.dispatch(AppStateAction::SetTrailers(trailers)),
+            Err(error) => error.handle(&app_state),
+        }
+    });
+}
+
+fn fetch_shipments(app_state: &AppStateContext) {
+    let app_state = app_state.clone();
+    spawn_local(async move {
+        match Api::new(&app_state).get_shipments().await {
+            Ok(shipments) => app_state.dispatch(AppStateAction::SetShipments(shipments)),
+            Err(error) => error.handle(&app_state),
+        }
+    });
+}
+
+fn window_text(booking: &DoorBooking) -> String {
+    let format = if booking.start.date() == now().date() { "%H:%M" } else { "%m/%d %H:%M" };
+    format!("{} - {}", booking.start.format(format), booking.end.format("%H:%M"))
+}
+
+// Shown under a door field when the door is taken. Doesn't stop the save: the dock lead may
+// know the other trailer is about to pull out.
+fn door_warning(conflicts: &[&DoorBooking]) -> Html {
+    if conflicts.is_empty() {
+        return html! {<></>};
+    }
+    html! {
+        <div style="color: orange; font-weight: bold; margin: 1%;">
+            { for conflicts.iter().map(|booking| html! {
+                <p>{format!(
+                    "Door {} is {} {} ({}, {})",
+                    booking.door,
+                    if booking.on_door { "occupied by" } else { "booked for" },
+                    booking.occupant.label(),
+                    booking.occupant.direction(),
+                    window_text(booking),
+                )}</p>
+            }) }
+        </div>
+    }
+}
+
+// The warning for a trailer as the edit form currently has it
+pub fn trailer_door_warning(app_state: &AppState, trailer: &TrailerResponse) -> Html {
+    let slot = Duration::minutes(DEFAULT_SLOT_MINUTES);
+    match DoorBooking::for_trailer(trailer, slot, now()) {
+        Some(candidate) => door_warning(&door_board(app_state).conflicts(&candidate)),
+        None => html! {<></>},
+    }
+}
+
+// The warning for a load as the door screen currently has it
+pub fn shipment_door_warning(app_state: &AppState, shipment: &Shipment) -> Html {
+    let slot = Duration::minutes(DEFAULT_SLOT_MINUTES);
+    match DoorBooking::for_shipment(shipment, slot, now()) {
+        Some(candidate) => door_warning(&door_board(app_state).conflicts(&candidate)),
+        None => html! {<></>},
+    }
+}
+
+fn booking_cell(booking: Option<&DoorBooking>) -> Html {
+    match booking {
+        Some(booking) => html! {
+            <td>
+                <p style="margin: 0;">{booking.occupant.label()}</p>
+                <p style="margin: 0; font-size: 0.8em;">{format!("{}, {}", booking.occupant.direction(), window_text(booking))}</p>
+            </td>
+        },
+        None => html! { <td>{"-"}</td> },
+    }
+}
+
+#[function_component(DoorBoardView)]
+pub fn door_board_view() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+    let refresh = use_force_update();
+
+    {
+        let app_state = app_state.clone();
+        use_effect_with(app_state.sync_generation, move |_| {
+            fetch_trailers(&app_state);
+            fetch_shipments(&app_state);
+            || ()
+        });
+    }
+
+    {
+        use_effect_with((), move |_| {
+            let interval = Interval::new(REFRESH_MS, move || refresh.force_update());
+            move || drop(interval)
+        });
+    }
+
+    let now = now();
+    let board = door_board(&app_state);
+    let double_booked = board.double_booked();
+
+    // Every door in the plant registry, then any door something is booked on that isn't listed
+    let mut doors: Vec<(String, String)> = app_state.plants().docks()
+        .flat_map(|dock| dock.doors.iter().map(|door| (door.clone(), dock.code.clone())))
+        .collect();
+    let mut unlisted: Vec<String> = board.doors().into_iter()
+        .filter(|door| !doors.iter().any(|(listed, _)| same_door(listed, door)))
+        .collect();
+    sort_doors(&mut unlisted);
+    doors.extend(unlisted.into_iter().map(|door| (door, String::new())));
+
+    html! {
+        <div style="margin-top: 7vh; width: 90vw;">
+            <h1 style="text-align: center;">{"Door Board"}</h1>
+            { if double_booked.is_empty() {
+                html! {<></>}
+            } else {
+                html! {
+                    <div style="text-align: center; color: red; font-weight: bold;">
+                        { for double_booked.iter().map(|(a, b)| html! {
+                            <p>{format!("Door {} double-booked: {} ({}) and {} ({})", a.door, a.occupant.label(), window_text(a), b.occupant.label(), window_text(b))}</p>
+                        }) }
+                    </div>
+                }
+            }}
+            <table>
+                <thead>
+                    <tr style="text-align: center;">
+                        <th>{"Door"}</th>
+                        <th>{"Dock"}</th>
+                        <th>{"Now"}</th>
+                        <th>{"Next"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                { for doors.iter().map(|(door, dock)| {
+                    let current = board.current(door, now);
+                    let clash = double_booked.iter().any(|(a, _)| same_door(&a.door, door));
+                    let style = match current {
+                        _ if clash => "text-align: center; background-color: #F44336; color: white;",
+                        Some(booking) if booking.on_door => "text-align: center; background-color: #FFC107;",
+                        Some(_) => "text-align: center; background-color: #FFF3CD;",
+                        None => "text-align: center;",
+                    };
+                    html! {
+                        <tr style={style}>
+                            <td>{door.clone()}</td>
+                            <td>{dock.clone()}</td>
+                            { booking_cell(current) }
+                            { booking_cell(board.next(door, now)) }
+                        </tr>
+                    }
+                }) }
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -1,18 +1,65 @@
-use serde_json::json;
+use std::str::FromStr;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use reqwest::Client;
-use gloo::console::log;
-use crate::{models::*, state::AppStateContext, AppStateAction};
-use chrono::prelude::*;
+use crate::{api::{Api, ApiError}, state::AppStateContext, AppStateAction, route::Route, door_board::{load_door_data, trailer_door_warning}, capacity::{capacity_warning, check_capacity, CapacityProblem}, ics::{download_ics, ics_filename, trailer_appointment}, carriers::{scac_options, scac_warning, SCAC_LIST}};
+use models::*;
+use models::ws_event::WsEvent;
+use models::capacity::Appointment;
+use models::carrier::{normalize_scac, scac_error};
+use models::time::{CalendarDate, TimeOfDay};
 
-fn format_date() -> String {
-    let local = Local::now();
-    let year = local.year();
-    let month = local.month();
-    let day = local.day();
-    format!("{}/{}/{}", month, day, year)
+// Dates and times are typed in, so a typo stops the save rather than clearing the schedule
+fn form_request(trailer_id: &str, form: &MyFormData) -> Result<SetScheduleRequest, String> {
+    Ok(SetScheduleRequest {
+        trailer_id: trailer_id.to_string(),
+        schedule_date: form.schedule_date.parse()?,
+        request_date: CalendarDate::today(),
+        carrier_code: normalize_scac(&form.scac),
+        schedule_time: form.schedule_time.parse()?,
+        last_free_date: form.last_free_date.parse()?,
+        contact_email: form.contact_email.clone(),
+        door: form.door.clone(),
+    })
+}
+
+// The trailer as the form would save it. Anything that doesn't parse yet keeps what the
+// trailer has, the error under the box is what stops it being saved.
+fn edited_trailer(trailer: &TrailerResponse, form: &MyFormData) -> TrailerResponse {
+    let mut edited = trailer.clone();
+    edited.schedule.door_number = form.door.clone();
+    edited.schedule.schedule_date = form.schedule_date.parse().unwrap_or(trailer.schedule.schedule_date);
+    edited.schedule.schedule_time = form.schedule_time.parse().unwrap_or(trailer.schedule.schedule_time);
+    edited
+}
+
+// Shown under a date or time box that doesn't parse
+fn parse_error<T: FromStr<Err = String>>(value: &str) -> Html {
+    match value.parse::<T>() {
+        Ok(_) => html! {<></>},
+        Err(error) => html! { <p style="color: red;">{error}</p> },
+    }
+}
+
+// Saves a trailer's schedule, tells every other screen and remembers it under Recently
+// Scheduled. Anything that schedules a trailer goes through here so they all send the same thing.
+pub async fn save_schedule(app_state: &AppStateContext, request: SetScheduleRequest) -> Result<(), ApiError> {
+    Api::new(app_state).set_schedule(&request).await?;
+    let recent = RecentTrailers {
+        trailer_id: request.trailer_id.clone(),
+        date: request.schedule_date.to_string(),
+        time: request.schedule_time.to_string(),
+        scac: request.carrier_code.clone(),
+        door: request.door.clone(),
+        contact_email: request.contact_email.clone(),
+        cisco_ids: app_state.trailers.iter()
+            .find(|trailer| trailer.trailer_id == request.trailer_id)
+            .map(|trailer| trailer.cisco_ids.clone())
+            .unwrap_or_default(),
+    };
+    app_state.send_ws_message(WsEvent::ScheduleTrailer(request));
+    app_state.dispatch(AppStateAction::AddToRecentlyScheduled(recent));
+    Ok(())
 }
 
 #[function_component(EditTrailer)]
@@ -20,17 +67,33 @@ pub fn edit_trailer() -> Html {
     let app_state = use_context::<AppStateContext>().expect("no state found");
     let trailer = app_state.current_trailer.as_ref().unwrap().clone();
     let form = use_state(|| MyFormData {
-        door: trailer.Schedule.DoorNumber,
-        contact_email: trailer.Schedule.ContactEmail,
-        schedule_date: trailer.Schedule.ScheduleDate,
-        schedule_time: trailer.Schedule.ScheduleTime,
-        last_free_date: trailer.Schedule.LastFreeDate,
-        scac: trailer.Schedule.CarrierCode,
+        door: trailer.schedule.door_number,
+        contact_email: trailer.schedule.contact_email,
+        schedule_date: trailer.schedule.schedule_date.to_string(),
+        schedule_time: trailer.schedule.schedule_time.to_string(),
+        last_free_date: trailer.schedule.last_free_date.to_string(),
+        scac: trailer.schedule.carrier_code,
     });
 
+    {
+        let app_state = app_state.clone();
+        use_effect_with((), move |_| {
+            load_door_data(&app_state);
+            || ()
+        });
+    }
+
+    let capacity_problem = use_state(|| None::<CapacityProblem>);
+
+    // To check its door against everything else
+    let edited = edited_trailer(app_state.current_trailer.as_ref().unwrap(), &form);
+
     let on_change = {
+        let app_state = app_state.clone();
         let form = form.clone();
+        let capacity_problem = capacity_problem.clone();
         Callback::from(move |e: InputEvent| {
+            capacity_problem.set(None);
             let input = e.target_unchecked_into::<HtmlInputElement>();
             let id = input.id();
             let value = input.value();
@@ -43,7 +106,22 @@ pub fn edit_trailer() -> Html {
                     "schedule_date" => form_data.schedule_date = value,
                     "schedule_time" => form_data.schedule_time = value,
                     "last_free_date" => form_data.last_free_date = value,
-                    "scac" => form_data.scac = value,
+                    "scac" => {
+                        // Picking a known carrier brings its contact, and its usual slot where
+                        // the trailer doesn't have one yet
+                        if let Some(carrier) = app_state.carriers.by_scac(&value) {
+                            if !carrier.dispatch_emails.is_empty() {
+                                form_data.contact_email = carrier.contact_email();
+                            }
+                            if form_data.door.is_empty() {
+                                form_data.door = carrier.default_door.clone();
+                            }
+                            if form_data.schedule_time.is_empty() {
+                                form_data.schedule_time = carrier.default_time.to_string();
+                            }
+                        }
+                        form_data.scac = value;
+                    },
                     _ => (),
                 }
                 form_data
@@ -53,164 +131,125 @@ pub fn edit_trailer() -> Html {
 
     let schedule_trailer = {
         let app_state = app_state.clone();
+        let trailer_id = trailer.trailer_id.clone();
         let form = form.clone();
-        Callback::from(move |_| {
+        let capacity_problem = capacity_problem.clone();
+        Callback::from(move |_: ()| {
+            // The warning under the box already says why
+            if !form.scac.trim().is_empty() && scac_error(&form.scac).is_some() {
+                return;
+            }
+            let Ok(request) = form_request(&trailer_id, &form) else {
+                return;
+            };
             let app_state = app_state.clone();
             let form = form.clone();
-            let date = format_date();
+            let capacity_problem = capacity_problem.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(trailer) = &app_state.current_trailer {
-                    if let Some(user) = &app_state.user {
-                        let request = SetScheduleRequest {
-                            TrailerID: trailer.TrailerID.clone(),
-                            ScheduleDate: form.schedule_date.clone(),
-                            RequestDate: date.clone(),
-                            CarrierCode: form.scac.clone(),
-                            ScheduleTime: form.schedule_time.clone(),
-                            LastFreeDate: form.last_free_date.clone(),
-                            ContactEmail: form.contact_email.clone(),
-                            Door: form.door.clone(),
-                        };
-                        let recent = RecentTrailers {
-                            trailer_id: trailer.TrailerID.clone(),
-                            date: form.schedule_date.clone(),
-                            time: form.schedule_time.clone(),
-                            scac: form.scac.clone(),
-                        };
-                        match client.post("http://192.168.4.172:8000/api/set_schedule")
-                            .header("Authorization", format!("Bearer {}", user.token))
-                            .json(&request)
-                            .send()
-                            .await {
-                                Ok(resp) => {
-                                    match resp.json::<Vec<TrailerSchedule>>().await {
-                                        Ok(_trailer_response) => {
-                                            let msg = SetScheduleRequest {
-                                                TrailerID: trailer.TrailerID.clone(),
-                                                ScheduleDate: form.schedule_date.clone(),
-                                                RequestDate: date,
-                                                CarrierCode: form.scac.clone(),
-                                                ScheduleTime: form.schedule_time.clone(),
-                                                LastFreeDate: form.last_free_date.clone(),
-                                                ContactEmail: form.contact_email.clone(),
-                                                Door: form.door.clone(),
-                                            };
-                                            let json_string = serde_json::to_string(&msg).unwrap();
-                                            let message = json!({
-                                                "type": "schedule_trailer",
-                                                "data": {
-                                                    "message": json_string
-                                                }
-                                            }).to_string();
-                                            app_state.send_ws_message(&message);
-                                            app_state.dispatch(AppStateAction::AddToRecentlyScheduled(recent));
-                                            app_state.dispatch(AppStateAction::SetCurrentView("landing".to_string()));
-                                        },
-                                        Err(error) => {
-                                            log!(format!("{:?}", error));
-                                            app_state.dispatch(AppStateAction::ClearUser);
-                                        }
-                                    }
-                                },
-                                Err(error) => log!(format!("{:?}", error))
-                            }
+                let Some(trailer) = app_state.current_trailer.clone() else {
+                    return;
+                };
+                // Only a new slot is checked, so fixing the email on a trailer in an overbooked
+                // hour still saves
+                let edited = edited_trailer(&trailer, &form);
+                if edited.schedule.schedule_date != trailer.schedule.schedule_date
+                    || edited.schedule.schedule_time != trailer.schedule.schedule_time
+                    || edited.schedule.door_number != trailer.schedule.door_number
+                {
+                    match check_capacity(&app_state, &edited).await {
+                        Ok(None) => {},
+                        Ok(problem) => {
+                            capacity_problem.set(problem);
+                            return;
+                        },
+                        Err(error) => {
+                            error.handle(&app_state);
+                            return;
+                        },
                     }
                 }
+                match save_schedule(&app_state, request).await {
+                    Ok(()) => {
+                        if !Route::back() {
+                            app_state.dispatch(AppStateAction::Navigate(Route::Trailers));
+                        }
+                    },
+                    Err(error) => error.handle(&app_state),
+                }
             });
         })
     };
 
     let on_key_press = {
-        let app_state = app_state.clone();
-        let form = form.clone();
+        let schedule_trailer = schedule_trailer.clone();
         Callback::from(move |e: KeyboardEvent| {
             if e.key() == "Enter" {
-                let app_state = app_state.clone();
-                let form = form.clone();
-                let date = format_date();
-                spawn_local(async move {
-                    let client = Client::new();
-                    if let Some(trailer) = &app_state.current_trailer {
-                        if let Some(user) = &app_state.user {
-                            let request = SetScheduleRequest {
-                                TrailerID: trailer.TrailerID.clone(),
-                                ScheduleDate: form.schedule_date.clone(),
-                                RequestDate: date.clone(),
-                                CarrierCode: form.scac.clone(),
-                                ScheduleTime: form.schedule_time.clone(),
-                                LastFreeDate: form.last_free_date.clone(),
-                                ContactEmail: form.contact_email.clone(),
-                                Door: form.door.clone(),
-                            };
-                            let recent = RecentTrailers {
-                                trailer_id: trailer.TrailerID.clone(),
-                                date: form.schedule_date.clone(),
-                                time: form.schedule_time.clone(),
-                                scac: form.scac.clone(),
-                            };
-                            match client.post("https://192.168.4.160:8443/api/set_schedule")
-                                .header("Authorization", format!("Bearer {}", user.token))
-                                .json(&request)
-                                .send()
-                                .await {
-                                    Ok(resp) => {
-                                        match resp.json::<Vec<TrailerSchedule>>().await {
-                                            Ok(_trailer_response) => {
-                                                let msg = SetScheduleRequest {
-                                                    TrailerID: trailer.TrailerID.clone(),
-                                                    ScheduleDate: form.schedule_date.clone(),
-                                                    RequestDate: date,
-                                                    CarrierCode: form.scac.clone(),
-                                                    ScheduleTime: form.schedule_time.clone(),
-                                                    LastFreeDate: form.last_free_date.clone(),
-                                                    ContactEmail: form.contact_email.clone(),
-                                                    Door: form.door.clone(),
-                                                };
-                                                let json_string = serde_json::to_string(&msg).unwrap();
-                                                let message = json!({
-                                                    "type": "schedule_trailer",
-                                                    "data": {
-                                                        "message": json_string
-                                                    }
-                                                }).to_string();
-                                                app_state.send_ws_message(&message);
-                                                app_state.dispatch(AppStateAction::AddToRecentlyScheduled(recent));
-                                                app_state.dispatch(AppStateAction::SetCurrentView("landing".to_string()));
-                                            },
-                                            Err(error) => {
-                                                log!(format!("{:?}", error));
-                                                app_state.dispatch(AppStateAction::ClearUser);
-                                            }
-                                        }
-                                    },
-                                    Err(error) => log!(format!("{:?}", error))
-                                }
-                        }
-                    }
-                });
+                schedule_trailer.emit(());
+            }
+        })
+    };
+
+    let use_slot = {
+        let form = form.clone();
+        let capacity_problem = capacity_problem.clone();
+        Callback::from(move |slot: Appointment| {
+            let mut form_data = (*form).clone();
+            form_data.schedule_date = slot.date().to_string();
+            form_data.schedule_time = slot.time().to_string();
+            form.set(form_data);
+            capacity_problem.set(None);
+        })
+    };
+
+    // For the carrier, as the form stands and as the trailer was before it
+    let confirmation = {
+        let mut confirmed = edited.clone();
+        confirmed.schedule.carrier_code = normalize_scac(&form.scac);
+        trailer_appointment(&confirmed, app_state.plants(), false)
+    };
+    let cancellation = trailer_appointment(app_state.current_trailer.as_ref().unwrap(), app_state.plants(), true);
+    let has_confirmation = confirmation.is_some();
+    let download_confirmation = {
+        let filename = ics_filename(&trailer.trailer_id);
+        Callback::from(move |_: MouseEvent| {
+            if let Some(appointment) = &confirmation {
+                download_ics(&filename, std::slice::from_ref(appointment));
+            }
+        })
+    };
+    let download_cancellation = {
+        let filename = ics_filename(&trailer.trailer_id);
+        let cancellation = cancellation.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(appointment) = &cancellation {
+                download_ics(&filename, std::slice::from_ref(appointment));
             }
         })
     };
 
     html!{
         <div style="text-align: center;">
-            <h1>{ "Edit Trailer: " }{trailer.TrailerID.clone()}</h1>
+            <h1>{ "Edit Trailer: " }{trailer.trailer_id.clone()}</h1>
             <div>
                 <label for="scac">{"SCAC:"}</label>
-                <input style="text-align: center;" id="scac" type="text" value={form.scac.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
+                <input style="text-align: center;" id="scac" type="text" list={SCAC_LIST} value={form.scac.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
+                { scac_options(&app_state) }
+                { scac_warning(&app_state, &form.scac) }
             </div>
             <div>
                 <label for="last_free_date">{"Last Free Date:"}</label>
                 <input style="text-align: center;" id="last_free_date" type="date" value={form.last_free_date.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
+                { parse_error::<CalendarDate>(&form.last_free_date) }
             </div>
             <div>
                 <label for="schedule_date">{"Schedule Date:"}</label>
                 <input style="text-align: center;" id="schedule_date" type="date" value={form.schedule_date.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
+                { parse_error::<CalendarDate>(&form.schedule_date) }
             </div>
             <div>
                 <label for="schedule_time">{"Schedule Time:"}</label>
                 <input style="text-align: center;" id="schedule_time" type="text" value={form.schedule_time.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
+                { parse_error::<TimeOfDay>(&form.schedule_time) }
             </div>
             <div>
                 <label for="contact_email">{"Email:"}</label>
@@ -220,7 +259,24 @@ pub fn edit_trailer() -> Html {
                 <label for="door">{"Door:"}</label>
                 <input style="text-align: center;" id="door" type="text" value={form.door.clone()} oninput={on_change.clone()} onkeypress={on_key_press.clone()} />
             </div>
-            <button style="background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={schedule_trailer}>{"Set Details"}</button>
+            { trailer_door_warning(&app_state, &edited) }
+            { match &*capacity_problem {
+                Some(problem) => capacity_warning(problem, use_slot),
+                None => html! {<></>},
+            }}
+            <button style="background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={schedule_trailer.reform(|_| ())}>{"Set Details"}</button>
+            <div style="margin-top: 3%; display: flex; flex-direction: column; align-items: center; gap: 1rem;">
+                { if has_confirmation {
+                    html! { <a onclick={download_confirmation}>{"Download Carrier Confirmation (.ics)"}</a> }
+                } else {
+                    html! {<></>}
+                }}
+                { if cancellation.is_some() {
+                    html! { <a onclick={download_cancellation}>{"Download Cancellation (.ics)"}</a> }
+                } else {
+                    html! {<></>}
+                }}
+            </div>
         </div>
     }
 }
\ No newline at end of file
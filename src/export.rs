@@ -0,0 +1,229 @@
+use csv::{Terminator, WriterBuilder};
+use gloo::{console::log, timers::callback::Timeout};
+use web_sys::{js_sys, wasm_bindgen::{JsCast, JsValue}, window, Blob, BlobPropertyBag, HtmlElement, Url};
+
+// How long a downloaded file's blob is kept around for the browser to finish reading it
+const REVOKE_AFTER_MS: u32 = 60_000;
+
+// A row type that can be written out as CSV. Each screen implements this for whatever it
+// exports instead of formatting lines by hand, so quoting is always done by the csv writer.
+pub trait CsvExport {
+    // Anything beyond the row itself needed to fill in its columns, e.g. the plant registry
+    type Context;
+
+    // Column keys in their default order. Also the header text unless an export renames one.
+    const COLUMNS: &'static [&'static str];
+
+    // One value per entry in COLUMNS, in the same order
+    fn fields(&self, context: &Self::Context) -> Vec<String>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvOptions {
+    // Column keys to write, in order. Empty means all of them in their default order.
+    pub columns: Vec<&'static str>,
+    // Header text for columns that shouldn't just use their key
+    pub headers: Vec<(&'static str, &'static str)>,
+    // Files fed back into the WMS don't have a header row
+    pub header_row: bool,
+    // Excel only reads a CSV as UTF-8 when it starts with a byte order mark
+    pub bom: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            columns: vec![],
+            headers: vec![],
+            header_row: true,
+            bom: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn columns(mut self, columns: &[&'static str]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    pub fn header(mut self, column: &'static str, text: &'static str) -> Self {
+        self.headers.push((column, text));
+        self
+    }
+
+    pub fn without_header(mut self) -> Self {
+        self.header_row = false;
+        self
+    }
+
+    pub fn with_bom(mut self) -> Self {
+        self.bom = true;
+        self
+    }
+
+    // Where each chosen column sits in T::COLUMNS. Keys that aren't columns of T are dropped.
+    pub(crate) fn positions<T: CsvExport>(&self) -> Vec<usize> {
+        if self.columns.is_empty() {
+            return (0..T::COLUMNS.len()).collect();
+        }
+        self.columns.iter()
+            .filter_map(|column| T::COLUMNS.iter().position(|c| c == column))
+            .collect()
+    }
+
+    pub(crate) fn header_text(&self, column: &'static str) -> &'static str {
+        self.headers.iter()
+            .find(|(key, _)| *key == column)
+            .map(|(_, text)| *text)
+            .unwrap_or(column)
+    }
+}
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Quoted where needed and CRLF terminated, as RFC 4180 has it
+pub fn to_csv<T: CsvExport>(rows: &[T], context: &T::Context, options: &CsvOptions) -> Result<Vec<u8>, csv::Error> {
+    let positions = options.positions::<T>();
+    let mut writer = WriterBuilder::new()
+        .terminator(Terminator::CRLF)
+        .from_writer(if options.bom { BOM.to_vec() } else { vec![] });
+    if options.header_row {
+        writer.write_record(positions.iter().map(|i| options.header_text(T::COLUMNS[*i])))?;
+    }
+    for row in rows {
+        let fields = row.fields(context);
+        // A short row gets blanks rather than taking the whole export down
+        writer.write_record(positions.iter().map(|i| fields.get(*i).map_or("", String::as_str)))?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+pub fn download_csv<T: CsvExport>(filename: &str, rows: &[T], context: &T::Context, options: &CsvOptions) {
+    match to_csv(rows, context, options) {
+        Ok(contents) => download(filename, "text/csv;charset=utf-8", &contents),
+        Err(error) => log!(format!("Failed to build {}: {}", filename, error)),
+    }
+}
+
+// Hands the browser a file to save, by clicking a throwaway link to it
+pub fn download(filename: &str, mime: &str, contents: &[u8]) {
+    if let Err(error) = save_as(filename, mime, contents) {
+        log!(format!("Failed to download {}: {:?}", filename, error));
+    }
+}
+
+fn save_as(filename: &str, mime: &str, contents: &[u8]) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let properties = BlobPropertyBag::new();
+    properties.set_type(mime);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &properties)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let document = window().and_then(|window| window.document()).ok_or("no document")?;
+    let body = document.body().ok_or("no body")?;
+    let element = document.create_element("a")?;
+    element.set_attribute("href", &url)?;
+    element.set_attribute("download", filename)?;
+    body.append_child(&element)?;
+    element.unchecked_ref::<HtmlElement>().click();
+    body.remove_child(&element)?;
+    // Firefox and Safari read the blob after the click returns, so it has to outlive it
+    Timeout::new(REVOKE_AFTER_MS, move || {
+        if let Err(error) = Url::revoke_object_url(&url) {
+            log!(format!("Failed to release download: {:?}", error));
+        }
+    }).forget();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row {
+        name: &'static str,
+        note: &'static str,
+        qty: u32,
+    }
+
+    impl CsvExport for Row {
+        type Context = ();
+
+        const COLUMNS: &'static [&'static str] = &["Name", "Note", "Qty"];
+
+        fn fields(&self, _: &()) -> Vec<String> {
+            vec![self.name.to_string(), self.note.to_string(), self.qty.to_string()]
+        }
+    }
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row { name: "Bolt", note: "plain", qty: 10 },
+            Row { name: "Nut, hex", note: "says \"M8\"\nsecond line", qty: 4 },
+        ]
+    }
+
+    fn csv(options: &CsvOptions) -> String {
+        String::from_utf8(to_csv(&rows(), &(), options).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn quotes_commas_quotes_and_newlines() {
+        assert_eq!(
+            csv(&CsvOptions::default()),
+            "Name,Note,Qty\r\nBolt,plain,10\r\n\"Nut, hex\",\"says \"\"M8\"\"\nsecond line\",4\r\n",
+        );
+    }
+
+    #[test]
+    fn ends_every_row_with_crlf() {
+        let text = csv(&CsvOptions::default());
+        assert!(text.ends_with("\r\n"));
+        // The newline inside the quoted note is the only bare one
+        assert_eq!(text.matches("\r\n").count(), 3);
+        assert_eq!(text.matches('\n').count(), 4);
+    }
+
+    #[test]
+    fn starts_with_a_bom_only_when_asked() {
+        let with_bom = to_csv(&rows(), &(), &CsvOptions::default().with_bom()).unwrap();
+        assert!(with_bom.starts_with(BOM));
+        assert_eq!(&with_bom[BOM.len()..], csv(&CsvOptions::default()).as_bytes());
+        assert!(!to_csv(&rows(), &(), &CsvOptions::default()).unwrap().starts_with(BOM));
+    }
+
+    #[test]
+    fn picks_and_orders_columns() {
+        let options = CsvOptions::default().columns(&["Qty", "Name", "Missing"]).header("Qty", "Quantity");
+        assert_eq!(csv(&options), "Quantity,Name\r\n10,Bolt\r\n4,\"Nut, hex\"\r\n");
+    }
+
+    #[test]
+    fn leaves_the_header_off_when_asked() {
+        let options = CsvOptions::default().columns(&["Name"]).without_header();
+        assert_eq!(csv(&options), "Bolt\r\n\"Nut, hex\"\r\n");
+    }
+
+    struct Short;
+
+    impl CsvExport for Short {
+        type Context = ();
+
+        const COLUMNS: &'static [&'static str] = &["A", "B"];
+
+        fn fields(&self, _: &()) -> Vec<String> {
+            vec!["a".to_string()]
+        }
+    }
+
+    #[test]
+    fn blanks_columns_a_row_has_no_field_for() {
+        assert_eq!(String::from_utf8(to_csv(&[Short], &(), &CsvOptions::default()).unwrap()).unwrap(), "A,B\r\na,\r\n");
+    }
+
+    #[test]
+    fn writes_nothing_but_the_header_for_no_rows() {
+        assert_eq!(String::from_utf8(to_csv::<Row>(&[], &(), &CsvOptions::default()).unwrap()).unwrap(), "Name,Note,Qty\r\n");
+    }
+}
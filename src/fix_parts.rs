@@ -1,10 +1,9 @@
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, FileReader, HtmlInputElement, js_sys, window};
 use yew::prelude::*;
 use gloo::console::log;
-use std::{fmt::Write, rc::Rc};
-use crate::{models::ItemDetails, ItemMaster};
-use csv::ReaderBuilder;
+use crate::import::{CsvImport, CsvImportWizard};
+use crate::export::{download_csv, CsvExport, CsvOptions};
+use crate::xlsx::{download_xlsx, Cell, XlsxExport};
+use models::{ItemDetails, ItemMaster};
 use std::collections::HashMap;
 
 fn remove_brn(items: &Vec<ItemDetails>) -> Vec<ItemDetails> {
@@ -61,19 +60,72 @@ fn compare_items(item_master: &Vec<ItemMaster>, item_list: &Vec<ItemDetails>) ->
     new_list.clone()
 }
 
-fn create_csv(item_list: &Vec<ItemDetails>, item_master: &Vec<ItemMaster>) -> String {
-    let mut csv_string = String::new();
-    let new_list = compare_items(item_master, item_list);
-    for item in new_list {
-        let _ = writeln!(
-            csv_string,
-            "{},{},GM,,{},,A,{},{},{},1,1,1,1,1,{},{},{},{},{},{},{},{},{},{},",
-            item.part, item.desc, item.class, item.location, item.wide, item.size,
-            item.std_pk, item.pri_len, item.pri_wid, item.pri_hei, item.pri_wt,
-            item.pal_qty, item.pal_len, item.pal_wid, item.pal_hei, item.pal_wt
-        );
+// The item master import: no header row, and a few columns the WMS wants but we never change
+pub const ITEM_MASTER_COLUMNS: &[&str] = &[
+    "Part", "Description", "Company", "Blank", "Class", "Blank 2", "Velocity", "Location", "Wide", "Size",
+    "EA Qty", "EA Length", "EA Width", "EA Height", "EA Weight", "Std Pack",
+    "Primary Length", "Primary Width", "Primary Height", "Primary Weight",
+    "Pallet Qty", "Pallet Length", "Pallet Width", "Pallet Height", "Pallet Weight", "End",
+];
+
+impl CsvExport for ItemMaster {
+    type Context = ();
+
+    const COLUMNS: &'static [&'static str] = ITEM_MASTER_COLUMNS;
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        vec![
+            self.part.clone(), self.desc.clone(), "GM".to_string(), String::new(), self.class.clone(), String::new(), "A".to_string(),
+            self.location.clone(), self.wide.clone(), self.size.clone(),
+            "1".to_string(), "1".to_string(), "1".to_string(), "1".to_string(), "1".to_string(),
+            self.std_pk.to_string(), self.pri_len.to_string(), self.pri_wid.to_string(), self.pri_hei.to_string(), self.pri_wt.to_string(),
+            self.pal_qty.to_string(), self.pal_len.to_string(), self.pal_wid.to_string(), self.pal_hei.to_string(), self.pal_wt.to_string(),
+            String::new(),
+        ]
+    }
+}
+
+impl XlsxExport for ItemMaster {
+    fn cells(&self, _: &()) -> Vec<Cell> {
+        vec![
+            self.part.clone().into(), self.desc.clone().into(), "GM".into(), "".into(), self.class.clone().into(), "".into(), "A".into(),
+            self.location.clone().into(), self.wide.clone().into(), self.size.clone().into(),
+            Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0),
+            self.std_pk.into(), self.pri_len.into(), self.pri_wid.into(), self.pri_hei.into(), self.pri_wt.into(),
+            self.pal_qty.into(), self.pal_len.into(), self.pal_wid.into(), self.pal_hei.into(), self.pal_wt.into(),
+            "".into(),
+        ]
+    }
+}
+
+impl CsvImport for ItemDetails {
+    const FIELDS: &'static [&'static str] = &[
+        "ITEM", "VELOCITY", "EA QTY", "EA LENGTH", "EA WIDTH", "EA HEIGHT", "EA WEIGHT", "CTN QTY",
+        "CTN LENGTH", "CTN WIDTH", "CTN HEIGHT", "CTN WEIGHT", "PAL QTY", "PAL LENGTH", "PAL WIDTH",
+        "PAL HEIGHT", "PAL WEIGHT",
+    ];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.item.is_empty() {
+            return vec![("ITEM", "no part number".to_string())];
+        }
+        vec![]
+    }
+}
+
+impl CsvImport for ItemMaster {
+    const FIELDS: &'static [&'static str] = &[
+        "part", "desc", "company", "blank", "class", "blank2", "velocity", "location", "wide", "size",
+        "ea_qty", "ea_len", "ea_wid", "ea_hei", "ea_wt", "std_pk", "pri_len", "pri_wid", "pri_hei", "pri_wt",
+        "pal_qty", "pal_len", "pal_wid", "pal_hei", "pal_wt",
+    ];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.part.is_empty() {
+            return vec![("part", "no part number".to_string())];
+        }
+        vec![]
     }
-    csv_string
 }
 
 #[function_component(FixParts)]
@@ -81,131 +133,43 @@ pub fn fix_parts() -> Html {
     let data = use_state(|| vec![]);
     let data2 = use_state(|| vec![]);
 
-    let on_file_change = {
+    let on_import = {
         let data = data.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data = data.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<ItemDetails>::new();
-                        for result in rdr.deserialize() {
-                            match result {
-                                Ok(record) => new_data.push(record), // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data.set(new_data); // Store parsed data in state
-                    })
-                };
-
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
-        })
+        Callback::from(move |rows: Vec<ItemDetails>| data.set(rows))
     };
 
-    let on_file_change2 = {
+    let on_import2 = {
         let data2 = data2.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data2 = data2.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<ItemMaster>::new();
-                        for result in rdr.deserialize() {
-                            match result {
-                                Ok(record) => new_data.push(record), // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data2.set(new_data); // Store parsed data in state
-                    })
-                };
-
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
-        })
+        Callback::from(move |rows: Vec<ItemMaster>| data2.set(rows))
     };
 
     let download_csv = {
         let data = data.clone();
         let data2 = data2.clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data, &data2);
-            let filename = "ITEMS.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("ITEMS.csv", &compare_items(&data2, &data), &(), &CsvOptions::default().without_header());
+        })
+    };
+
+    let download_xlsx = {
+        let data = data.clone();
+        let data2 = data2.clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx("ITEMS.xlsx", "Items", &compare_items(&data2, &data), &(), &CsvOptions::default());
         })
     };
 
     html! {
         <div>
             <h1>{ "Restore Item Master" }</h1>
-            <input type="file" accept=".csv" onchange={on_file_change} />
+            <CsvImportWizard<ItemDetails> title="Item Details" on_import={on_import} />
             <ul>
                 { for data.iter().map(|item| html! {
                     <li>{ format!("Part: {}, Standard Pack: {}, Pallet Quantity: {}", 
                         item.item, item.ctn_qty, item.pal_qty)}</li>
                 }) }
             </ul>
-            <input type="file" accept=".csv" onchange={on_file_change2} />
+            <CsvImportWizard<ItemMaster> title="Item Master" on_import={on_import2} />
             <ul>
                 { for data2.iter().map(|item| html! {
                     <li>{
@@ -216,6 +180,7 @@ pub fn fix_parts() -> Html {
                 }) }
             </ul>
             <button onclick={download_csv}>{ "Download CSV" }</button>
+            <button onclick={download_xlsx}>{ "Download XLSX" }</button>
         </div>
     }
 }
\ No newline at end of file
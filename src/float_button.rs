@@ -1,5 +1,5 @@
 use yew::prelude::*;
-use crate::state::*;
+use crate::{route::Route, state::*};
 
 #[function_component(FloatingIcon)]
 pub fn floating_icon() -> Html {
@@ -26,13 +26,13 @@ pub fn floating_icon() -> Html {
 
     let on_click = {
         let app_state = app_state.clone();
-        Callback::from(move |view: String| {
-            app_state.dispatch(AppStateAction::SetCurrentView(view));
+        Callback::from(move |route: Route| {
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     html! {
-        <div style={inline_style} onclick={on_click.clone().reform(move |_| "new_shipment".to_string())}>
+        <div style={inline_style} onclick={on_click.clone().reform(move |_| Route::NewShipment)}>
             {"+"}
         </div>
     }
@@ -1,11 +1,11 @@
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, FileReader, HtmlInputElement, js_sys, window};
 use yew::prelude::*;
 use gloo::console::log;
-use std::{fmt::Write, rc::Rc};
-use crate::{models::GmapItem, ItemCompare, ScaleItem, ScaleItemMap};
-use csv::ReaderBuilder;
-use std::collections::HashMap;
+use crate::import::{CsvImport, CsvImportWizard};
+use crate::export::{download_csv, CsvExport, CsvOptions};
+use crate::xlsx::{add_sheet, download_workbook, Cell, XlsxExport};
+use rust_xlsxwriter::{Color, Workbook, XlsxError};
+use models::{GmapItem, ItemCompare, ScaleItem, ScaleItemMap};
+use std::collections::{BTreeMap, HashMap};
 
 fn merge_gmap_items(mut items: &Vec<GmapItem>) -> Vec<GmapItem> {
     // Create a HashMap to store unique items by part (or other unique identifier)
@@ -135,14 +135,98 @@ fn compare_items(gmap_items: &Vec<GmapItem>, scale_items: &Vec<ScaleItem>) -> Ve
     item_compare
 }
 
-fn create_csv(gmap_data: &Vec<GmapItem>, scale_data: &Vec<ScaleItem>) -> String {
-    let mut csv_string = String::new();
-    let _ = writeln!(csv_string, "Part Number, Scale OH, Scale AL, Scale Missing, Scale Actual, GMAP, Dif, In Transit, Plant, Plant DOH");
-    let compared = compare_items(gmap_data, scale_data);
+impl CsvExport for ItemCompare {
+    type Context = ();
+
+    const COLUMNS: &'static [&'static str] = &[
+        "Part Number", "Scale OH", "Scale AL", "Scale Missing", "Scale Actual", "GMAP", "Dif", "In Transit", "Plant", "Plant DOH",
+    ];
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        vec![
+            self.part.clone(),
+            self.scale_oh_quantity.to_string(),
+            self.scale_al_quantity.to_string(),
+            self.scale_missing_quantity.to_string(),
+            self.scale_actual_quantity.to_string(),
+            self.asl_quantity.to_string(),
+            (self.scale_actual_quantity - self.asl_quantity).to_string(),
+            self.in_transit.to_string(),
+            self.plant.clone(),
+            self.plant_doh.clone(),
+        ]
+    }
+}
+
+impl XlsxExport for ItemCompare {
+    fn cells(&self, _: &()) -> Vec<Cell> {
+        vec![
+            self.part.clone().into(),
+            self.scale_oh_quantity.into(),
+            self.scale_al_quantity.into(),
+            self.scale_missing_quantity.into(),
+            self.scale_actual_quantity.into(),
+            self.asl_quantity.into(),
+            (self.scale_actual_quantity - self.asl_quantity).into(),
+            self.in_transit.into(),
+            self.plant.clone().into(),
+            self.plant_doh.clone().into(),
+        ]
+    }
+
+    // Red where Scale is short of GMAP, amber where it's over
+    fn fill(&self, column: &str, _: &()) -> Option<Color> {
+        if column != "Dif" {
+            return None;
+        }
+        match (self.scale_actual_quantity - self.asl_quantity).signum() {
+            -1 => Some(Color::RGB(0xFFC7CE)),
+            1 => Some(Color::RGB(0xFFEB9C)),
+            _ => None,
+        }
+    }
+}
+
+// One sheet per plant, in plant order, so each plant can work its own list
+fn create_workbook(compared: Vec<ItemCompare>) -> Result<Workbook, XlsxError> {
+    let mut by_plant: BTreeMap<String, Vec<ItemCompare>> = BTreeMap::new();
     for item in compared {
-        let _ = writeln!(csv_string, "{},{},{},{},{},{},{},{},{},{}", item.part, item.scale_oh_quantity, item.scale_al_quantity, item.scale_missing_quantity, item.scale_actual_quantity, item.asl_quantity, item.scale_actual_quantity - item.asl_quantity, item.in_transit, item.plant, item.plant_doh);
+        by_plant.entry(item.plant.clone()).or_default().push(item);
+    }
+    let mut workbook = Workbook::new();
+    for (plant, items) in by_plant {
+        let name = if plant.is_empty() { "No Plant".to_string() } else { plant };
+        add_sheet(&mut workbook, &name, &items, &(), &CsvOptions::default())?;
+    }
+    Ok(workbook)
+}
+
+impl CsvImport for GmapItem {
+    const FIELDS: &'static [&'static str] = &[
+        "PLANT", "F/U", "PART", "PART NAME", "DUNS", "SUPPLIER NAME", "PLANT BANK", "PLANT BANK OVERRIDE",
+        "PLANT BANK OVERRIDE USER", "EFFECTIVE DATE", "PLANT CBAL", "PLANT DOH", "ASL BANK", "ASL QTY",
+        "Day 1 QTY OFFSET", "IN TRANSIT ASL TO PLANT",
+    ];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.part.is_empty() {
+            return vec![("PART", "no part number".to_string())];
+        }
+        vec![]
+    }
+}
+
+impl CsvImport for ScaleItem {
+    const FIELDS: &'static [&'static str] = &[
+        "ITEM", "LOCATION", "OH_QTY", "AL_QTY", "AV_QTY", "missing_quantity",
+    ];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.item.is_empty() {
+            return vec![("ITEM", "no part number".to_string())];
+        }
+        vec![]
     }
-    csv_string
 }
 
 #[function_component(Gmap)]
@@ -150,98 +234,14 @@ pub fn gmap() -> Html {
     let data = use_state(|| vec![]);
     let data2 = use_state(|| vec![]);
 
-    let on_file_change = {
+    let on_import = {
         let data = data.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data = data.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<GmapItem>::new();
-                        for result in rdr.deserialize() {
-                            match result {
-                                Ok(record) => new_data.push(record), // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data.set(new_data); // Store parsed data in state
-                    })
-                };
-
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
-        })
+        Callback::from(move |rows: Vec<GmapItem>| data.set(rows))
     };
 
-    let on_file_change2 = {
+    let on_import2 = {
         let data2 = data2.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data2 = data2.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<ScaleItem>::new();
-                        for result in rdr.deserialize() {
-                            match result {
-                                Ok(record) => new_data.push(record), // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data2.set(new_data); // Store parsed data in state
-                    })
-                };
-
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
-        })
+        Callback::from(move |rows: Vec<ScaleItem>| data2.set(rows))
     };
 
     let merge = {
@@ -264,26 +264,25 @@ pub fn gmap() -> Html {
         let data = data.clone();
         let data2 = data2.clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data, &data2);
-            let filename = "ITEMS.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("ITEMS.csv", &compare_items(&data, &data2), &(), &CsvOptions::default().with_bom());
+        })
+    };
+
+    let download_xlsx = {
+        let data = data.clone();
+        let data2 = data2.clone();
+        Callback::from(move |_: MouseEvent| {
+            match create_workbook(compare_items(&data, &data2)) {
+                Ok(mut workbook) => download_workbook("ITEMS.xlsx", &mut workbook),
+                Err(error) => log!(format!("Failed to build ITEMS.xlsx: {}", error)),
+            }
         })
     };
 
     html! {
         <div>
             <h1>{ "Item ASL CSV" }</h1>
-            <input type="file" accept=".csv" onchange={on_file_change} />
+            <CsvImportWizard<GmapItem> title="GMAP" on_import={on_import} />
             <button onclick={merge}>{ "Merge GMAP" }</button>
             <ul>
                 { for data.iter().map(|item| html! {
@@ -291,7 +290,7 @@ pub fn gmap() -> Html {
                         item.part, item.part_name, item.plant, item.asl_qty)}</li>
                 }) }
             </ul>
-            <input type="file" accept=".csv" onchange={on_file_change2} />
+            <CsvImportWizard<ScaleItem> title="Scale" on_import={on_import2} />
             <ul>
                 { for data2.iter().map(|item| html! {
                     <li>{
@@ -302,6 +301,7 @@ pub fn gmap() -> Html {
                 }) }
             </ul>
             <button onclick={download_csv}>{ "Download CSV" }</button>
+            <button onclick={download_xlsx}>{ "Download XLSX" }</button>
         </div>
     }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+use chrono::{Duration, Utc};
+use models::*;
+use models::door_board::DEFAULT_SLOT_MINUTES;
+use models::ics::{calendar, sequence, IcsAppointment};
+use crate::{export::download, plants::{Plant, PlantRegistry}};
+
+const ICS_MIME: &str = "text/calendar;charset=utf-8";
+
+// Names and addresses of the plants a trailer is going to, most trailers only having the one
+fn destination(cisco_ids: &[String], plants: &PlantRegistry) -> (String, String) {
+    let mut found: Vec<&Plant> = cisco_ids.iter().filter_map(|cisco_id| plants.by_cisco_id(cisco_id)).collect();
+    found.dedup_by(|a, b| a.code == b.code);
+    let names = found.iter().map(|plant| plant.name.as_str()).collect::<Vec<_>>().join(" / ");
+    let addresses = found.iter()
+        .map(|plant| plant.address.as_str())
+        .filter(|address| !address.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ");
+    (names, addresses)
+}
+
+// Nothing when the trailer doesn't have both a date and a time yet
+pub fn trailer_appointment(trailer: &TrailerResponse, plants: &PlantRegistry, cancelled: bool) -> Option<IcsAppointment> {
+    let schedule = &trailer.schedule;
+    let start = schedule.schedule_date.0?.and_time(schedule.schedule_time.0?);
+    let (plant, address) = destination(&trailer.cisco_ids, plants);
+    Some(IcsAppointment {
+        trailer_id: trailer.trailer_id.clone(),
+        start,
+        end: start + Duration::minutes(DEFAULT_SLOT_MINUTES),
+        door: schedule.door_number.clone(),
+        carrier_code: schedule.carrier_code.clone(),
+        plant,
+        address,
+        sequence: sequence(Utc::now()),
+        cancelled,
+    })
+}
+
+// Recently Scheduled only keeps what was sent, so fill in a trailer from that
+pub fn recent_appointment(recent: &RecentTrailers, plants: &PlantRegistry) -> Option<IcsAppointment> {
+    let trailer = TrailerResponse {
+        trailer_id: recent.trailer_id.clone(),
+        schedule: Schedule {
+            schedule_date: recent.date.parse().unwrap_or_default(),
+            schedule_time: recent.time.parse().unwrap_or_default(),
+            carrier_code: recent.scac.clone(),
+            contact_email: recent.contact_email.clone(),
+            door_number: recent.door.clone(),
+            ..Schedule::default()
+        },
+        cisco_ids: recent.cisco_ids.clone(),
+    };
+    trailer_appointment(&trailer, plants, false)
+}
+
+pub fn ics_filename(trailer_id: &str) -> String {
+    let id: String = trailer_id.trim().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    format!("appointment_{}.ics", id)
+}
+
+pub fn download_ics(filename: &str, appointments: &[IcsAppointment]) {
+    let contents = calendar(appointments, Utc::now().naive_utc());
+    download(filename, ICS_MIME, contents.as_bytes());
+}
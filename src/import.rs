@@ -0,0 +1,491 @@
+use std::rc::Rc;
+use csv::{ReaderBuilder, StringRecord, Terminator, WriterBuilder};
+use serde::de::DeserializeOwned;
+use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, File, FileReader, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use gloo::console::log;
+use crate::export::download;
+
+// How many rows of the file to show before anything is imported
+const PREVIEW_ROWS: usize = 5;
+// The error table stops here. The rejects download always has every row.
+const SHOWN_ERRORS: usize = 100;
+
+// A row type that can be read in from a CSV the user picks. Each screen implements this for
+// what it imports, and the wizard below does the reading, column mapping and validation.
+pub trait CsvImport: DeserializeOwned {
+    // The column names the type deserializes from, in the order the mapping lists them
+    const FIELDS: &'static [&'static str];
+
+    // Tidy a raw value before it's parsed, e.g. thousands separators in a quantity
+    fn clean(_field: &str, value: &str) -> String {
+        value.trim().to_string()
+    }
+
+    // Anything wrong with a row that parsed, as (field, reason)
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceRow {
+    // Line in the file, counting the header, so it matches what a spreadsheet shows
+    pub line: u64,
+    pub values: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SourceFile {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<SourceRow>,
+    // Lines the csv reader couldn't make sense of at all
+    pub unreadable: Vec<UnreadableLine>,
+}
+
+// Kept as it was in the file, so it can go in the rejects download
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnreadableLine {
+    pub error: RowError,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowError {
+    pub line: u64,
+    pub column: String,
+    pub reason: String,
+}
+
+// The result of checking every row against the mapping
+#[derive(Clone, Debug)]
+pub struct Checked<T> {
+    pub rows: Vec<T>,
+    pub errors: Vec<RowError>,
+    pub rejected: Vec<SourceRow>,
+}
+
+pub fn read_source(name: &str, text: &str, has_headers: bool) -> SourceFile {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut source = SourceFile { name: name.to_string(), ..Default::default() };
+    if has_headers {
+        if let Ok(headers) = reader.headers() {
+            source.headers = headers.iter().map(|header| header.trim().to_string()).collect();
+        }
+    }
+    for result in reader.records() {
+        match result {
+            Ok(record) => source.rows.push(SourceRow {
+                line: record.position().map(|pos| pos.line()).unwrap_or_default(),
+                values: record.iter().map(|value| value.to_string()).collect(),
+            }),
+            Err(error) => {
+                let line = error.position().map(|pos| pos.line()).unwrap_or_default();
+                source.unreadable.push(UnreadableLine {
+                    error: RowError { line, column: String::new(), reason: error.to_string() },
+                    text: text.lines().nth(line.saturating_sub(1) as usize).unwrap_or_default().to_string(),
+                });
+            },
+        }
+    }
+    // Without a header row the columns are just numbered
+    let width = source.rows.iter().map(|row| row.values.len()).max().unwrap_or_default();
+    for i in source.headers.len()..width {
+        source.headers.push(format!("Column {}", i + 1));
+    }
+    source
+}
+
+fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// Each field starts out on the source column with the same name, ignoring case and spacing. A
+// file with no matching names but the right number of columns is taken in order.
+pub fn auto_map<T: CsvImport>(headers: &[String]) -> Vec<Option<usize>> {
+    let mapping: Vec<Option<usize>> = T::FIELDS.iter()
+        .map(|field| headers.iter().position(|header| normalize(header) == normalize(field)))
+        .collect();
+    if mapping.iter().all(Option::is_none) && headers.len() == T::FIELDS.len() {
+        return (0..headers.len()).map(Some).collect();
+    }
+    mapping
+}
+
+pub fn check<T: CsvImport>(source: &SourceFile, mapping: &[Option<usize>]) -> Checked<T> {
+    let fields = StringRecord::from(T::FIELDS.to_vec());
+    let errors = source.unreadable.iter().map(|line| line.error.clone()).collect();
+    let mut checked = Checked { rows: vec![], errors, rejected: vec![] };
+    for row in &source.rows {
+        let record: StringRecord = T::FIELDS.iter().zip(mapping)
+            .map(|(field, column)| {
+                let value = column.and_then(|i| row.values.get(i)).map(String::as_str).unwrap_or_default();
+                T::clean(field, value)
+            })
+            .collect();
+        let errors: Vec<RowError> = match record.deserialize::<T>(Some(&fields)) {
+            Ok(item) => {
+                let errors: Vec<RowError> = item.validate().into_iter()
+                    .map(|(column, reason)| RowError { line: row.line, column: column.to_string(), reason })
+                    .collect();
+                if errors.is_empty() {
+                    checked.rows.push(item);
+                }
+                errors
+            },
+            Err(error) => vec![deserialize_error::<T>(row.line, &error)],
+        };
+        if !errors.is_empty() {
+            checked.errors.extend(errors);
+            checked.rejected.push(row.clone());
+        }
+    }
+    checked
+}
+
+fn deserialize_error<T: CsvImport>(line: u64, error: &csv::Error) -> RowError {
+    match error.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => RowError {
+            line,
+            column: err.field().and_then(|i| T::FIELDS.get(i as usize)).map(|field| field.to_string()).unwrap_or_default(),
+            reason: err.kind().to_string(),
+        },
+        _ => RowError { line, column: String::new(), reason: error.to_string() },
+    }
+}
+
+// The rejected rows as they were in the file, with what was wrong with each on the end, so they
+// can be fixed and imported again. An unreadable line goes in whole under the first column.
+pub fn rejects_csv(source: &SourceFile, errors: &[RowError], rejected: &[SourceRow]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = WriterBuilder::new()
+        .terminator(Terminator::CRLF)
+        .flexible(true)
+        .from_writer(vec![]);
+    writer.write_record(source.headers.iter().map(String::as_str).chain(["Line", "Errors"]))?;
+    let mut lines: Vec<(u64, Vec<String>)> = rejected.iter()
+        .map(|row| (row.line, row.values.clone()))
+        .chain(source.unreadable.iter().map(|line| (line.error.line, vec![line.text.clone()])))
+        .collect();
+    lines.sort_by_key(|(line, _)| *line);
+    for (line, mut values) in lines {
+        let reasons: Vec<String> = errors.iter()
+            .filter(|error| error.line == line)
+            .map(|error| if error.column.is_empty() { error.reason.clone() } else { format!("{}: {}", error.column, error.reason) })
+            .collect();
+        // Short rows are padded so Line and Errors stay in their own columns. Long ones are kept
+        // whole.
+        if values.len() < source.headers.len() {
+            values.resize(source.headers.len(), String::new());
+        }
+        values.push(line.to_string());
+        values.push(reasons.join("; "));
+        writer.write_record(&values)?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+pub fn read_file(file: File, on_load: Callback<String>) {
+    let file_reader = FileReader::new().unwrap();
+    let reader_clone = file_reader.clone();
+    file_reader.set_onload(Some(Closure::once_into_js(move || {
+        let content = reader_clone.result().unwrap();
+        if let Some(text) = content.as_string() {
+            on_load.emit(text);
+        }
+    }).unchecked_ref()));
+    file_reader.read_as_text(&file).unwrap();
+}
+
+#[derive(Properties)]
+pub struct ImportProps<T: 'static> {
+    pub title: AttrValue,
+    // Gets the rows that passed, once the user commits to the import
+    pub on_import: Callback<Vec<T>>,
+}
+
+impl<T> PartialEq for ImportProps<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title && self.on_import == other.on_import
+    }
+}
+
+// Pick a file, check how its columns line up with T, see which rows won't import and why, and
+// only then hand the good rows over.
+#[function_component(CsvImportWizard)]
+pub fn csv_import_wizard<T>(props: &ImportProps<T>) -> Html
+where
+    T: CsvImport + Clone + 'static,
+{
+    let text = use_state(|| None::<(String, Rc<String>)>);
+    let has_headers = use_state(|| true);
+    let source = use_state(|| None::<Rc<SourceFile>>);
+    let mapping = use_state(Vec::<Option<usize>>::new);
+
+    // A new file or header setting starts the mapping over
+    let load = {
+        let source = source.clone();
+        let mapping = mapping.clone();
+        Callback::from(move |(name, text, has_headers): (String, Rc<String>, bool)| {
+            let new_source = read_source(&name, &text, has_headers);
+            mapping.set(auto_map::<T>(&new_source.headers));
+            source.set(Some(Rc::new(new_source)));
+        })
+    };
+
+    let checked = use_memo(((*source).clone(), (*mapping).clone()), |(source, mapping)| {
+        source.as_ref().map(|source| Rc::new(check::<T>(source, mapping)))
+    });
+
+    let on_file_change = {
+        let text = text.clone();
+        let has_headers = has_headers.clone();
+        let load = load.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                let text = text.clone();
+                let has_headers = *has_headers;
+                let load = load.clone();
+                let name = file.name();
+                read_file(file, Callback::from(move |content: String| {
+                    let content = Rc::new(content);
+                    text.set(Some((name.clone(), content.clone())));
+                    load.emit((name.clone(), content, has_headers));
+                }));
+            }
+        })
+    };
+
+    let on_headers = {
+        let text = text.clone();
+        let has_headers = has_headers.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            has_headers.set(input.checked());
+            if let Some((name, text)) = (*text).clone() {
+                load.emit((name, text, input.checked()));
+            }
+        })
+    };
+
+    let on_map = {
+        let mapping = mapping.clone();
+        Callback::from(move |(field, e): (usize, Event)| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let mut new_mapping = (*mapping).clone();
+            if let Some(column) = new_mapping.get_mut(field) {
+                *column = select.value().parse().ok();
+            }
+            mapping.set(new_mapping);
+        })
+    };
+
+    let (Some(source), Some(checked)) = ((*source).clone(), checked.as_ref().clone()) else {
+        return html! {
+            <div>
+                <h3>{ props.title.clone() }</h3>
+                <input type="file" accept=".csv" onchange={on_file_change} />
+            </div>
+        };
+    };
+
+    let download_rejects = {
+        let source = source.clone();
+        let checked = checked.clone();
+        Callback::from(move |_: MouseEvent| {
+            let filename = format!("rejects_{}", source.name);
+            match rejects_csv(&source, &checked.errors, &checked.rejected) {
+                Ok(contents) => download(&filename, "text/csv;charset=utf-8", &contents),
+                Err(error) => log!(format!("Failed to build {}: {}", filename, error)),
+            }
+        })
+    };
+
+    let on_import = {
+        let on_import = props.on_import.clone();
+        let checked = checked.clone();
+        Callback::from(move |_: MouseEvent| on_import.emit(checked.rows.clone()))
+    };
+
+    html! {
+        <div>
+            <h3>{ props.title.clone() }</h3>
+            <input type="file" accept=".csv" onchange={on_file_change} />
+            <label>
+                <input type="checkbox" checked={*has_headers} onchange={on_headers} />
+                { "First row is headers" }
+            </label>
+            <h4>{ format!("Preview of {}", source.name) }</h4>
+            <table>
+                <thead>
+                    <tr>{ for source.headers.iter().map(|header| html! { <th>{ header }</th> }) }</tr>
+                </thead>
+                <tbody>
+                    { for source.rows.iter().take(PREVIEW_ROWS).map(|row| html! {
+                        <tr>{ for row.values.iter().map(|value| html! { <td>{ value }</td> }) }</tr>
+                    }) }
+                </tbody>
+            </table>
+            <h4>{ "Columns" }</h4>
+            <table>
+                <thead>
+                    <tr><th>{ "Field" }</th><th>{ "From column" }</th></tr>
+                </thead>
+                <tbody>
+                    { for T::FIELDS.iter().enumerate().map(|(field, name)| {
+                        let selected = mapping.get(field).copied().flatten();
+                        html! {
+                            <tr>
+                                <td>{ *name }</td>
+                                <td>
+                                    <select onchange={on_map.reform(move |e: Event| (field, e))}>
+                                        <option value="" selected={selected.is_none()}>{ "(none)" }</option>
+                                        { for source.headers.iter().enumerate().map(|(i, header)| html! {
+                                            <option value={i.to_string()} selected={selected == Some(i)}>{ header }</option>
+                                        }) }
+                                    </select>
+                                </td>
+                            </tr>
+                        }
+                    }) }
+                </tbody>
+            </table>
+            <h4>{ format!("{} rows ready, {} rejected", checked.rows.len(), checked.rejected.len() + source.unreadable.len()) }</h4>
+            {
+                if checked.errors.is_empty() {
+                    html! {<></>}
+                } else {
+                    html! {
+                        <>
+                            <table>
+                                <thead>
+                                    <tr><th>{ "Line" }</th><th>{ "Column" }</th><th>{ "Reason" }</th></tr>
+                                </thead>
+                                <tbody>
+                                    { for checked.errors.iter().take(SHOWN_ERRORS).map(|error| html! {
+                                        <tr>
+                                            <td>{ error.line }</td>
+                                            <td>{ error.column.clone() }</td>
+                                            <td>{ error.reason.clone() }</td>
+                                        </tr>
+                                    }) }
+                                </tbody>
+                            </table>
+                            {
+                                if checked.errors.len() > SHOWN_ERRORS {
+                                    html! { <p>{ format!("and {} more", checked.errors.len() - SHOWN_ERRORS) }</p> }
+                                } else {
+                                    html! {<></>}
+                                }
+                            }
+                            <button onclick={download_rejects}>{ "Download Rejects" }</button>
+                        </>
+                    }
+                }
+            }
+            <button onclick={on_import} disabled={checked.rows.is_empty()}>{ format!("Import {} Rows", checked.rows.len()) }</button>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Part {
+        #[serde(rename = "Part Number")]
+        part_number: String,
+        #[serde(rename = "Qty")]
+        qty: u32,
+    }
+
+    impl CsvImport for Part {
+        const FIELDS: &'static [&'static str] = &["Part Number", "Qty"];
+
+        fn validate(&self) -> Vec<(&'static str, String)> {
+            if self.qty == 0 {
+                return vec![("Qty", "must be more than zero".to_string())];
+            }
+            vec![]
+        }
+    }
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn maps_headers_ignoring_case_and_spacing() {
+        assert_eq!(auto_map::<Part>(&headers(&["QTY", "  part   NUMBER ", "Notes"])), vec![Some(1), Some(0)]);
+        assert_eq!(auto_map::<Part>(&headers(&["Part Number", "Notes"])), vec![Some(0), None]);
+    }
+
+    #[test]
+    fn takes_unnamed_columns_in_order_when_the_count_matches() {
+        assert_eq!(auto_map::<Part>(&headers(&["Column 1", "Column 2"])), vec![Some(0), Some(1)]);
+        assert_eq!(auto_map::<Part>(&headers(&["Column 1", "Column 2", "Column 3"])), vec![None, None]);
+    }
+
+    #[test]
+    fn rejects_rows_that_fail_to_parse_or_validate() {
+        let source = read_source("parts.csv", "Part Number,Qty\n7100-001,12\n7100-002,0\n7100-003,lots\n", true);
+        let checked = check::<Part>(&source, &auto_map::<Part>(&source.headers));
+        assert_eq!(checked.rows, vec![Part { part_number: "7100-001".to_string(), qty: 12 }]);
+        assert_eq!(checked.rejected.iter().map(|row| row.line).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(checked.errors.len(), 2);
+        assert_eq!(checked.errors[0], RowError { line: 3, column: "Qty".to_string(), reason: "must be more than zero".to_string() });
+        assert_eq!(checked.errors[1].line, 4);
+    }
+
+    #[test]
+    fn rejects_file_round_trips() {
+        let source = read_source("parts.csv", "Part Number,Qty\n\"7100, left\",0\n7100-002,5\n", true);
+        let checked = check::<Part>(&source, &auto_map::<Part>(&source.headers));
+        let rejects = String::from_utf8(rejects_csv(&source, &checked.errors, &checked.rejected).unwrap()).unwrap();
+
+        let reread = read_source("rejects.csv", &rejects, true);
+        assert_eq!(reread.headers, headers(&["Part Number", "Qty", "Line", "Errors"]));
+        assert_eq!(reread.rows.len(), 1);
+        assert_eq!(reread.rows[0].values, headers(&["7100, left", "0", "2", "Qty: must be more than zero"]));
+
+        // Fixed up, it goes straight back in with the extra columns ignored
+        let fixed = read_source("rejects.csv", &rejects.replace(",0,2,", ",3,2,"), true);
+        let checked = check::<Part>(&fixed, &auto_map::<Part>(&fixed.headers));
+        assert_eq!(checked.rows, vec![Part { part_number: "7100, left".to_string(), qty: 3 }]);
+        assert!(checked.errors.is_empty());
+
+        // Unreadable lines and values past the last header make it into the file too
+        let mut source = read_source("parts.csv", "Part Number,Qty
+7100-001,0,left dock
+7100-002,4
+???
+", true);
+        // The csv reader won't fail on text that's already a &str, so this stands in for a line it
+        // couldn't read
+        source.rows.retain(|row| row.line != 4);
+        source.unreadable.push(UnreadableLine {
+            error: RowError { line: 4, column: String::new(), reason: "unreadable".to_string() },
+            text: "???".to_string(),
+        });
+        let checked = check::<Part>(&source, &auto_map::<Part>(&source.headers));
+        let rejects = String::from_utf8(rejects_csv(&source, &checked.errors, &checked.rejected).unwrap()).unwrap();
+
+        let reread = read_source("rejects.csv", &rejects, true);
+        assert_eq!(reread.headers, headers(&["Part Number", "Qty", "Column 3", "Line", "Errors"]));
+        assert_eq!(reread.rows[0].values, headers(&["7100-001", "0", "left dock", "2", "Qty: must be more than zero"]));
+        assert_eq!(reread.rows[1].values, headers(&["???", "", "", "4", "unreadable"]));
+        assert_eq!(reread.rows.len(), 2);
+
+        // A row longer than the headers still keeps every value
+        let long = SourceFile { headers: headers(&["Part Number", "Qty"]), ..SourceFile::default() };
+        let row = SourceRow { line: 2, values: headers(&["7100-001", "0", "left dock"]) };
+        let rejects = String::from_utf8(rejects_csv(&long, &[], &[row]).unwrap()).unwrap();
+        assert!(rejects.contains("7100-001,0,left dock,2,\r\n"));
+    }
+}
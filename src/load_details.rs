@@ -1,42 +1,21 @@
 use std::rc::Rc;
 
-use crate::{models::*, AppStateAction};
+use crate::{api::Api, daily_csv::ReceiptLine, export::{download_csv, CsvOptions}};
+use models::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{js_sys, window};
 use yew::prelude::*;
-use reqwest::Client;
-use gloo::console::log;
-use std::fmt::Write;
-use chrono::prelude::*;
 
-use crate::{AppState, AppStateContext};
+use crate::AppStateContext;
 
-fn render_location(location: &String) -> String {
-    match location.as_str() {
-        "18008" => "AR".to_string(),
-        "18044" => "FF".to_string(),
-        "22010" => "40".to_string(),
-        _ => "".to_string()  
-    }
-}
-
-fn format_current_date() -> String {
-    let local: DateTime<Local> = Local::now();
-    let year = local.year();
-    let month = format!("{:02}", local.month());
-    let day = format!("{:02}", local.day());
-    format!("{}{}{}", year, month, day)
-}
-
-fn create_csv(data: &Vec<SidParts>, app_state: &AppState) -> String {
-    let mut csv_string = String::new();
-    for sid in data {
-        let s = format!("{}{}", app_state.current_trailer.as_ref().unwrap().TrailerID, render_location(&sid.Sid.CiscoID));
-        for part in &sid.Parts {
-            let _ = writeln!(csv_string, "{},{},{},DAL,P, ,{},{},{},1", s, part.partNumber, part.quantity, render_location(&sid.Sid.CiscoID), format_current_date(), app_state.current_trailer.as_ref().unwrap().TrailerID);
-        }
-    }
-    csv_string
+fn receipt_lines(data: &[SidParts], trailer_id: &str) -> Vec<ReceiptLine> {
+    data.iter()
+        .flat_map(|sid| sid.parts.iter().map(|part| ReceiptLine {
+            trailer_id: trailer_id.to_string(),
+            cisco_id: sid.sid.cisco_id.clone(),
+            part: part.part_number.clone(),
+            quantity: part.quantity,
+        }))
+        .collect()
 }
 
 #[function_component(LoadDetails)]
@@ -48,19 +27,8 @@ pub fn load_details() -> Html {
         let data = details.clone();
         let app_state = app_state.clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data, &app_state);
-            let filename = "data.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            let trailer_id = app_state.current_trailer.as_ref().map(|trailer| trailer.trailer_id.clone()).unwrap_or_default();
+            download_csv("data.csv", &receipt_lines(&data, &trailer_id), app_state.plants(), &CsvOptions::default().without_header());
         })
     };
 
@@ -73,33 +41,15 @@ pub fn load_details() -> Html {
                 let app_state = app_state.clone();
                 let details = details.clone();
                 spawn_local(async move {
-                    let client = Client::new();
                     if let Some(trl) = &app_state.current_trailer{
                         let request = LoadInfoRequest {
-                            param: trl.TrailerID.clone()
+                            param: trl.trailer_id.clone()
                         };
-                        if let Some(user) = &app_state.user {
-                            match client.post("http://192.168.4.172:8000/api/get_load_info")
-                                .json(&request)
-                                .header("Authorization", format!("Bearer {}", user.token))
-                                .send()
-                                .await {
-                                    Ok(resp) => {
-                                        match resp.json::<Vec<SidParts>>().await {
-                                            Ok(load_response) => {
-                                                details.set(Rc::new(load_response.clone()));
-                                            },
-                                            Err(error) => {
-                                                log!(format!("{:?}", error));
-                                                app_state.dispatch(AppStateAction::ClearUser);
-                                            },
-                                        }
-                                    },
-                                    Err(error) => {
-                                        log!(format!("Failed to login: {:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
+                        match Api::new(&app_state).get_load_info(&request).await {
+                            Ok(load_response) => {
+                                details.set(Rc::new(load_response.clone()));
+                            },
+                            Err(error) => error.handle(&app_state),
                         }
                     }
                 });
@@ -117,12 +67,12 @@ pub fn load_details() -> Html {
                 if let Some(trailer) = trailer {
                     html! {
                         <div style="margin-top: 7vh;">
-                        <h1 style="text-align: center;">{"Load Details: "} {trailer.TrailerID}</h1>
+                        <h1 style="text-align: center;">{"Load Details: "} {trailer.trailer_id}</h1>
                         { for details.iter().map(|sids| 
                             
                             html! {
                                 <>
-                                <h3 style="text-align: center">{sids.Sid.id.clone()}{"  ||  "}{render_location(&sids.Sid.CiscoID)}</h3>
+                                <h3 style="text-align: center">{sids.sid.id.clone()}{"  ||  "}{app_state.plants().location(&sids.sid.cisco_id)}</h3>
                                 <table>
                                     <thead>
                                         <tr>
@@ -131,10 +81,10 @@ pub fn load_details() -> Html {
                                         </tr>
                                     </thead>
                                     <tbody>
-                                { for sids.Parts.iter().map(|part|
+                                { for sids.parts.iter().map(|part|
                                     html! {
                                         <tr style="text-align: center;">
-                                            <td style="text-align: center;">{part.partNumber.clone()}</td>
+                                            <td style="text-align: center;">{part.part_number.clone()}</td>
                                             <td style="text-align: center;">{part.quantity}</td>
                                         </tr>
                                     }
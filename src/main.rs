@@ -1,4 +1,3 @@
-mod models;
 mod state;
 mod trucks;
 mod daily_csv;
@@ -16,6 +15,7 @@ mod set_door;
 mod set_picker;
 mod trailer_arrive;
 mod shipments;
+mod shipment_action;
 //mod server;
 mod load_details;
 mod depart;
@@ -24,13 +24,32 @@ mod recent;
 mod nav;
 mod todays_schedule;
 mod edit_trailer;
-use std::rc::Rc;
+mod config;
+mod plants;
+mod export;
+mod xlsx;
+mod import;
+mod trailer_filter;
+mod demurrage;
+mod door_board;
+mod calendar;
+mod capacity;
+mod bulk_schedule;
+mod ics;
+mod carriers;
+mod api;
+mod ws;
+mod route;
+mod permissions;
+mod user;
+mod outbox;
+mod outbox_local_storage;
 use models::*;
+use user::User;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
-use reqwest::Client;
 use gloo::console::log;
-use web_sys::{js_sys::{self}, wasm_bindgen::{self, closure::Closure, prelude::wasm_bindgen, JsCast}, HtmlInputElement, KeyboardEvent, MessageEvent, WebSocket};
+use web_sys::{js_sys::{self}, wasm_bindgen::{self, closure::Closure, prelude::wasm_bindgen}, window, HtmlInputElement, KeyboardEvent};
 use trucks::Trucks;
 use state::*;
 use load_details::*;
@@ -40,6 +59,11 @@ use edit_trailer::EditTrailer;
 use trailers_date_range::TrailersDateRange;
 use recent::Recent;
 use upload::Upload;
+use demurrage::Demurrage;
+use door_board::DoorBoardView;
+use calendar::Calendar;
+use bulk_schedule::BulkSchedule;
+use carriers::{fetch_carriers, Carriers};
 use set_picker::SetPicker;
 use trailer_arrive::SetTrailer;
 use set_door::SetDoor;
@@ -49,6 +73,14 @@ use verified_by::VerifiedBy;
 use todays_shipments::TodaysShipments;
 use depart::Depart;
 use shipment_details::ShipmentDetails;
+use config::load_config;
+use api::Api;
+use ws::ReconnectingSocket;
+use route::Route;
+use permissions::RequirePermission;
+use outbox::OutboxBanner;
+use ws::WsStatus;
+use gloo::events::EventListener;
 
 #[wasm_bindgen]
 extern "C" {
@@ -59,92 +91,155 @@ extern "C" {
 fn app() -> Html {
     
     let app_state = use_reducer(|| AppState::default());
-    let app_state_rc = Rc::new(app_state.clone());
-    let app_st = Rc::new(app_state.clone());
+    let config_error = use_state(|| None::<String>);
+    let route_error = use_state(|| None::<String>);
 
     {
-        let app_state_rc = app_state_rc.clone();
+        let app_state = app_state.clone();
+        let config_error = config_error.clone();
         use_effect_with((), move |_| {
-            let ws = WebSocket::new("ws://localhost:9001").unwrap();
-            let app_state_rc = app_state_rc.clone();
-            log!(format!("{:?}", ws.clone()));
-
-            let onmessage_callback = Closure::wrap(Box::new(move |e: MessageEvent| {
-                if let Ok(txt) = e.data().dyn_into::<js_sys::JsString>() {
-                    let incoming_message: IncomingMessage = serde_json::from_str(&txt.as_string().unwrap()).unwrap();
-                    match incoming_message.r#type.as_str() {
-                        "hot_trailer" => {
-                            app_state_rc.dispatch(AppStateAction::HandleHotTrailer(incoming_message.data));
-                        }
-                        "schedule_trailer" => {
-                            app_state_rc.dispatch(AppStateAction::HandleScheduleTrailer(incoming_message.data));
-                        }
-                        "set_door" => {
-                            app_state_rc.dispatch(AppStateAction::HandleSetDoor(incoming_message.data));
-                        }
-                        "trailer_arrived" => {
-                            app_state_rc.dispatch(AppStateAction::HandleTrailerArrived(incoming_message.data));
-                        }
-                        "set_shipment_trailer" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentTrailer(incoming_message.data));
-                        }
-                        "set_shipment_door" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentDoor(incoming_message.data));
-                        }
-                        "start_shipment_pick" => {
-                            app_state_rc.dispatch(AppStateAction::HandlePickStart(incoming_message.data));
-                        }
-                        "finish_shipment_pick" => {
-                            app_state_rc.dispatch(AppStateAction::HandlePickFinish(incoming_message.data));
-                        }
-                        "shipment_loading" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentTrailer(incoming_message.data));
-                        }
-                        "shipment_trailer_arrival" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentTrailer(incoming_message.data));
-                        }
-                        "new_shipment" => {
-                            app_state_rc.dispatch(AppStateAction::HandleNewShipment(incoming_message.data));
-                        }
-                        "shipment_hold" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentHold(incoming_message.data));
-                        }
-                        "shipment_depart" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentDepart(incoming_message.data));
-                        }
-                        "shipment_start_loading" => {
-                            app_state_rc.dispatch(AppStateAction::HandleShipmentLoading(incoming_message.data));
-                        }
-                        "verified_by" => {
-                            app_state_rc.dispatch(AppStateAction::HandleVerifiedBy(incoming_message.data));
-                        }
-                        _ => {
-                            log!(format!("Unknown event type: {:?}", incoming_message.r#type));
-                        }
-                    }
+            spawn_local(async move {
+                match load_config().await {
+                    Ok(config) => {
+                        log!(format!("Using {} profile", config.profile));
+                        app_state.dispatch(AppStateAction::SetConfig(config));
+                    },
+                    Err(error) => {
+                        log!(format!("{:?}", error));
+                        config_error.set(Some(error));
+                    },
                 }
-            }) as Box<dyn FnMut(MessageEvent)>);
-            ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
-            onmessage_callback.forget();
-
-            let onerror_callback = Closure::wrap(Box::new(move |e: ErrorEvent| {
-                log!(&format!("Error: {:?}", e));
-            }) as Box<dyn FnMut(ErrorEvent)>);
-            ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
-            onerror_callback.forget();
-            let app_st_clone = app_st.clone();
-            app_st.dispatch(AppStateAction::ConnectWebSocket(ws));
+            });
+            || ()
+        });
+    }
 
+    {
+        let dispatcher = app_state.dispatcher();
+        use_effect_with(app_state.config.clone(), move |config| {
+            let socket = config.as_ref().map(|config| ReconnectingSocket::open(&config.ws_url, dispatcher));
             move || {
-                app_st_clone.dispatch(AppStateAction::DisconnectWebSocket);
+                if let Some(socket) = socket {
+                    socket.close();
+                }
+            }
+        });
+    }
+
+    {
+        let dispatcher = app_state.dispatcher();
+        use_effect_with((), move |_| {
+            let listener = EventListener::new(&window().unwrap(), "popstate", move |_| {
+                dispatcher.dispatch(AppStateAction::SetRoute(Route::current()));
+            });
+            move || drop(listener)
+        });
+    }
+
+    // Send anything queued while offline as soon as the browser or the socket says we're back.
+    // The listener only has the state from when it was added, with no config or user yet, so it
+    // just raises a flag and the effect below replays with the current state.
+    let back_online = use_state(|| false);
+    {
+        let back_online = back_online.clone();
+        use_effect_with((), move |_| {
+            let listener = EventListener::new(&window().unwrap(), "online", move |_| {
+                back_online.set(true);
+            });
+            move || drop(listener)
+        });
+    }
+
+    {
+        let app_state = app_state.clone();
+        let can_send = app_state.user.is_some() && app_state.config.is_some();
+        let back_online = back_online.clone();
+        use_effect_with((can_send, *back_online), move |(can_send, online)| {
+            if *can_send && *online {
+                back_online.set(false);
+                spawn_local(outbox::replay(app_state.clone()));
+            }
+            || ()
+        });
+    }
+
+    {
+        let app_state = app_state.clone();
+        let ready = app_state.user.is_some() && app_state.ws_status == WsStatus::Connected;
+        use_effect_with(ready, move |ready| {
+            if *ready {
+                spawn_local(outbox::replay(app_state.clone()));
+            }
+            || ()
+        });
+    }
+
+    // Autocomplete and demurrage use the carrier directory on every screen, so load it on sign in
+    {
+        let app_state = app_state.clone();
+        let ready = app_state.user.is_some() && app_state.config.is_some();
+        use_effect_with(ready, move |ready| {
+            if *ready {
+                fetch_carriers(&app_state);
             }
+            || ()
         });
     }
 
-    if let Some(_user) = &app_state.user {
+    // A deep link only carries an id, so fetch the load or trailer it points at
+    {
+        let app_state = app_state.clone();
+        let route_error = route_error.clone();
+        let ready = app_state.user.is_some() && app_state.config.is_some();
+        use_effect_with((app_state.route.clone(), ready), move |(route, ready)| {
+            route_error.set(None);
+            let missing_load = route.load_id()
+                .filter(|load_id| app_state.current_shipment.as_ref().map(|shipment| shipment.load_id.as_str()) != Some(*load_id))
+                .map(|load_id| load_id.to_string());
+            let missing_trailer = route.trailer_id()
+                .filter(|trailer_id| app_state.current_trailer.as_ref().map(|trailer| trailer.trailer_id.as_str()) != Some(*trailer_id))
+                .map(|trailer_id| trailer_id.to_string());
+            if *ready {
+                if let Some(load_id) = missing_load {
+                    spawn_local(async move {
+                        match Api::new(&app_state).get_shipments().await {
+                            Ok(shipments) => match shipments.into_iter().find(|shipment| shipment.load_id == load_id) {
+                                Some(shipment) => app_state.dispatch(AppStateAction::SetCurrentShipment(shipment)),
+                                None => route_error.set(Some(format!("Load {} not found", load_id))),
+                            },
+                            Err(error) => error.handle(&app_state),
+                        }
+                    });
+                } else if let Some(trailer_id) = missing_trailer {
+                    spawn_local(async move {
+                        match Api::new(&app_state).schedule_trailer().await {
+                            Ok(trailers) => match trailers.into_iter().find(|trailer| trailer.trailer_id == trailer_id) {
+                                Some(trailer) => app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer)),
+                                None => route_error.set(Some(format!("Trailer {} not found", trailer_id))),
+                            },
+                            Err(error) => error.handle(&app_state),
+                        }
+                    });
+                }
+            }
+            || ()
+        });
+    }
+
+    if app_state.config.is_none() {
+        html! {
+            <div style="text-align: center; margin-top: 7vh;">
+                { match &*config_error {
+                    Some(error) => html! { <p>{ error.clone() }</p> },
+                    None => html! { <p>{ "Loading configuration..." }</p> },
+                }}
+            </div>
+        }
+    } else if let Some(_user) = &app_state.user {
         html! {
             <ContextProvider<AppStateContext> context={app_state.clone()}>
                 <Nav />
+                <OutboxBanner />
                 <div style="
                 display: flex;
                 flex-direction: column;
@@ -154,25 +249,7 @@ fn app() -> Html {
                 width: 100vw;
                 ">
                 {
-                    match app_state.current_view.as_str() {
-                        "landing" => html! { <Trucks /> },
-                        "load_details" => html! { <LoadDetails />},
-                        "todays_schedule" => html! { <TodaysSchedule /> },
-                        "edit_trailer" => html! { <EditTrailer /> },
-                        "trailers_date_range" => html! { <TrailersDateRange /> },
-                        "recent" => html! { <Recent /> },
-                        "upload" => html! { <Upload /> },
-                        "shipments" => html! { <Shipments /> },
-                        "set_picker" => html! { <SetPicker /> },
-                        "set_trailer" => html! { <SetTrailer /> },
-                        "new_shipment" => html! { <NewShipment /> },
-                        "set_door" => html! { <SetDoor /> },
-                        "verified_by" => html! { <VerifiedBy /> },
-                        "todays_shipments" => html! { <TodaysShipments /> },
-                        "depart" => html! { <Depart /> },
-                        "shipment_details" => html! { <ShipmentDetails /> },
-                        _ => html! { <p>{ "Page not found" }</p> },
-                    }
+                    render_route(&app_state, &route_error)
                 }
                 </div>
             </ContextProvider<AppStateContext>>
@@ -195,6 +272,58 @@ fn app() -> Html {
     }
 }
 
+fn render_route(app_state: &AppState, route_error: &Option<String>) -> Html {
+    let view = match &app_state.route {
+        Route::Trailers => html! { <Trucks /> },
+        Route::TodaysSchedule => html! { <TodaysSchedule /> },
+        Route::TrailersDateRange => html! { <TrailersDateRange /> },
+        Route::BulkSchedule => html! { <BulkSchedule /> },
+        Route::LoadDetails(trailer_id) => with_trailer(app_state, route_error, trailer_id, html! { <LoadDetails /> }),
+        Route::EditTrailer(trailer_id) => with_trailer(app_state, route_error, trailer_id, html! { <EditTrailer /> }),
+        Route::Recent => html! { <Recent /> },
+        Route::Upload => html! { <Upload /> },
+        Route::Demurrage => html! { <Demurrage /> },
+        Route::Doors => html! { <DoorBoardView /> },
+        Route::Calendar => html! { <Calendar /> },
+        Route::Carriers => html! { <Carriers /> },
+        Route::Shipments => html! { <Shipments /> },
+        Route::TodaysShipments => html! { <TodaysShipments /> },
+        Route::NewShipment => html! { <NewShipment /> },
+        Route::ShipmentDetails(load_id) => with_shipment(app_state, route_error, load_id, html! { <ShipmentDetails /> }),
+        Route::SetPicker(load_id) => with_shipment(app_state, route_error, load_id, html! { <SetPicker /> }),
+        Route::SetTrailer(load_id) => with_shipment(app_state, route_error, load_id, html! { <SetTrailer /> }),
+        Route::SetDoor(load_id) => with_shipment(app_state, route_error, load_id, html! { <SetDoor /> }),
+        Route::VerifiedBy(load_id) => with_shipment(app_state, route_error, load_id, html! { <VerifiedBy /> }),
+        Route::Depart(load_id) => with_shipment(app_state, route_error, load_id, html! { <Depart /> }),
+        Route::NotFound(_) => html! { <p>{ "Page not found" }</p> },
+    };
+    match app_state.route.permission() {
+        Some(permission) => html! {
+            <RequirePermission {permission} fallback={html! { <p>{ "You don't have permission to open this page" }</p> }}>
+                { view }
+            </RequirePermission>
+        },
+        None => view,
+    }
+}
+
+// The shipment screens read current_shipment, so hold them back until it matches the URL
+fn with_shipment(app_state: &AppState, route_error: &Option<String>, load_id: &str, view: Html) -> Html {
+    match (&app_state.current_shipment, route_error) {
+        (Some(shipment), _) if shipment.load_id == load_id => view,
+        (_, Some(error)) => html! { <p>{ error.clone() }</p> },
+        _ => html! { <p>{ format!("Loading load {}...", load_id) }</p> },
+    }
+}
+
+fn with_trailer(app_state: &AppState, route_error: &Option<String>, trailer_id: &str, view: Html) -> Html {
+    match (&app_state.current_trailer, route_error) {
+        (Some(trailer), _) if trailer.trailer_id == trailer_id => view,
+        (_, Some(error)) => html! { <p>{ error.clone() }</p> },
+        _ => html! { <p>{ format!("Loading trailer {}...", trailer_id) }</p> },
+    }
+}
+
 #[function_component(Login)]
 fn login() -> Html {
     let local_view = use_state(|| "login".to_string());
@@ -214,39 +343,29 @@ fn login() -> Html {
             let app_state = app_state.clone();
 
             spawn_local(async move {
-                let client = Client::new();
                 let request = LoginRequest {
                     username: (*username).clone(),
                     password: (*password).clone(),
                 };
 
-                match client.post("http://localhost:8000/login")
-                    .json(&request)
-                    .send()
-                    .await {
-                    Ok(resp) => {
-                        match resp.json::<LoginResponse>().await {
-                            Ok(login_response) => {
-                                let user = User {
-                                    username: login_response.user.username,
-                                    role: login_response.user.role,
-                                    token: login_response.token,
-                                    refresh_token: login_response.refresh_token,
-                                };
-                                app_state.dispatch(AppStateAction::SetUser(user));
-                                app_state.dispatch(AppStateAction::SetCurrentView("landing".to_string()));
-                                unsafe { let _promise = saveCredentials(&username, &password)
-                                    .then(&Closure::once(|_result| {
-                                        log!(format!("Credentials stored successfully!"));
-                                    }))
-                                    .catch(&Closure::once(|err| {
-                                        log!(format!("Failed to store credentials: {:?}", err));
-                                    }));}
-                            },
-                            Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
-                        }
+                match Api::new(&app_state).login(&request).await {
+                    Ok(login_response) => {
+                        let user = User {
+                            username: login_response.user.username,
+                            role: login_response.user.role,
+                            token: login_response.token,
+                            refresh_token: login_response.refresh_token,
+                        };
+                        app_state.dispatch(AppStateAction::SetUser(user));
+                        unsafe { let _promise = saveCredentials(&username, &password)
+                            .then(&Closure::once(|_result| {
+                                log!(format!("Credentials stored successfully!"));
+                            }))
+                            .catch(&Closure::once(|err| {
+                                log!(format!("Failed to store credentials: {:?}", err));
+                            }));}
                     },
-                    Err(error) => log!(format!("Failed to login: {:?}", error)),
+                    Err(error) => log!(format!("Failed to login: {}", error)),
                 }
             });
         })
@@ -265,58 +384,40 @@ fn login() -> Html {
                     let password = password.clone();
                     let app_state = app_state.clone();
                     spawn_local(async move {
-                        let client = Client::new();
                         let request = LoginRequest {
                             username: (*username).clone(),
                             password: (*password).clone(),
                         };
 
-                        match client.post("http://localhost:8000/login")
-                            .json(&request)
-                            .send()
-                            .await {
-                            Ok(resp) => {
-                                match resp.json::<LoginResponse>().await {
-                                    Ok(login_response) => {
-                                        let user = User {
-                                            username: login_response.user.username,
-                                            role: login_response.user.role,
-                                            token: login_response.token,
-                                            refresh_token: login_response.refresh_token,
-                                        };
-                                        app_state.dispatch(AppStateAction::SetUser(user));
-                                        app_state.dispatch(AppStateAction::SetCurrentView("landing".to_string()));
+                        match Api::new(&app_state).login(&request).await {
+                            Ok(login_response) => {
+                                let user = User {
+                                    username: login_response.user.username,
+                                    role: login_response.user.role,
+                                    token: login_response.token,
+                                    refresh_token: login_response.refresh_token,
+                                };
+                                app_state.dispatch(AppStateAction::SetUser(user));
                                     },
-                                    Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
-                                }
-                            },
-                            Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
+                            Err(error) => log!(format!("Failed to login: {}", error)),
                         }
                     });
                 } else {
                     let username = username.clone();
                     let password = password.clone();
                     let local_view = local_view.clone();
+                    let app_state = app_state.clone();
                     spawn_local(async move {
-                        let client = Client::new();
                         let request = LoginRequest {
                             username: (*username).clone(),
                             password: (*password).clone(),
                         };
 
-                        match client.post("http://localhost:8000/register")
-                            .json(&request)
-                            .send()
-                            .await {
-                            Ok(resp) => {
-                                match resp.json::<String>().await {
-                                    Ok(_registration_response) => {
-                                        local_view.set("login".to_string());
-                                    },
-                                    Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
-                                }
+                        match Api::new(&app_state).register(&request).await {
+                            Ok(_registration_response) => {
+                                local_view.set("login".to_string());
                             },
-                            Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
+                            Err(error) => log!(format!("Failed to register: {}", error)),
                         }
                     });
                 }
@@ -336,25 +437,16 @@ fn login() -> Html {
             let app_state = app_state.clone();
             let local_view = local_view.clone();
             spawn_local(async move {
-                let client = Client::new();
                 let request = LoginRequest {
                     username: (*username).clone(),
                     password: (*password).clone(),
                 };
 
-                match client.post("http://localhost:8000/register")
-                    .json(&request)
-                    .send()
-                    .await {
-                    Ok(resp) => {
-                        match resp.json::<String>().await {
-                            Ok(_registration_response) => {
-                                local_view.set("login".to_string());
-                            },
-                            Err(error) => log!(format!("Failed to parse JSON: {:?}", error)),
-                        }
+                match Api::new(&app_state).register(&request).await {
+                    Ok(_registration_response) => {
+                        local_view.set("login".to_string());
                     },
-                    Err(error) => log!(format!("Failed to login: {:?}", error)),
+                    Err(error) => log!(format!("Failed to register: {}", error)),
                 }
             });
         })
@@ -430,5 +522,7 @@ fn login() -> Html {
 
 
 fn main() {
+    // So models can say what it couldn't read from the API
+    console_log::init().ok();
     yew::Renderer::<App>::new().render();
 }
\ No newline at end of file
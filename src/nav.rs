@@ -1,5 +1,5 @@
 use yew::prelude::*;
-use crate::{AppStateAction, AppStateContext};
+use crate::{route::Route, AppStateAction, AppStateContext};
 
 #[function_component(Nav)]
 pub fn nav() -> Html {
@@ -7,8 +7,8 @@ pub fn nav() -> Html {
 
     let update_view = {
         let app_state = app_state.clone();
-        Callback::from(move |view: String| {
-            app_state.dispatch(AppStateAction::SetCurrentView(view));
+        Callback::from(move |route: Route| {
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
@@ -32,27 +32,47 @@ pub fn nav() -> Html {
             color: limegreen;
             flex-wrap: wrap;
             ">
-                <div onclick={update_view.clone().reform(move |_| "landing".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::Trailers)}>
                     <p>{"All Trailers"}</p>
                 </div>
-                <div onclick={update_view.clone().reform(move |_| "shipments".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::Shipments)}>
                     <p>{"Shipments"}</p>
                 </div>
-                <div onclick={update_view.clone().reform(move |_| "recent".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::Recent)}>
                     <p>{"Recently Scheduled"}</p>
                 </div>
-                <div onclick={update_view.clone().reform(move |_| "trailers_date_range".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::TrailersDateRange)}>
                     <p>{"Date Range"}</p>
                 </div>
-                <div onclick={update_view.clone().reform(move |_| "todays_schedule".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::Calendar)}>
+                    <p>{"Calendar"}</p>
+                </div>
+                <div onclick={update_view.clone().reform(move |_| Route::TodaysSchedule)}>
                     <p>{"Today's Schedule"}</p>
                 </div>
-                <div onclick={update_view.clone().reform(move |_| "upload".to_string())}>
+                <div onclick={update_view.clone().reform(move |_| Route::Doors)}>
+                    <p>{"Door Board"}</p>
+                </div>
+                <div onclick={update_view.clone().reform(move |_| Route::Demurrage)}>
+                    <p>{"Demurrage"}</p>
+                </div>
+                <div onclick={update_view.clone().reform(move |_| Route::Carriers)}>
+                    <p>{"Carriers"}</p>
+                </div>
+                <div onclick={update_view.clone().reform(move |_| Route::Upload)}>
                     <p>{"Upload CSV"}</p>
                 </div>
                 <div onclick={logout}>
                     <p>{"Logout"}</p>
                 </div>
+                <div title={app_state.ws_status.label()}>
+                    <p style={format!("color: {}; font-weight: bold;", app_state.ws_status.color())}>{"● "}{app_state.ws_status.label()}</p>
+                </div>
+                if !app_state.outbox.is_empty() {
+                    <div title="Changes saved on this device, waiting for the network">
+                        <p style="color: orange; font-weight: bold;">{format!("{} pending sync", app_state.outbox.len())}</p>
+                    </div>
+                }
             </div>
         </>
     }
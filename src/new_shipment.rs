@@ -1,33 +1,15 @@
-use serde_json::json;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use reqwest::Client;
 use gloo::console::log;
-use crate::{models::*, state::AppStateContext, AppStateAction, float_button::FloatingIcon};
-use chrono::prelude::*;
+use crate::{api::Api, state::AppStateContext, AppStateAction, float_button::FloatingIcon, route::Route};
+use models::*;
+use models::shipment_status::ShipmentStatus;
+use models::time::{CalendarDate, Timestamp};
+use models::ws_event::WsEvent;
 
-// Formats a NaiveDate to a string like "MMDDYYYY"
-fn format_date(date: NaiveDate) -> String {
-    format!("{:02}{:02}{}", date.month(), date.day(), date.year())
-}
-
-// Returns today’s date formatted as "YYYY-MM-DD", suitable for an <input type="date" />
-fn format_date_form() -> String {
-    let local = Local::now();
-    format!("{:04}-{:02}-{:02}", local.year(), local.month(), local.day())
-}
-
-fn time(t: &str) -> String {
-    if t.len() < 1 { 
-        return "00:00".to_string();
-    } else { 
-        return t.to_string(); 
-    };
-}
-
-/*async fn get_load_id(date: &str, dock: &str) -> String {
-    let prefix = get_load_prefix(dock, date.to_string());
+/*async fn get_load_id(plants: &PlantRegistry, date: CalendarDate, dock: &str) -> String {
+    let prefix = plants.load_prefix(dock, date).unwrap_or_default();
     let client = Client::new();
     let request = LoadCountRequest {
         prefix: prefix.clone(),
@@ -64,18 +46,6 @@ fn time(t: &str) -> String {
         }
     }
 }
-
-fn get_load_prefix(dock: &str, date: String) -> String {
-    // Parse the date or default to today's date if parsing fails.
-    let dt = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
-        .unwrap_or_else(|_| Local::today().naive_local());
-    match dock.to_lowercase().as_str() {
-        "uuu"   => format!("ARM131LY_{}_", format_date(dt)),
-        "vaa"   => format!("ARM131VY_{}_", format_date(dt)),
-        "173y"  => format!("40M173Y_{}_", format_date(dt)),
-        "174y"  => format!("40M174Y_{}_", format_date(dt)),
-        _       => "".to_string(),
-    }
 }*/
 
 #[function_component(NewShipment)]
@@ -86,7 +56,7 @@ pub fn new_shipment() -> Html {
     let form = use_state(|| ShipmentFormData {
         door: "".to_string(),
         dock: "".to_string(),
-        schedule_date: format_date_form(),
+        schedule_date: CalendarDate::today().to_string(),
         schedule_time: "".to_string(),
         trailer: "".to_string(),
         picker: "".to_string(),
@@ -97,6 +67,7 @@ pub fn new_shipment() -> Html {
 
     let on_change = {
         let form = form.clone();
+        let plants = app_state.plants().clone();
         Callback::from(move |e: InputEvent| {
             let input = e.target_unchecked_into::<HtmlInputElement>();
             let id = input.id();
@@ -116,6 +87,10 @@ pub fn new_shipment() -> Html {
                     "load_id" => form_data.load_id = value,
                     _ => (),
                 }
+                // Start the load ID off with the dock's prefix until someone types one
+                if id == "dock" && form_data.load_id.is_empty() {
+                    form_data.load_id = plants.load_prefix(&form_data.dock, form_data.schedule_date.parse().unwrap_or_default()).unwrap_or_default();
+                }
                 form_data
             });
         })
@@ -128,54 +103,32 @@ pub fn new_shipment() -> Html {
             let app_state = app_state.clone();
             let form = form.clone();
             spawn_local(async move {
-                if let Some(user) = &app_state.user {
-                    let request = Shipment {
-                        ScheduleDate: form.schedule_date,
-                        ScheduleTime: form.schedule_time,
-                        ArrivalTime: "".to_string(),
-                        DepartTime: "".to_string(),
-                        Dock: form.dock,
-                        Door: form.door,
-                        LoadId: form.load_id,
-                        LoadNum: form.load_num,
-                        Status: "".to_string(),
-                        Picker: "".to_string(),
-                        PickStartTime: "".to_string(),
-                        PickFinishTime: "".to_string(),
-                        VerifiedBy: "".to_string(),
-                        TrailerNum: "".to_string(),
-                        IsHold: false,
-                        Seal: "".to_string(),
-                    };             
-                    let client: Client = Client::new();
-                    match client
-                        .post("http://localhost:8000/api/new_shipment")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await 
-                    {
-                        Ok(response) => {
-                            match response.json::<Shipment>().await {
-                                Ok(shipment) => {
-                                    let json_string = serde_json::to_string(&shipment).unwrap();
-                                    let message = json!({
-                                        "type": "new_shipment",
-                                        "data": {
-                                            "message": json_string
-                                        }
-                                    }).to_string();
-                                    log!(format!("{:?}", message.clone()));
-                                    app_state.send_ws_message(&message);
-                                    app_state.dispatch(AppStateAction::SetCurrentView("shipments".to_string()));
-                                },
-                                Err(error) => log!(format!("{:?}", error)),
-                            }
-                        },
-                        Err(e) => {
-                            log!(format!("Error sending shipment request: {:?}", e));
-                        }
-                    }
+                let request = Shipment {
+                    schedule_date: form.schedule_date.parse().unwrap_or_default(),
+                    schedule_time: form.schedule_time.parse().unwrap_or_default(),
+                    arrival_time: Timestamp::default(),
+                    depart_time: Timestamp::default(),
+                    dock: form.dock,
+                    door: form.door,
+                    load_id: form.load_id,
+                    load_num: form.load_num,
+                    status: ShipmentStatus::NotStarted,
+                    picker: "".to_string(),
+                    pick_start_time: Timestamp::default(),
+                    pick_finish_time: Timestamp::default(),
+                    verified_by: "".to_string(),
+                    trailer_num: "".to_string(),
+                    is_hold: false,
+                    seal: "".to_string(),
+                };             
+                match Api::new(&app_state).new_shipment(&request).await {
+                    Ok(shipment) => {
+                        let event = WsEvent::NewShipment(shipment);
+                        log!(format!("{:?}", event.clone()));
+                        app_state.send_ws_message(event);
+                        app_state.dispatch(AppStateAction::Navigate(Route::Shipments));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
         })
@@ -199,7 +152,10 @@ pub fn new_shipment() -> Html {
                 </div>
                 <div>
                     <label for="dock">{ "Dock" }</label>
-                    <input type="text" id="dock" value={form.dock.clone()} oninput={on_change.clone()} />
+                    <input type="text" id="dock" list="docks" value={form.dock.clone()} oninput={on_change.clone()} />
+                    <datalist id="docks">
+                        { for app_state.plants().docks().map(|dock| html! { <option value={dock.code.clone()} /> }) }
+                    </datalist>
                 </div>
                 <div>
                     <label for="door">{ "Door" }</label>
@@ -0,0 +1,194 @@
+use std::{cell::Cell, collections::HashSet};
+use gloo::console::log;
+use wasm_bindgen_futures::spawn_local;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+use crate::{api::{Api, ApiError}, outbox_local_storage::load_outbox_from_local_storage, state::{AppStateAction, AppStateContext}};
+use models::*;
+use models::time::Timestamp;
+use models::ws_event::{WsEvent, WsFrame};
+
+thread_local! {
+    // Only one replay may walk the outbox at a time or mutations could be sent twice
+    static REPLAYING: Cell<bool> = const { Cell::new(false) };
+}
+
+// A change that can wait for the network. Dock doors lose Wi-Fi all the time, so these are
+// queued in local storage instead of failing, and sent in order once we're back online.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", content = "request")]
+pub enum Mutation {
+    PickStart(PickStart),
+    SetArrivalTime(Arrival),
+    Depart(Departure),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingMutation {
+    pub id: u64,
+    pub queued_at: Timestamp,
+    pub mutation: Mutation,
+}
+
+// A queued mutation the server refused when it was finally sent, e.g. the load was
+// departed from another tablet in the meantime
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutboxConflict {
+    pub pending: PendingMutation,
+    pub message: String,
+}
+
+impl Mutation {
+    pub fn describe(&self) -> String {
+        match self {
+            Mutation::PickStart(request) => format!("Pick start for load {} by {}", request.load_id, request.picker),
+            Mutation::SetArrivalTime(request) if request.arrival_time.is_empty() => format!("Clear arrival for trailer {}", request.trailer_id),
+            Mutation::SetArrivalTime(request) => format!("Arrival of trailer {} at {}", request.trailer_id, request.arrival_time),
+            Mutation::Depart(request) => format!("Departure of load {} at {}", request.load_id, request.depart_time),
+        }
+    }
+
+    // What every screen should see once this goes through
+    fn event(&self) -> WsEvent {
+        match self {
+            Mutation::PickStart(request) => WsEvent::StartShipmentPick(request.clone()),
+            Mutation::SetArrivalTime(request) => WsEvent::TrailerArrived(request.clone()),
+            Mutation::Depart(request) => WsEvent::ShipmentDepart(request.clone()),
+        }
+    }
+
+    async fn send(&self, api: &Api) -> Result<(), ApiError> {
+        let result = match self {
+            Mutation::PickStart(request) => api.set_shipment_pick_start(request).await.map(|_| ()),
+            Mutation::SetArrivalTime(request) => api.set_arrival_time(request).await.map(|_| ()),
+            Mutation::Depart(request) => api.set_shipment_departure_time(request).await.map(|_| ()),
+        };
+        match result {
+            // A 2xx with a body we couldn't read still made the change, and the body isn't used
+            Err(ApiError::Decode(message)) => {
+                log!(format!("Sent {}, but couldn't read the reply: {}", self.describe(), message));
+                Ok(())
+            },
+            result => result,
+        }
+    }
+}
+
+impl PendingMutation {
+    pub fn new(id: u64, mutation: Mutation) -> Self {
+        PendingMutation {
+            id,
+            queued_at: Timestamp::now(),
+            mutation,
+        }
+    }
+}
+
+// Sends a mutation, or queues it when the network is down (or earlier ones are still waiting,
+// so they stay in order). Either way the caller can carry on as if it went through.
+pub async fn submit(app_state: &AppStateContext, mutation: Mutation) -> Result<(), ApiError> {
+    if !pending().is_empty() {
+        queue(app_state, mutation);
+        spawn_local(replay(app_state.clone()));
+        return Ok(());
+    }
+    match mutation.send(&Api::new(app_state)).await {
+        Ok(()) => {
+            app_state.send_ws_message(mutation.event());
+            Ok(())
+        },
+        Err(ApiError::Network(message)) => {
+            log!(format!("Offline, queueing {}: {}", mutation.describe(), message));
+            queue(app_state, mutation);
+            Ok(())
+        },
+        Err(ApiError::Server { status: 409, message }) => {
+            let pending = PendingMutation::new(app_state.next_mutation_id(), mutation);
+            app_state.dispatch(AppStateAction::RejectMutation(OutboxConflict { pending, message }));
+            Ok(())
+        },
+        Err(error) => Err(error),
+    }
+}
+
+// Sends everything in the outbox, oldest first, stopping at the first network failure
+pub async fn replay(app_state: AppStateContext) {
+    if REPLAYING.with(|replaying| replaying.replace(true)) {
+        return;
+    }
+    let api = Api::new(&app_state);
+    let mut done = HashSet::new();
+    while let Some(next) = pending().into_iter().find(|pending| !done.contains(&pending.id)) {
+        done.insert(next.id);
+        match next.mutation.send(&api).await {
+            Ok(()) => {
+                log!(format!("Synced {}", next.mutation.describe()));
+                app_state.send_ws_message(next.mutation.event());
+                app_state.dispatch(AppStateAction::RemoveMutation(next.id));
+            },
+            Err(ApiError::Network(_)) => break,
+            Err(ApiError::Unauthorized) => {
+                ApiError::Unauthorized.handle(&app_state);
+                break;
+            },
+            // The server is up but struggling, try again on the next reconnect
+            Err(ApiError::Server { status, .. }) if status >= 500 => break,
+            Err(error) => {
+                app_state.dispatch(AppStateAction::RejectMutation(OutboxConflict {
+                    pending: next,
+                    message: error.to_string(),
+                }));
+            },
+        }
+    }
+    REPLAYING.with(|replaying| replaying.set(false));
+}
+
+fn pending() -> Vec<PendingMutation> {
+    load_outbox_from_local_storage().unwrap_or_default()
+}
+
+fn queue(app_state: &AppStateContext, mutation: Mutation) {
+    // Show the change straight away, the same way a broadcast from another tablet would
+    let frame = WsFrame::new(mutation.event());
+    app_state.dispatch(AppStateAction::QueueMutation(mutation));
+    app_state.dispatch(AppStateAction::HandleWsFrame(frame));
+}
+
+#[function_component(OutboxBanner)]
+pub fn outbox_banner() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+
+    let dismiss = {
+        let app_state = app_state.clone();
+        Callback::from(move |id: u64| {
+            app_state.dispatch(AppStateAction::DismissConflict(id));
+        })
+    };
+
+    if app_state.outbox_conflicts.is_empty() {
+        return html! {<></>};
+    }
+
+    html! {
+        <div style="
+        position: fixed;
+        top: 7vh;
+        width: 100vw;
+        background-color: #ffcdd2;
+        color: black;
+        z-index: 900;
+        text-align: center;
+        ">
+            { app_state.outbox_conflicts.iter().map(|conflict| {
+                let id = conflict.pending.id;
+                html! {
+                    <p>
+                        { format!("Not saved: {} (queued {}). {}", conflict.pending.mutation.describe(), conflict.pending.queued_at, conflict.message) }
+                        <button style="margin-left: 1rem;" onclick={dismiss.clone().reform(move |_| id)}>{"Dismiss"}</button>
+                    </p>
+                }
+            }).collect::<Html>() }
+        </div>
+    }
+}
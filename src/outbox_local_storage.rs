@@ -0,0 +1,21 @@
+use web_sys::window;
+use serde_json::{self, Error};
+use crate::outbox::PendingMutation;
+
+const OUTBOX_KEY: &str = "outbox";
+
+pub fn save_outbox_to_local_storage(outbox: &Vec<PendingMutation>) -> Result<(), Error> {
+    let window = window().unwrap();
+    let storage = window.local_storage().unwrap().unwrap();
+    let outbox_json = serde_json::to_string(outbox)?;
+    storage.set_item(OUTBOX_KEY, &outbox_json).unwrap();
+    Ok(())
+}
+
+pub fn load_outbox_from_local_storage() -> Option<Vec<PendingMutation>> {
+    let window = window().unwrap();
+    let storage = window.local_storage().unwrap().unwrap();
+    storage.get_item(OUTBOX_KEY).ok().flatten().and_then(|outbox_json| {
+        serde_json::from_str(&outbox_json).ok()
+    })
+}
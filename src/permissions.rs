@@ -0,0 +1,94 @@
+use yew::prelude::*;
+use crate::{route::Route, state::AppStateContext};
+use crate::user::User;
+use models::shipment_status::ShipmentAction;
+
+// What a user is allowed to do. Screens ask for one of these instead of comparing role strings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    // Mark a trailer arrived, or clear the arrival time
+    MarkArrival,
+    // Edit a trailer's schedule, door, SCAC and contact
+    ScheduleTrailer,
+    // Put a shipment on hold or take it off
+    HoldShipment,
+    // Record a shipment's departure and seal
+    DepartShipment,
+    // Work a shipment through picking, verification and loading: picker, trailer, door, new loads
+    ManageItems,
+    // Add, change and remove carriers in the carrier directory
+    ManageCarriers,
+}
+
+// The only place roles are mapped to permissions
+fn role_permissions(role: &str) -> &'static [Permission] {
+    match role {
+        "admin" => &[
+            Permission::MarkArrival,
+            Permission::ScheduleTrailer,
+            Permission::HoldShipment,
+            Permission::DepartShipment,
+            Permission::ManageItems,
+            Permission::ManageCarriers,
+        ],
+        "write" => &[
+            Permission::MarkArrival,
+            Permission::ScheduleTrailer,
+            Permission::DepartShipment,
+            Permission::ManageItems,
+        ],
+        _ => &[],
+    }
+}
+
+impl User {
+    pub fn can(&self, permission: Permission) -> bool {
+        role_permissions(&self.role).contains(&permission)
+    }
+
+    // Whether this user may move a load along by this step
+    pub fn can_take(&self, action: ShipmentAction) -> bool {
+        match action {
+            ShipmentAction::Depart => self.can(Permission::DepartShipment),
+            _ => self.can(Permission::ManageItems),
+        }
+    }
+}
+
+impl Route {
+    // The permission needed to open a screen at all, for screens that only change things
+    pub fn permission(&self) -> Option<Permission> {
+        match self {
+            Route::EditTrailer(_) | Route::BulkSchedule => Some(Permission::ScheduleTrailer),
+            Route::NewShipment
+            | Route::SetPicker(_)
+            | Route::SetTrailer(_)
+            | Route::SetDoor(_)
+            | Route::VerifiedBy(_) => Some(Permission::ManageItems),
+            Route::Depart(_) => Some(Permission::DepartShipment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RequirePermissionProps {
+    pub permission: Permission,
+    #[prop_or_default]
+    pub children: Html,
+    // Shown instead of the children when the user lacks the permission. Renders nothing by default.
+    #[prop_or_default]
+    pub fallback: Html,
+}
+
+#[function_component(RequirePermission)]
+pub fn require_permission(props: &RequirePermissionProps) -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+    let allowed = app_state.user.as_ref().is_some_and(|user| user.can(props.permission));
+
+    if allowed {
+        props.children.clone()
+    } else {
+        props.fallback.clone()
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use models::time::CalendarDate;
+
+// Everything the app knows about the sites it ships between. Comes from the "plants" list in
+// config.json, so a new plant, dock or door is a config change rather than a code change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(transparent)]
+pub struct PlantRegistry {
+    pub plants: Vec<Plant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Plant {
+    // What trailers, CSVs and the parts master call the plant, e.g. "AR"
+    pub code: String,
+    // Shown in exports that want the full name, e.g. "ARLINGTON"
+    pub name: String,
+    // Shown in the totals on the shipment screens. Defaults to the code.
+    #[serde(default)]
+    pub short_name: String,
+    // The Cisco location IDs that belong to this plant, e.g. "18008"
+    #[serde(default)]
+    pub cisco_ids: Vec<String>,
+    // Street address, for appointment confirmations sent to carriers
+    #[serde(default)]
+    pub address: String,
+    // Where this plant's loads are built. Plants we only receive from have none.
+    #[serde(default)]
+    pub docks: Vec<Dock>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Dock {
+    pub code: String,
+    // Start of the load IDs built on this dock, before the date, e.g. "ARM131LY". Spring Hill's
+    // 75y has never had one, its loads only being counted on the shipments screen, so New
+    // Shipment leaves the ID for the user to type there.
+    #[serde(default)]
+    pub load_prefix: String,
+    // Every door on the dock, so the door board shows the idle ones too
+    #[serde(default)]
+    pub doors: Vec<String>,
+}
+
+// What the parts master uses for a part stocked at more than one plant
+const MULTI_PLANT: &str = "MULTI";
+
+impl Plant {
+    pub fn short_name(&self) -> &str {
+        if self.short_name.is_empty() { &self.code } else { &self.short_name }
+    }
+}
+
+impl PlantRegistry {
+    pub fn by_code(&self, code: &str) -> Option<&Plant> {
+        self.plants.iter().find(|plant| plant.code == code)
+    }
+
+    pub fn by_cisco_id(&self, cisco_id: &str) -> Option<&Plant> {
+        self.plants.iter().find(|plant| plant.cisco_ids.iter().any(|id| id == cisco_id))
+    }
+
+    // Dock codes are typed in by hand, so case doesn't matter
+    pub fn dock(&self, dock: &str) -> Option<(&Plant, &Dock)> {
+        self.plants.iter().find_map(|plant| {
+            plant.docks.iter().find(|d| d.code.eq_ignore_ascii_case(dock.trim())).map(|d| (plant, d))
+        })
+    }
+
+    // Plants that build loads, in the order they're listed
+    pub fn shipping_plants(&self) -> impl Iterator<Item = &Plant> {
+        self.plants.iter().filter(|plant| !plant.docks.is_empty())
+    }
+
+    pub fn docks(&self) -> impl Iterator<Item = &Dock> {
+        self.plants.iter().flat_map(|plant| plant.docks.iter())
+    }
+
+    // The plant code for one Cisco location, or nothing when it isn't ours
+    pub fn location(&self, cisco_id: &str) -> String {
+        self.by_cisco_id(cisco_id).map(|plant| plant.code.clone()).unwrap_or_default()
+    }
+
+    // The plant codes for all of a trailer's Cisco locations, e.g. "AR 40"
+    pub fn locations(&self, cisco_ids: &[String]) -> String {
+        cisco_ids.iter()
+            .filter_map(|cisco_id| self.by_cisco_id(cisco_id))
+            .map(|plant| plant.code.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn plant_name(&self, code: &str) -> String {
+        self.by_code(code).map(|plant| plant.name.clone()).unwrap_or_else(|| MULTI_PLANT.to_string())
+    }
+
+    // e.g. "ARM131LY_05012024_" for a load on dock uuu on May 1st
+    pub fn load_prefix(&self, dock: &str, date: CalendarDate) -> Option<String> {
+        let (_, dock) = self.dock(dock)?;
+        if dock.load_prefix.is_empty() {
+            return None;
+        }
+        let date = if date.is_empty() { CalendarDate::today() } else { date };
+        Some(format!("{}_{}_", dock.load_prefix, date.format("%m%d%Y")))
+    }
+
+    pub fn doors(&self, dock: &str) -> Vec<String> {
+        self.dock(dock).map(|(_, dock)| dock.doors.clone()).unwrap_or_default()
+    }
+}
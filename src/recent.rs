@@ -1,10 +1,12 @@
 use chrono::NaiveDate;
-use web_sys::{js_sys, window};
 use yew::prelude::*;
 use crate::AppStateAction;
 use crate::AppStateContext;
-use crate::models::*;
-use std::fmt::Write;
+use crate::export::{download_csv, CsvExport, CsvOptions};
+use crate::xlsx::{download_xlsx, Cell, XlsxExport};
+use crate::ics::{download_ics, ics_filename, recent_appointment};
+use models::*;
+use models::time::{CalendarDate, TimeOfDay};
 
 fn format_date(date_str: &str) -> String {
     let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
@@ -14,13 +16,25 @@ fn format_date(date_str: &str) -> String {
     date.format("%m-%d-%Y").to_string()
 }
 
-fn create_csv(data: &Vec<RecentTrailers>) -> String {
-    let mut csv_string = String::new();
-    let _ = writeln!(csv_string, "Trailer, Scheduled Date, Scheduled Time, Carrier");
-    for trailer in data {
-        let _ = writeln!(csv_string, "{},{},{},{}", trailer.trailer_id, format_date(&trailer.date), trailer.time, trailer.scac);
+impl CsvExport for RecentTrailers {
+    type Context = ();
+
+    const COLUMNS: &'static [&'static str] = &["Trailer", "Scheduled Date", "Scheduled Time", "Carrier"];
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        vec![self.trailer_id.clone(), format_date(&self.date), self.time.clone(), self.scac.clone()]
+    }
+}
+
+impl XlsxExport for RecentTrailers {
+    fn cells(&self, _: &()) -> Vec<Cell> {
+        vec![
+            self.trailer_id.clone().into(),
+            self.date.parse::<CalendarDate>().unwrap_or_default().into(),
+            self.time.parse::<TimeOfDay>().unwrap_or_default().into(),
+            self.scac.clone().into(),
+        ]
     }
-    csv_string
 }
 
 #[function_component(Recent)]
@@ -30,19 +44,34 @@ pub fn recent() -> Html {
     let download_csv = {
         let data = app_state.recent_trailers.clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data);
-            let filename = "recent_trailers.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("recent_trailers.csv", &data, &(), &CsvOptions::default().with_bom());
+        })
+    };
+
+    let download_xlsx = {
+        let data = app_state.recent_trailers.clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx("recent_trailers.xlsx", "Recent Trailers", &data, &(), &CsvOptions::default());
+        })
+    };
+
+    // Everything recently scheduled in one file, for a carrier with several trailers coming
+    let download_all_ics = {
+        let app_state = app_state.clone();
+        Callback::from(move |_: MouseEvent| {
+            let appointments: Vec<_> = app_state.recent_trailers.iter()
+                .filter_map(|trailer| recent_appointment(trailer, app_state.plants()))
+                .collect();
+            download_ics("recent_appointments.ics", &appointments);
+        })
+    };
+
+    let download_ics_for = {
+        let app_state = app_state.clone();
+        Callback::from(move |trailer: RecentTrailers| {
+            if let Some(appointment) = recent_appointment(&trailer, app_state.plants()) {
+                download_ics(&ics_filename(&trailer.trailer_id), &[appointment]);
+            }
         })
     };
 
@@ -68,7 +97,11 @@ pub fn recent() -> Html {
             margin-bottom: 3%">
            { if app_state.recent_trailers.len() > 0 {
                 html! {
+                    <>
                     <a style="margin-top: 3%;" onclick={download_csv}>{"Download CSV"}</a>
+                    <a style="margin-top: 3%;" onclick={download_xlsx}>{"Download Excel"}</a>
+                    <a style="margin-top: 3%;" onclick={download_all_ics}>{"Download Appointments (.ics)"}</a>
+                    </>
                 }
            } else {
             html! { <></>}
@@ -89,6 +122,9 @@ pub fn recent() -> Html {
                         <td>
                             {"Carrier"}
                         </td>
+                        <td>
+                            {"Confirmation"}
+                        </td>
                     </tr>
                 </thead>
                 <tbody>
@@ -107,6 +143,14 @@ pub fn recent() -> Html {
                                 <td>
                                     {trailer.scac.clone()}
                                 </td>
+                                <td>
+                                    { if recent_appointment(trailer, app_state.plants()).is_some() {
+                                        let trailer = trailer.clone();
+                                        html! { <a onclick={download_ics_for.reform(move |_| trailer.clone())}>{".ics"}</a> }
+                                    } else {
+                                        html! {<></>}
+                                    }}
+                                </td>
                             </tr>
                         }
                     )}
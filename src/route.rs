@@ -0,0 +1,146 @@
+use web_sys::{js_sys::{decode_uri_component, encode_uri_component}, wasm_bindgen::JsValue, window};
+
+// Every screen has a URL so it can be bookmarked, shared and reached with the back button.
+// Screens about one load or trailer carry its id and load it themselves when opened cold.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Route {
+    Trailers,
+    TodaysSchedule,
+    TrailersDateRange,
+    BulkSchedule,
+    LoadDetails(String),
+    EditTrailer(String),
+    Recent,
+    Upload,
+    Demurrage,
+    Doors,
+    Calendar,
+    Carriers,
+    Shipments,
+    TodaysShipments,
+    NewShipment,
+    ShipmentDetails(String),
+    SetPicker(String),
+    SetTrailer(String),
+    SetDoor(String),
+    VerifiedBy(String),
+    Depart(String),
+    NotFound(String),
+}
+
+impl Route {
+    pub fn from_path(path: &str) -> Route {
+        let segments: Vec<String> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(decode)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(|segment| segment.as_str()).collect();
+        match segments.as_slice() {
+            [] => Route::Trailers,
+            ["schedule", "today"] => Route::TodaysSchedule,
+            ["trailers", "range"] => Route::TrailersDateRange,
+            ["trailers", "bulk"] => Route::BulkSchedule,
+            ["trailers", trailer_id] => Route::LoadDetails(trailer_id.to_string()),
+            ["trailers", trailer_id, "edit"] => Route::EditTrailer(trailer_id.to_string()),
+            ["recent"] => Route::Recent,
+            ["upload"] => Route::Upload,
+            ["demurrage"] => Route::Demurrage,
+            ["doors"] => Route::Doors,
+            ["calendar"] => Route::Calendar,
+            ["carriers"] => Route::Carriers,
+            ["shipments"] => Route::Shipments,
+            ["shipments", "today"] => Route::TodaysShipments,
+            ["shipments", "new"] => Route::NewShipment,
+            ["shipments", load_id] => Route::ShipmentDetails(load_id.to_string()),
+            ["shipments", load_id, "picker"] => Route::SetPicker(load_id.to_string()),
+            ["shipments", load_id, "trailer"] => Route::SetTrailer(load_id.to_string()),
+            ["shipments", load_id, "door"] => Route::SetDoor(load_id.to_string()),
+            ["shipments", load_id, "verify"] => Route::VerifiedBy(load_id.to_string()),
+            ["shipments", load_id, "depart"] => Route::Depart(load_id.to_string()),
+            _ => Route::NotFound(path.to_string()),
+        }
+    }
+
+    pub fn to_path(&self) -> String {
+        match self {
+            Route::Trailers => "/".to_string(),
+            Route::TodaysSchedule => "/schedule/today".to_string(),
+            Route::TrailersDateRange => "/trailers/range".to_string(),
+            Route::BulkSchedule => "/trailers/bulk".to_string(),
+            Route::LoadDetails(trailer_id) => format!("/trailers/{}", encode(trailer_id)),
+            Route::EditTrailer(trailer_id) => format!("/trailers/{}/edit", encode(trailer_id)),
+            Route::Recent => "/recent".to_string(),
+            Route::Upload => "/upload".to_string(),
+            Route::Demurrage => "/demurrage".to_string(),
+            Route::Doors => "/doors".to_string(),
+            Route::Calendar => "/calendar".to_string(),
+            Route::Carriers => "/carriers".to_string(),
+            Route::Shipments => "/shipments".to_string(),
+            Route::TodaysShipments => "/shipments/today".to_string(),
+            Route::NewShipment => "/shipments/new".to_string(),
+            Route::ShipmentDetails(load_id) => format!("/shipments/{}", encode(load_id)),
+            Route::SetPicker(load_id) => format!("/shipments/{}/picker", encode(load_id)),
+            Route::SetTrailer(load_id) => format!("/shipments/{}/trailer", encode(load_id)),
+            Route::SetDoor(load_id) => format!("/shipments/{}/door", encode(load_id)),
+            Route::VerifiedBy(load_id) => format!("/shipments/{}/verify", encode(load_id)),
+            Route::Depart(load_id) => format!("/shipments/{}/depart", encode(load_id)),
+            Route::NotFound(path) => path.clone(),
+        }
+    }
+
+    // The load a shipment screen is about, if it is one
+    pub fn load_id(&self) -> Option<&str> {
+        match self {
+            Route::ShipmentDetails(load_id)
+            | Route::SetPicker(load_id)
+            | Route::SetTrailer(load_id)
+            | Route::SetDoor(load_id)
+            | Route::VerifiedBy(load_id)
+            | Route::Depart(load_id) => Some(load_id),
+            _ => None,
+        }
+    }
+
+    // The trailer a trailer screen is about, if it is one
+    pub fn trailer_id(&self) -> Option<&str> {
+        match self {
+            Route::LoadDetails(trailer_id) | Route::EditTrailer(trailer_id) => Some(trailer_id),
+            _ => None,
+        }
+    }
+
+    pub fn current() -> Route {
+        let path = window()
+            .and_then(|w| w.location().pathname().ok())
+            .unwrap_or_else(|| "/".to_string());
+        Route::from_path(&path)
+    }
+
+    // Adds a history entry unless we're already there
+    pub fn push(&self) {
+        if *self == Route::current() {
+            return;
+        }
+        if let Some(history) = window().and_then(|w| w.history().ok()) {
+            let _ = history.push_state_with_url(&JsValue::NULL, "", Some(&self.to_path()));
+        }
+    }
+
+    // Goes back a page if there is one in this tab. Returns false when the app was opened
+    // directly on this URL, so the caller can pick somewhere sensible instead.
+    pub fn back() -> bool {
+        match window().and_then(|w| w.history().ok()) {
+            Some(history) if history.length().unwrap_or(0) > 1 => history.back().is_ok(),
+            _ => false,
+        }
+    }
+}
+
+fn encode(segment: &str) -> String {
+    String::from(encode_uri_component(segment))
+}
+
+fn decode(segment: &str) -> String {
+    decode_uri_component(segment).map(String::from).unwrap_or_else(|_| segment.to_string())
+}
@@ -1,10 +1,9 @@
-use serde_json::json;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use reqwest::Client;
-use gloo::console::log;
-use crate::{models::*, state::AppStateContext, AppStateAction};
+use crate::{api::Api, state::AppStateContext, AppStateAction, route::Route, door_board::{load_door_data, shipment_door_warning}};
+use models::*;
+use models::ws_event::WsEvent;
 use chrono::prelude::*;
 
 #[function_component(SetDoor)]
@@ -12,7 +11,7 @@ pub fn set_door() -> Html {
 
     let app_state = use_context::<AppStateContext>().expect("no state found");
     let shipment = app_state.current_shipment.as_ref().unwrap().clone();
-    let door = use_state(|| shipment.TrailerNum.clone());
+    let door = use_state(|| shipment.door.clone());
     
     let set_door = {
         let app_state = app_state.clone();
@@ -23,47 +22,35 @@ pub fn set_door() -> Html {
             let door = door.clone();
             let shipment = shipment.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = SetShipmentDoorRequest {
-                        LoadId: shipment.LoadId,
-                        Door: (*door).clone()
-                    };
-                    match client.post("http://localhost:8000/api/shipment_door")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Shipment>().await {
-                                    Ok(shipment) => {
-                                        let msg = SetShipmentDoorMessage {
-                                            LoadId: shipment.LoadId,
-                                            Door: shipment.Door,
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "set_shipment_door",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                        app_state.dispatch(AppStateAction::SetCurrentView("shipments".to_string()));
-                                    },
-                                    Err(e) => {
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                        log!(format!("{:?}", e));
-                                    }
-                                }
-                            },
-                            Err(e) => log!(format!("{:?}", e)),
-                        }
+                let request = ShipmentDoor {
+                    load_id: shipment.load_id,
+                    door: (*door).clone()
+                };
+                match Api::new(&app_state).shipment_door(&request).await {
+                    Ok(shipment) => {
+                        let msg = ShipmentDoor {
+                            load_id: shipment.load_id,
+                            door: shipment.door,
+                        };
+                        app_state.send_ws_message(WsEvent::SetShipmentDoor(msg));
+                        app_state.dispatch(AppStateAction::Navigate(Route::Shipments));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             })
         })
     };
 
+    {
+        let app_state = app_state.clone();
+        use_effect_with((), move |_| {
+            load_door_data(&app_state);
+            || ()
+        });
+    }
+
+    let edited = Shipment { door: (*door).clone(), ..shipment.clone() };
+
     let on_change = {
         let door = door.clone();
         Callback::from(move |e: InputEvent| {
@@ -77,9 +64,13 @@ pub fn set_door() -> Html {
 
     html! {
         <div style="text-align: center;">
-            <h1>{"Load: "} {shipment.LoadId}</h1>
+            <h1>{"Load: "} {shipment.load_id}</h1>
             <label for="door">{ "Door" }</label>
-            <input style="text-align: center; width: 25vw;" id="door" type="text" value={(*door).clone()} oninput={on_change.clone()} />
+            <input style="text-align: center; width: 25vw;" id="door" type="text" list="doors" value={(*door).clone()} oninput={on_change.clone()} />
+            <datalist id="doors">
+                { for app_state.plants().doors(&shipment.dock).into_iter().map(|door| html! { <option value={door} /> }) }
+            </datalist>
+            { shipment_door_warning(&app_state, &edited) }
             <button style="background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={set_door}>{"Set Details"}</button>
         </div> 
     }
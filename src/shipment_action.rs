@@ -0,0 +1,116 @@
+use yew::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use gloo::console::log;
+use crate::{api::Api, state::AppStateContext, AppStateAction, route::Route};
+use models::*;
+use models::time::Timestamp;
+use models::shipment_status::ShipmentAction;
+use crate::user::User;
+use models::ws_event::WsEvent;
+
+// Background and text color of the button for each step
+fn button_colors(action: ShipmentAction) -> (&'static str, &'static str) {
+    match action {
+        ShipmentAction::StartPick => ("red", "black"),
+        ShipmentAction::FinishPick => ("orange", "black"),
+        ShipmentAction::Verify => ("teal", "black"),
+        ShipmentAction::StartLoading => ("green", "black"),
+        ShipmentAction::Depart => ("blue", "white"),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PropTypes {
+    pub user: User,
+    pub shipment: Shipment,
+}
+
+// The next step for a load, if it has one this user may take. What's offered comes from the
+// load's status, so a button can't show up for a step the load isn't ready for.
+#[function_component(ActionButton)]
+pub fn action_button(props: &PropTypes) -> Html {
+
+    let app_state = use_context::<AppStateContext>().expect("no state found");
+
+    let finish_picking = {
+        let app_state = app_state.clone();
+        let shipment = props.shipment.clone();
+        Callback::from(move |_| {
+            let app_state = app_state.clone();
+            let shipment = shipment.clone();
+            spawn_local(async move {
+                let request = PickFinish {
+                    load_id: shipment.load_id.clone(),
+                    finish_time: Timestamp::now(),
+                };
+                log!(format!("{:?}",request.clone()));
+                match Api::new(&app_state).shipment_pick_finish(&request).await {
+                    Ok(shipment) => {
+                        let msg = PickFinish {
+                            load_id: shipment.load_id,
+                            finish_time: shipment.pick_finish_time,
+                        };
+                        app_state.send_ws_message(WsEvent::FinishShipmentPick(msg));
+                    },
+                    Err(error) => error.handle(&app_state),
+                }
+            })
+        })
+    };
+
+    let start_loading = {
+        let app_state = app_state.clone();
+        let shipment = props.shipment.clone();
+        Callback::from(move |_| {
+            let app_state = app_state.clone();
+            let shipment = shipment.clone();
+            spawn_local(async move {
+                let request = ShipmentRef {
+                    load_id: shipment.load_id.clone(),
+                };
+                match Api::new(&app_state).shipment_begin_loading(&request).await {
+                    Ok(shipment) => {
+                        let msg = ShipmentRef {
+                            load_id: shipment.load_id,
+                        };
+                        app_state.send_ws_message(WsEvent::ShipmentStartLoading(msg));
+                    },
+                    Err(error) => error.handle(&app_state),
+                }
+            })
+        })
+    };
+
+    let Some(action) = props.shipment.available_actions().into_iter().find(|action| props.user.can_take(*action)) else {
+        return html! {<></>};
+    };
+
+    let on_action = {
+        let app_state = app_state.clone();
+        let shipment = props.shipment.clone();
+        Callback::from(move |_| {
+            app_state.dispatch(AppStateAction::SetCurrentShipment(shipment.clone()));
+
+            match action {
+                ShipmentAction::StartPick => app_state.dispatch(AppStateAction::Navigate(Route::SetPicker(shipment.load_id.clone()))),
+                ShipmentAction::FinishPick => finish_picking.emit(()),
+                ShipmentAction::Verify => app_state.dispatch(AppStateAction::Navigate(Route::VerifiedBy(shipment.load_id.clone()))),
+                ShipmentAction::StartLoading => start_loading.emit(()),
+                ShipmentAction::Depart => app_state.dispatch(AppStateAction::Navigate(Route::Depart(shipment.load_id.clone()))),
+            }
+        })
+    };
+
+    let (background, color) = button_colors(action);
+    html! {
+        <button style={format!("background-color: {};
+                color: {};
+                padding: 14px 20px;
+                border: none;
+                cursor: pointer;
+                border-radius: 4px;", background, color)}
+                onclick={on_action}>
+                    {action.label()}
+        </button>
+    }
+}
@@ -1,21 +1,38 @@
 use std::rc::Rc;
 
-use crate::{models::*, AppStateAction};
+use crate::{api::Api, import::{CsvImport, CsvImportWizard}, export::{download_csv, CsvExport, CsvOptions}, AppStateAction, route::Route};
+use models::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, FileReader, HtmlInputElement, js_sys, window};
 use yew::prelude::*;
-use reqwest::Client;
 use gloo::console::log;
-use std::fmt::Write;
-use csv::ReaderBuilder;
 
 use crate::{AppState, AppStateContext};
 
 
-fn create_csv() -> String {
-    let mut csv_string = String::new();
-    let _ = writeln!(csv_string, "item,quantity,ip");
-    csv_string
+// Only the header goes out, as a template for the upload below
+impl CsvExport for ShipmentLine {
+    type Context = ();
+
+    const COLUMNS: &'static [&'static str] = &["item", "quantity", "ip"];
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        vec![self.item.clone(), self.quantity.to_string(), self.ip.clone()]
+    }
+}
+
+impl CsvImport for ShipmentLine {
+    const FIELDS: &'static [&'static str] = &["item", "quantity", "ip"];
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        let mut errors = vec![];
+        if self.item.is_empty() {
+            errors.push(("item", "no part number".to_string()));
+        }
+        if self.quantity == 0 {
+            errors.push(("quantity", "quantity must be more than 0".to_string()));
+        }
+        errors
+    }
 }
 
 #[function_component(ShipmentDetails)]
@@ -28,19 +45,7 @@ pub fn shipment_details() -> Html {
         let data = details.clone();
         let app_state = app_state.clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv();
-            let filename = "upload_template.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv::<ShipmentLine>("upload_template.csv", &[], &(), &CsvOptions::default());
         })
     };
 
@@ -53,33 +58,15 @@ pub fn shipment_details() -> Html {
                 let app_state = app_state.clone();
                 let details = details.clone();
                 spawn_local(async move {
-                    let client = Client::new();
                     if let Some(shp) = &app_state.current_shipment{
-                        let request = ShipmentLoadingMessage {
-                            LoadId: shp.LoadId.clone()
+                        let request = ShipmentRef {
+                            load_id: shp.load_id.clone()
                         };
-                        if let Some(user) = &app_state.user {
-                            match client.post("http://localhost:8000/api/get_shipment_details")
-                                .json(&request)
-                                .header("Authorization", format!("Bearer {}", user.token))
-                                .send()
-                                .await {
-                                    Ok(resp) => {
-                                        match resp.json::<Vec<ShipmentLine>>().await {
-                                            Ok(load_response) => {
-                                                details.set(Rc::new(load_response.clone()));
-                                            },
-                                            Err(error) => {
-                                                log!(format!("{:?}", error));
-                                                app_state.dispatch(AppStateAction::ClearUser);
-                                            },
-                                        }
-                                    },
-                                    Err(error) => {
-                                        log!(format!("Failed to login: {:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
+                        match Api::new(&app_state).get_shipment_details(&request).await {
+                            Ok(load_response) => {
+                                details.set(Rc::new(load_response.clone()));
+                            },
+                            Err(error) => error.handle(&app_state),
                         }
                     }
                 });
@@ -89,51 +76,9 @@ pub fn shipment_details() -> Html {
         }
     }
 
-    let on_file_change = {
+    let on_import = {
         let data = data.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data = data.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<ShipmentLine>::new();
-                        for result in rdr.deserialize() {
-                            match result {
-                                Ok(record) => new_data.push(record), // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data.set(new_data); // Store parsed data in state
-                    })
-                };
-
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
-        })
+        Callback::from(move |rows: Vec<ShipmentLine>| data.set(rows))
     };
 
     let upload_csv = {
@@ -143,33 +88,16 @@ pub fn shipment_details() -> Html {
             let app_state = app_state.clone();
             let data = data.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = ShipmentLineUploadRequest {
-                        LoadId: app_state.current_shipment.as_ref().unwrap().LoadId.clone(),
-                        Lines: data,
-                    };
-                    match client.post("http://localhost:8000/api/shipment_lines")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<ShipmentLine>>().await {
-                                    Ok(load_response) => {
-                                        log!(format!("{:?}", load_response));
-                                        app_state.dispatch(AppStateAction::SetCurrentView("shipments".to_string()));
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(e) => {
-                                log!(format!("Error sending request: {:?}", e));
-                            }
-                        }
+                let request = ShipmentLineUploadRequest {
+                    load_id: app_state.current_shipment.as_ref().unwrap().load_id.clone(),
+                    lines: data,
+                };
+                match Api::new(&app_state).shipment_lines(&request).await {
+                    Ok(load_response) => {
+                        log!(format!("{:?}", load_response));
+                        app_state.dispatch(AppStateAction::Navigate(Route::Shipments));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             })
         })
@@ -183,7 +111,7 @@ pub fn shipment_details() -> Html {
                 if let Some(shipment) = shipment {
                     html! {
                         <div style="margin-top: 7vh;">
-                            <h1 style="text-align: center;">{"Load Details: "} {shipment.LoadId}</h1>
+                            <h1 style="text-align: center;">{"Load Details: "} {shipment.load_id}</h1>
                             <div style="margin: 3%; display: flex; width: 70vw; flex-direction: row; justify-content: space-evenly;">
                                 <a onclick={download_csv}>{"Download Upload Template"}</a>
                             </div>
@@ -209,7 +137,7 @@ pub fn shipment_details() -> Html {
                             </table>
                             <h1 style="text-align: center;">{ "Shipment Line Upload" }</h1>
                             <div style="margin: 3%; display: flex; width: 70vw; flex-direction: row; justify-content: space-evenly;">
-                                <input type="file" accept=".csv" onchange={on_file_change} />
+                                <CsvImportWizard<ShipmentLine> title="Shipment Lines" on_import={on_import} />
                                 <button onclick={upload_csv}>{"Upload"}</button>
                                 <ul>
                                     { for data.iter().map(|item| html! {
@@ -1,28 +1,41 @@
 use std::{error::Error, rc::Rc};
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use web_sys::WebSocket;
 use yew::prelude::*;
-use crate::{models::*, recent_local_storage::*, user_local_storage::*};
+use crate::{config::AppConfig, plants::PlantRegistry, trailer_filter::TrailerFilter, outbox::{Mutation, OutboxConflict, PendingMutation}, outbox_local_storage::*, recent_local_storage::*, route::Route, user_local_storage::*, ws::WsStatus};
+use models::*;
+use models::capacity::CapacityRules;
+use models::carrier::{Carrier, CarrierDirectory};
+use models::demurrage::{DemurrageRates, DEFAULT_WARNING_DAYS};
+use models::shipment_status::ShipmentStatus;
+use crate::user::User;
+use models::ws_event::{RejectedFrame, WsEvent, WsFrame};
 use gloo::console::log;
 
-#[derive(Clone, Debug, PartialEq, Deserialize)]
-pub struct IncomingMessage {
-    pub r#type: String,
-    pub data: serde_json::Value,
-}
-
 #[derive(Clone, Debug, PartialEq)]
 pub struct AppState {
     pub user: Option<User>,
     pub current_trailer: Option<TrailerResponse>,
-    pub current_view: String,
+    pub route: Route,
     pub ws: Option<WebSocket>,
+    pub ws_status: WsStatus,
+    pub rejected_frames: Vec<RejectedFrame>,
+    pub last_seq: Option<u64>,
+    pub sync_generation: u32,
+    pub outbox: Vec<PendingMutation>,
+    pub outbox_conflicts: Vec<OutboxConflict>,
     pub messages: Vec<String>,
     pub trailers: Vec<TrailerResponse>,
-    pub last_view: String,
     pub recent_trailers:Vec<RecentTrailers>,
     pub shipments: Vec<Shipment>,
     pub current_shipment: Option<Shipment>,
+    pub config: Option<AppConfig>,
+    pub trailer_filter: TrailerFilter,
+    // Trailer IDs ticked in the trailer tables, for bulk scheduling
+    pub selected_trailers: Vec<String>,
+    pub carriers: CarrierDirectory,
+    // The config's demurrage rates with the carrier directory's on top, once there's a directory
+    carrier_rates: Option<DemurrageRates>,
 }
 
 impl Default for AppState {
@@ -30,158 +43,239 @@ impl Default for AppState {
         AppState {
             user: load_user_from_local_storage(),
             current_trailer: None,
-            current_view: load_view_from_session_storage().unwrap_or("landing".to_string()),
-            last_view: "".to_string(),
+            route: Route::current(),
             ws: None,
+            ws_status: WsStatus::Connecting,
+            rejected_frames: vec![],
+            last_seq: None,
+            sync_generation: 0,
+            outbox: load_outbox_from_local_storage().unwrap_or_default(),
+            outbox_conflicts: vec![],
             messages: vec![],
             trailers: vec![],
             recent_trailers: load_recent_from_local_storage().unwrap_or_default(),
             shipments: vec![],
             current_shipment: None,
+            config: None,
+            trailer_filter: TrailerFilter::default(),
+            selected_trailers: vec![],
+            carriers: CarrierDirectory::default(),
+            carrier_rates: None,
         }
     }
 }
 
-impl User {
-    pub fn is_authorized(&self) -> bool {
-        if self.role == "admin".to_string() || self.role == "write".to_string() {
-            true
-        } else {
-            false
+impl AppState {
+    pub fn api_url(&self, path: &str) -> String {
+        self.config.as_ref().map(|config| config.api_url(path)).unwrap_or_default()
+    }
+    pub fn plants(&self) -> &PlantRegistry {
+        static NO_PLANTS: PlantRegistry = PlantRegistry { plants: Vec::new() };
+        self.config.as_ref().map_or(&NO_PLANTS, |config| &config.plants)
+    }
+    pub fn demurrage(&self) -> &DemurrageRates {
+        static NO_RATES: DemurrageRates = DemurrageRates { default_rate: 0.0, carriers: Vec::new(), warning_days: DEFAULT_WARNING_DAYS };
+        if let Some(rates) = &self.carrier_rates {
+            return rates;
         }
+        self.config.as_ref().map_or(&NO_RATES, |config| &config.demurrage)
     }
-}
-
-impl AppState {
-    pub fn send_ws_message(&self, message: &str) {
+    pub fn capacity(&self) -> &CapacityRules {
+        static NO_RULES: CapacityRules = CapacityRules { per_hour: 0, plants: Vec::new(), door_groups: Vec::new(), blackouts: Vec::new() };
+        self.config.as_ref().map_or(&NO_RULES, |config| &config.capacity)
+    }
+    fn merge_carrier_rates(mut self) -> Self {
+        self.carrier_rates = None;
+        if !self.carriers.is_empty() {
+            self.carrier_rates = Some(self.carriers.demurrage_rates(self.demurrage()));
+        }
+        self
+    }
+    pub fn send_ws_message(&self, event: WsEvent) {
         if let Some(ws) = &self.ws {
-            ws.send_with_str(message).unwrap();
+            if let Err(e) = ws.send_with_str(&WsFrame::new(event).to_json()) {
+                log!(format!("Failed to send: {:?}", e));
+            }
+        }
+    }
+    // Checks a frame's sequence number against the last one seen. Returns false for a frame
+    // we've already applied. When events were missed, bumps sync_generation so every screen
+    // refetches its data from the API.
+    fn track_sequence(&mut self, frame: &WsFrame) -> bool {
+        let seq = match (&frame.event, frame.seq) {
+            (WsEvent::Hello { seq }, _) => {
+                if self.last_seq.is_some_and(|last| last != *seq) {
+                    log!(format!("Missed events while disconnected ({:?} -> {}), resyncing", self.last_seq, seq));
+                    self.resync();
+                }
+                self.last_seq = Some(*seq);
+                return false;
+            },
+            (_, Some(seq)) => seq,
+            (_, None) => return true,
+        };
+        match self.last_seq {
+            Some(last) if seq <= last => return false,
+            Some(last) if seq > last + 1 => {
+                log!(format!("Missed events {}..{}, resyncing", last + 1, seq));
+                self.resync();
+            },
+            _ => {},
+        }
+        self.last_seq = Some(seq);
+        true
+    }
+    fn resync(&mut self) {
+        self.sync_generation = self.sync_generation.wrapping_add(1);
+    }
+    pub fn next_mutation_id(&self) -> u64 {
+        self.outbox.iter().map(|pending| pending.id)
+            .chain(self.outbox_conflicts.iter().map(|conflict| conflict.pending.id))
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+    fn reject_frame(&mut self, rejected: RejectedFrame) {
+        log!(format!("Rejected frame ({}): {}", rejected.reason, rejected.frame));
+        self.rejected_frames.push(rejected);
+        // Only the most recent are worth keeping around
+        if self.rejected_frames.len() > MAX_REJECTED_FRAMES {
+            self.rejected_frames.remove(0);
+        }
+    }
+    // Refuses an event that would move a load out of order, e.g. a pick finishing on a load
+    // that already departed. The same step arriving twice (our own echo, or a new picker on a
+    // load that's already picking) is let through.
+    fn check_transition(&self, event: &WsEvent) -> Result<(), String> {
+        let Some((load_id, action)) = event.shipment_action() else {
+            return Ok(());
+        };
+        match self.shipments.iter().find(|shipment| shipment.load_id == load_id) {
+            Some(shipment) if shipment.status != action.target() && shipment.status.next(action).is_none() => {
+                Err(format!("{} for load {} while it is {}", action.label(), load_id, shipment.status))
+            },
+            _ => Ok(()),
+        }
+    }
+    fn apply(&mut self, event: WsEvent) {
+        match event {
+            WsEvent::HotTrailer(msg) => self.toggle_hot_trailer(&msg.trailer_id),
+            WsEvent::ScheduleTrailer(msg) => self.scheduled(msg),
+            WsEvent::TrailerArrived(msg) => self.arrived(msg),
+            WsEvent::NewShipment(shipment) => self.new_shipment(shipment),
+            WsEvent::ShipmentTrailerArrival(msg) => self.set_shipment_trailer(msg),
+            WsEvent::SetShipmentDoor(msg) => self.set_shipment_door(msg),
+            WsEvent::StartShipmentPick(msg) => self.shipment_pick_start(msg),
+            WsEvent::FinishShipmentPick(msg) => self.shipment_pick_finish(msg),
+            WsEvent::VerifiedBy(msg) => self.verified_by(msg),
+            WsEvent::ShipmentStartLoading(msg) => self.shipment_start_loading(msg),
+            WsEvent::ShipmentHold(msg) => self.shipment_hold(msg),
+            WsEvent::ShipmentDepart(msg) => self.shipment_depart(msg),
+            WsEvent::Ping | WsEvent::Pong | WsEvent::Hello { .. } => {},
         }
     }
     fn toggle_hot_trailer(&mut self, trailer_id: &str) {
         for trailer in self.trailers.iter_mut() {
-            if trailer.TrailerID == trailer_id {
-                trailer.Schedule.IsHot = !trailer.Schedule.IsHot;
+            if trailer.trailer_id == trailer_id {
+                trailer.schedule.is_hot = !trailer.schedule.is_hot;
                 break;
             }
         }
     }
-    fn arrived(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let arrival_message: ArrivalMessage = serde_json::from_str(msg)?;
+    fn arrived(&mut self, arrival_message: Arrival) {
         for trailer in self.trailers.iter_mut() {
-            if trailer.TrailerID == arrival_message.TrailerID {
-                trailer.Schedule.ArrivalTime = arrival_message.ArrivalTime;
+            if trailer.trailer_id == arrival_message.trailer_id {
+                trailer.schedule.arrival_time = arrival_message.arrival_time;
                 break;
             }
         }
-        Ok(())
     }
-    fn scheduled(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let schedule_message: SetScheduleRequest = serde_json::from_str(msg)?;
+    fn scheduled(&mut self, schedule_message: SetScheduleRequest) {
         for trailer in self.trailers.iter_mut() {
-            if trailer.TrailerID == schedule_message.TrailerID {
-                trailer.Schedule.ScheduleDate = schedule_message.ScheduleDate;
-                trailer.Schedule.RequestDate = schedule_message.RequestDate;
-                trailer.Schedule.CarrierCode = schedule_message.CarrierCode;
-                trailer.Schedule.ScheduleTime = schedule_message.ScheduleTime;
-                trailer.Schedule.DoorNumber = schedule_message.Door;
-                trailer.Schedule.ContactEmail = schedule_message.ContactEmail;
-                trailer.Schedule.LastFreeDate = schedule_message.LastFreeDate;
+            if trailer.trailer_id == schedule_message.trailer_id {
+                trailer.schedule.schedule_date = schedule_message.schedule_date;
+                trailer.schedule.request_date = schedule_message.request_date;
+                trailer.schedule.carrier_code = schedule_message.carrier_code;
+                trailer.schedule.schedule_time = schedule_message.schedule_time;
+                trailer.schedule.door_number = schedule_message.door;
+                trailer.schedule.contact_email = schedule_message.contact_email;
+                trailer.schedule.last_free_date = schedule_message.last_free_date;
                 break;
             }
         }
-        Ok(())
     }
-    fn set_shipment_trailer(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: TrailerArrivalMessage = serde_json::from_str(msg)?;
+    fn set_shipment_trailer(&mut self, shipment_message: ShipmentTrailer) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.ArrivalTime = shipment_message.ArrivalTime;
-                shipment.TrailerNum = shipment_message.TrailerNum;
+            if shipment.load_id == shipment_message.load_id {
+                shipment.arrival_time = shipment_message.arrival_time;
+                shipment.trailer_num = shipment_message.trailer_num;
                 break;
             }
         }
-        Ok(())
     }
-    fn set_shipment_door(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: SetShipmentDoorMessage = serde_json::from_str(msg)?;
+    fn set_shipment_door(&mut self, shipment_message: ShipmentDoor) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.Door = shipment_message.Door;
+            if shipment.load_id == shipment_message.load_id {
+                shipment.door = shipment_message.door;
                 break;
             }
         }
-        Ok(())
     }
-    fn shipment_depart(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: SetShipmentDepartMessage = serde_json::from_str(msg)?;
+    fn shipment_depart(&mut self, shipment_message: Departure) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.DepartTime = shipment_message.DepartTime;
-                shipment.Status = "COMPLETE".to_string();
+            if shipment.load_id == shipment_message.load_id {
+                shipment.depart_time = shipment_message.depart_time;
+                shipment.seal = shipment_message.seal;
+                shipment.status = ShipmentStatus::Complete;
                 break;
             }
         }
-        Ok(())
     }
-    fn shipment_pick_start(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: PickStartMessage = serde_json::from_str(msg)?;
+    fn shipment_pick_start(&mut self, shipment_message: PickStart) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.Picker = shipment_message.Picker;
-                shipment.PickStartTime = shipment_message.StartTime;
-                shipment.Status = "PICKING".to_string();
+            if shipment.load_id == shipment_message.load_id {
+                shipment.picker = shipment_message.picker;
+                shipment.pick_start_time = shipment_message.start_time;
+                shipment.status = ShipmentStatus::Picking;
                 break;
             }
         }
-        Ok(())
     }
-    fn shipment_pick_finish(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: PickFinishMessage = serde_json::from_str(msg)?;
+    fn shipment_pick_finish(&mut self, shipment_message: PickFinish) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.PickFinishTime = shipment_message.FinishTime;
-                shipment.Status = "VERIFICATION".to_string();
+            if shipment.load_id == shipment_message.load_id {
+                shipment.pick_finish_time = shipment_message.finish_time;
+                shipment.status = ShipmentStatus::Verification;
                 break;
             }
         }
-        Ok(())
     }
-    fn shipment_start_loading(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: StartLoadingMessage = serde_json::from_str(msg)?;
+    fn shipment_start_loading(&mut self, shipment_message: ShipmentRef) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.Status = "LOADING".to_string();
+            if shipment.load_id == shipment_message.load_id {
+                shipment.status = ShipmentStatus::Loading;
                 break;
             }
         }
-        Ok(())
     }
-    fn shipment_hold(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: StartLoadingMessage = serde_json::from_str(msg)?;
+    fn shipment_hold(&mut self, shipment_message: ShipmentRef) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.IsHold = !shipment.IsHold;
+            if shipment.load_id == shipment_message.load_id {
+                shipment.is_hold = !shipment.is_hold;
                 break;
             }
         }
-        Ok(())
     }
-    fn verified_by(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: VerifiedByMessage = serde_json::from_str(msg)?;
+    fn verified_by(&mut self, shipment_message: Verification) {
         for shipment in self.shipments.iter_mut() {
-            if shipment.LoadId == shipment_message.LoadId {
-                shipment.VerifiedBy = shipment_message.VerifiedBy;
-                shipment.Status = "READY TO LOAD".to_string();
+            if shipment.load_id == shipment_message.load_id {
+                shipment.verified_by = shipment_message.verified_by;
+                shipment.status = ShipmentStatus::ReadyToLoad;
                 break;
             }
         }
-        Ok(())
     }
-    fn new_shipment(&mut self, msg: &str) -> Result<(), Box<dyn Error>> {
-        let shipment_message: Shipment = serde_json::from_str(msg)?;
+    fn new_shipment(&mut self, shipment_message: Shipment) {
         self.shipments.insert(0, shipment_message);
-        Ok(())
     }
     fn recent(&mut self, trailer: RecentTrailers) -> Result<(), Box<dyn Error>> {
         let mut found = false;
@@ -192,6 +286,9 @@ impl AppState {
                 trl.date = t.date.clone();
                 trl.time = t.time.clone();
                 trl.scac = t.scac.clone();
+                trl.door = t.door.clone();
+                trl.contact_email = t.contact_email.clone();
+                trl.cisco_ids = t.cisco_ids.clone();
                 found = true;
             }
         }
@@ -204,33 +301,34 @@ impl AppState {
     }
 }
 
+const MAX_REJECTED_FRAMES: usize = 50;
+
 pub enum AppStateAction {
     SetUser(User),
     ClearUser,
     SetCurrentTrailer(TrailerResponse),
     ClearCurrentTrailer,
-    SetCurrentView(String),
+    Navigate(Route),
+    SetRoute(Route),
     ConnectWebSocket(WebSocket),
     DisconnectWebSocket,
-    HandleHotTrailer(serde_json::Value),
-    HandleScheduleTrailer(serde_json::Value),
-    HandleSetDoor(serde_json::Value),
-    HandleTrailerArrived(serde_json::Value),
+    SetWsStatus(WsStatus),
+    HandleWsFrame(WsFrame),
+    RejectWsFrame(RejectedFrame),
+    QueueMutation(Mutation),
+    RemoveMutation(u64),
+    RejectMutation(OutboxConflict),
+    DismissConflict(u64),
     SetTrailers(Vec<TrailerResponse>),
-    SetLastView(String),
     AddToRecentlyScheduled(RecentTrailers),
     ClearRecentlyScheduled,
-    HandleNewShipment(serde_json::Value),
-    HandlePickFinish(serde_json::Value),
-    HandleShipmentDoor(serde_json::Value),
-    HandleShipmentTrailer(serde_json::Value),
-    HandleShipmentDepart(serde_json::Value),
-    HandlePickStart(serde_json::Value),
-    HandleShipmentHold(serde_json::Value),
-    HandleVerifiedBy(serde_json::Value),
-    HandleShipmentLoading(serde_json::Value),
     SetShipments(Vec<Shipment>),
     SetCurrentShipment(Shipment),
+    SetConfig(AppConfig),
+    SetTrailerFilter(TrailerFilter),
+    ToggleTrailerSelected(String),
+    SetSelectedTrailers(Vec<String>),
+    SetCarriers(Vec<Carrier>),
 }
 
 impl Reducible for AppState {
@@ -243,90 +341,72 @@ impl Reducible for AppState {
                 Rc::new(Self { user: Some(user), ..(*self).clone() })
             },
             AppStateAction::SetCurrentShipment(shipment) => Rc::new(Self { current_shipment: Some(shipment), ..(*self).clone() }),
-            AppStateAction::ClearUser => Rc::new(Self { user: None, ..(*self).clone() }),
-            AppStateAction::SetCurrentTrailer(trailer) => Rc::new(Self { current_trailer: Some(trailer), ..(*self).clone() }),
-            AppStateAction::ClearCurrentTrailer => Rc::new(Self { current_trailer: None, ..(*self).clone() }),
-            AppStateAction::ClearRecentlyScheduled => Rc::new(Self { recent_trailers: vec![], ..(*self).clone() }),
-            AppStateAction::SetCurrentView(view) => {
-                let _ = save_view_to_session_storage(&view);
-                Rc::new(Self { current_view: view, ..(*self).clone() })
-            },
-            AppStateAction::HandleShipmentDoor(data) => {
-                log!(format!("Handling shipment door: {:?}", data));
+            AppStateAction::SetConfig(config) => Rc::new(Self { config: Some(config), ..(*self).clone() }.merge_carrier_rates()),
+            AppStateAction::SetCarriers(carriers) => Rc::new(Self { carriers: CarrierDirectory::new(carriers), ..(*self).clone() }.merge_carrier_rates()),
+            AppStateAction::SetTrailerFilter(trailer_filter) => Rc::new(Self { trailer_filter, ..(*self).clone() }),
+            AppStateAction::ToggleTrailerSelected(trailer_id) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.set_shipment_door(message);
+                if new_state.selected_trailers.contains(&trailer_id) {
+                    new_state.selected_trailers.retain(|selected| *selected != trailer_id);
+                } else {
+                    new_state.selected_trailers.push(trailer_id);
                 }
                 Rc::new(new_state)
             },
-            AppStateAction::HandleShipmentHold(data) => {
-                log!(format!("Handling shipment hold: {:?}", data));
-                let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.shipment_hold(message);
-                }
-                Rc::new(new_state)
+            AppStateAction::SetSelectedTrailers(selected_trailers) => Rc::new(Self { selected_trailers, ..(*self).clone() }),
+            AppStateAction::ClearUser => Rc::new(Self { user: None, ..(*self).clone() }),
+            AppStateAction::SetCurrentTrailer(trailer) => Rc::new(Self { current_trailer: Some(trailer), ..(*self).clone() }),
+            AppStateAction::ClearCurrentTrailer => Rc::new(Self { current_trailer: None, ..(*self).clone() }),
+            AppStateAction::ClearRecentlyScheduled => Rc::new(Self { recent_trailers: vec![], ..(*self).clone() }),
+            AppStateAction::Navigate(route) => {
+                route.push();
+                Rc::new(Self { route, ..(*self).clone() })
             },
-            AppStateAction::HandleNewShipment(data) => {
-                log!(format!("Handling new shipment: {:?}", data));
+            AppStateAction::SetShipments(shipments) => Rc::new(Self { shipments, ..(*self).clone() }),
+            AppStateAction::SetRoute(route) => Rc::new(Self { route, ..(*self).clone() }),
+            AppStateAction::ConnectWebSocket(ws) => Rc::new(Self { ws: Some(ws), ws_status: WsStatus::Connected, ..(*self).clone() }),
+            AppStateAction::DisconnectWebSocket => Rc::new(Self { ws: None, ..(*self).clone() }),
+            AppStateAction::SetWsStatus(ws_status) => Rc::new(Self { ws_status, ..(*self).clone() }),
+            AppStateAction::HandleWsFrame(frame) => {
+                log!(format!("Handling {:?}", frame));
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.new_shipment(message);
+                if new_state.track_sequence(&frame) {
+                    match new_state.check_transition(&frame.event) {
+                        Ok(()) => new_state.apply(frame.event),
+                        Err(reason) => new_state.reject_frame(RejectedFrame { reason, frame: frame.to_json() }),
+                    }
                 }
                 Rc::new(new_state)
             },
-            AppStateAction::HandleShipmentLoading(data) => {
-                log!(format!("Handling loading: {:?}", data));
+            AppStateAction::QueueMutation(mutation) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.shipment_start_loading(message);
-                }
+                new_state.outbox.push(PendingMutation::new(self.next_mutation_id(), mutation));
+                let _ = save_outbox_to_local_storage(&new_state.outbox);
                 Rc::new(new_state)
             },
-            AppStateAction::HandlePickFinish(data) => {
-                log!(format!("Handling pick finish: {:?}", data));
+            AppStateAction::RemoveMutation(id) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.shipment_pick_finish(message);
-                }
+                new_state.outbox.retain(|pending| pending.id != id);
+                let _ = save_outbox_to_local_storage(&new_state.outbox);
                 Rc::new(new_state)
             },
-            AppStateAction::HandleVerifiedBy(data) => {
-                log!(format!("Handling verified by: {:?}", data));
+            AppStateAction::RejectMutation(conflict) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.verified_by(message);
-                }
+                new_state.outbox.retain(|pending| pending.id != conflict.pending.id);
+                let _ = save_outbox_to_local_storage(&new_state.outbox);
+                new_state.outbox_conflicts.push(conflict);
                 Rc::new(new_state)
             },
-            AppStateAction::HandlePickStart(data) => {
-                log!(format!("Handling pick start: {:?}", data));
+            AppStateAction::DismissConflict(id) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.shipment_pick_start(message);
-                }
+                new_state.outbox_conflicts.retain(|conflict| conflict.pending.id != id);
                 Rc::new(new_state)
             },
-            AppStateAction::HandleShipmentDepart(data) => {
-                log!(format!("Handling complete shipment: {:?}", data));
+            AppStateAction::RejectWsFrame(rejected) => {
                 let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.shipment_depart(message);
-                }
+                new_state.reject_frame(rejected);
                 Rc::new(new_state)
             },
-            AppStateAction::HandleShipmentTrailer(data) => {
-                log!(format!("Handling shipment trailer: {:?}", data));
-                let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.set_shipment_trailer(message);
-                }
-                Rc::new(new_state)
-            },
-            AppStateAction::SetShipments(shipments) => Rc::new(Self { shipments, ..(*self).clone() }),
-            AppStateAction::SetLastView(view) => Rc::new(Self { last_view: view, ..(*self).clone() }),
-            AppStateAction::ConnectWebSocket(ws) => Rc::new(Self { ws: Some(ws), ..(*self).clone() }),
-            AppStateAction::DisconnectWebSocket => Rc::new(Self { ws: None, ..(*self).clone() }),
             AppStateAction::SetTrailers(trailers) => Rc::new(Self { trailers, ..(*self).clone()}),
             AppStateAction::AddToRecentlyScheduled(trailer) => {
                 let mut new_state = (*self).clone();
@@ -334,39 +414,6 @@ impl Reducible for AppState {
                 let _ = save_recent_to_local_storage(&new_state.recent_trailers);
                 Rc::new(new_state)
             }
-            AppStateAction::HandleHotTrailer(data) => {
-                // Handle hot_trailer data
-                log!(format!("Handling hot_trailer: {:?}", data));
-                let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    new_state.toggle_hot_trailer(message);
-                }
-                Rc::new(new_state)
-            },
-            AppStateAction::HandleScheduleTrailer(data) => {
-                // Handle schedule_trailer data
-                log!(format!("Handling schedule_trailer: {:?}", data));
-                let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.scheduled(message);
-                }
-                Rc::new(new_state)
-            },
-            AppStateAction::HandleSetDoor(data) => {
-                // Handle set_door data
-                log!(format!("Handling set_door: {:?}", data));
-                self
-            },
-            AppStateAction::HandleTrailerArrived(data) => {
-                // Handle trailer_arrived data
-                log!(format!("Handling trailer_arrived: {:?}", data));
-                let mut new_state = (*self).clone();
-                if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
-                    let _ = new_state.arrived(message);
-                }
-                Rc::new(new_state)
-            },
-            _ => todo!(),
         }
     }
 }
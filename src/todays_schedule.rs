@@ -1,72 +1,61 @@
-use crate::{models::*, state::*};
-use chrono::prelude::*;
-use serde_json::json;
+use crate::{api::Api, export::{download_csv, CsvExport, CsvOptions}, xlsx::{download_xlsx, Cell, XlsxExport}, plants::PlantRegistry, outbox::{self, Mutation}, state::*, route::Route, permissions::Permission, demurrage::lfd_cell, bulk_schedule::{bulk_schedule_bar, select_all_cell, select_cell}};
+use models::*;
+use models::time::{CalendarDate, Timestamp};
+use models::ws_event::WsEvent;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{js_sys, window};
 use yew::prelude::*;
-use reqwest::Client;
 use gloo::console::log;
+use rust_xlsxwriter::Color;
 use crate::daily_csv::DailyCsv;
-use std::fmt::Write;
 
 use crate::AppStateContext;
 
-fn render_locations(locations: &Vec<String>) -> String {
-    let mut txt = String::new();
-    for location in locations {
-        match location.as_str() {
-            "18008" => txt.push_str(" AR"),
-            "18044" => txt.push_str(" FF"),
-            "22010" => txt.push_str(" 40"),
-            _ => {}
-        }
-    }
-    txt.trim().to_string()
-}
+impl CsvExport for TrailerResponse {
+    type Context = PlantRegistry;
 
-fn create_csv(data: &Vec<TrailerResponse>) -> String {
-    let mut csv_string = String::new();
-    let _ = writeln!(csv_string, "Container ID, Request Date, SCAC Code, Plant Code, Schedule Date, Schedule Time, Arrival Time, Door Number, Contact Email");
-    for trailer in data {
-        let _ = writeln!(csv_string, "{},{},{},{},{},{},{},{},{}", trailer.TrailerID, trailer.Schedule.RequestDate, trailer.Schedule.CarrierCode ,render_locations(&trailer.CiscoIDs), trailer.Schedule.ScheduleDate, trailer.Schedule.ScheduleTime, trailer.Schedule.ArrivalTime, trailer.Schedule.DoorNumber, trailer.Schedule.ContactEmail);
+    const COLUMNS: &'static [&'static str] = &[
+        "Container ID", "Request Date", "SCAC Code", "Plant Code", "Schedule Date",
+        "Schedule Time", "Arrival Time", "Door Number", "Contact Email",
+    ];
+
+    fn fields(&self, plants: &PlantRegistry) -> Vec<String> {
+        vec![
+            self.trailer_id.clone(),
+            self.schedule.request_date.to_string(),
+            self.schedule.carrier_code.clone(),
+            plants.locations(&self.cisco_ids),
+            self.schedule.schedule_date.to_string(),
+            self.schedule.schedule_time.to_string(),
+            self.schedule.arrival_time.to_string(),
+            self.schedule.door_number.clone(),
+            self.schedule.contact_email.clone(),
+        ]
     }
-    csv_string
 }
 
-fn parse_time(time_str: &str) -> (u32, u32) {
-    let time_str = if time_str.is_empty() { "00:00" } else { time_str };
-    
-    // Split the string by ':'
-    let parts: Vec<&str> = time_str.split(':').collect();
-    
-    // Initialize hours and minutes to 0
-    let mut hours = 0;
-    let mut minutes = 0;
-
-    // Parse hours if available
-    if let Some(hour_str) = parts.get(0) {
-        hours = hour_str.parse::<u32>().unwrap_or(0);
+impl XlsxExport for TrailerResponse {
+    fn cells(&self, plants: &PlantRegistry) -> Vec<Cell> {
+        vec![
+            self.trailer_id.clone().into(),
+            self.schedule.request_date.into(),
+            self.schedule.carrier_code.clone().into(),
+            plants.locations(&self.cisco_ids).into(),
+            self.schedule.schedule_date.into(),
+            self.schedule.schedule_time.into(),
+            self.schedule.arrival_time.into(),
+            self.schedule.door_number.clone().into(),
+            self.schedule.contact_email.clone().into(),
+        ]
     }
 
-    // Parse minutes if available
-    if let Some(minute_str) = parts.get(1) {
-        minutes = minute_str.parse::<u32>().unwrap_or(0);
+    // Hot trailers stand out, and so does anything that's already arrived
+    fn fill(&self, column: &str, _: &PlantRegistry) -> Option<Color> {
+        match column {
+            "Container ID" if self.schedule.is_hot => Some(Color::RGB(0xFFC7CE)),
+            "Arrival Time" if !self.schedule.arrival_time.is_empty() => Some(Color::RGB(0xC6EFCE)),
+            _ => None,
+        }
     }
-
-    (hours, minutes)
-}
-
-fn time() -> String {
-    let now = Local::now();
-    format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second())
-}
-
-fn format_current_date() -> String {
-    let local: DateTime<Local> = Local::now();
-    let year = local.year();
-    let month = format!("{:02}", local.month());
-    let day = format!("{:02}", local.day());
-    format!("{}-{}-{}", year, month, day)
 }
 
 #[function_component(TodaysSchedule)]
@@ -75,43 +64,19 @@ pub fn todays_schedule() -> Html {
 
     {
         let app_state = app_state.clone();
-        use_effect_with((), move |_| {
+        use_effect_with(app_state.sync_generation, move |_| {
             spawn_local(async move {
-                let client = Client::new();
-                let date = format_current_date();
                 let request = TodaysTrucksRequest {
-                    date: date.clone()
+                    date: CalendarDate::today(),
                 };
-                if let Some(user) = &app_state.user {
-                    match client.post("http://192.168.4.172:8000/api/todays_trucks")
-                        .json(&request)
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerResponse>>().await {
-                                    Ok(todays_trailers) => {
-                                        let mut trailers = todays_trailers.clone();
-                                        trailers.sort_by(|a, b| {
-                                            let (hours_a, minutes_a) = parse_time(&a.Schedule.ScheduleTime);
-                                            let (hours_b, minutes_b) = parse_time(&b.Schedule.ScheduleTime);
-                                            if hours_a == hours_b {
-                                                minutes_a.cmp(&minutes_b)
-                                            } else {
-                                                hours_a.cmp(&hours_b)
-                                            }
-                                        });
-                                        //log!(format!("{:?}", trailers));
-                                        app_state.dispatch(AppStateAction::SetTrailers(trailers));
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("Error: {:?}", error))
-                        }
+                match Api::new(&app_state).todays_trucks(&request).await {
+                    Ok(todays_trailers) => {
+                        let mut trailers = todays_trailers.clone();
+                        trailers.sort_by(|a, b| a.schedule.schedule_time.cmp(&b.schedule.schedule_time));
+                        //log!(format!("{:?}", trailers));
+                        app_state.dispatch(AppStateAction::SetTrailers(trailers));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
             || ()
@@ -120,20 +85,17 @@ pub fn todays_schedule() -> Html {
 
     let download_csv = {
         let data = app_state.trailers.clone();
+        let plants = app_state.plants().clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data);
-            let filename = "daily.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("daily.csv", &data, &plants, &CsvOptions::default().with_bom());
+        })
+    };
+
+    let download_xlsx = {
+        let data = app_state.trailers.clone();
+        let plants = app_state.plants().clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx("daily.xlsx", "Today's Schedule", &data, &plants, &CsvOptions::default());
         })
     };
 
@@ -142,41 +104,12 @@ pub fn todays_schedule() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: "".to_string(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: "".to_string(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::default(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -187,42 +120,12 @@ pub fn todays_schedule() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                let now = time();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: now.clone(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: now.clone(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::now(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -233,37 +136,15 @@ pub fn todays_schedule() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = HotTrailerRequest {
-                        TrailerID: trailer_id.clone(),
-                    };
-
-                    match client.post("http://192.168.4.172:8000/api/hot_trailer")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(trailer_response) => {
-                                        log!(format!("{:?}", trailer_response));
-                                        let message = json!({
-                                            "type": "hot_trailer",
-                                            "data": {
-                                                "message": trailer_id.clone()
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = HotTrailer {
+                    trailer_id: trailer_id.clone(),
+                };
+                match Api::new(&app_state).hot_trailer(&request).await {
+                    Ok(trailer_response) => {
+                        log!(format!("{:?}", trailer_response));
+                        app_state.send_ws_message(WsEvent::HotTrailer(HotTrailer { trailer_id: trailer_id.clone() }));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
         })
@@ -272,16 +153,18 @@ pub fn todays_schedule() -> Html {
     let load = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::LoadDetails(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetCurrentView("load_details".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     let edit = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::EditTrailer(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetCurrentView("edit_trailer".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
@@ -300,10 +183,14 @@ pub fn todays_schedule() -> Html {
             margin-right: auto;
             margin-bottom: 3%">
                 <a onclick={download_csv}>{"Download Todays Schedule"}</a>
+                <a onclick={download_xlsx}>{"Download Todays Schedule (Excel)"}</a>
             </div>
+            { bulk_schedule_bar(&app_state) }
             <table>
                 <thead>
                     <tr style="text-align: center;">
+                        { select_all_cell(&app_state, app_state.trailers.iter().map(|trailer| trailer.trailer_id.clone()).collect()) }
+                        <th>{"#"}</th>
                         <th>{"Request Date"}</th>
                         <th>{"Trailer ID"}</th>
                         <th>{"SCAC"}</th>
@@ -318,33 +205,34 @@ pub fn todays_schedule() -> Html {
                 </thead>
                 <tbody>
                 { app_state.trailers.iter().enumerate().map(|(index, trailer)| {
-                    if trailer.Schedule.IsHot {
-                        let trailer_id = trailer.TrailerID.clone();
-                        let trailer_id1 = trailer.TrailerID.clone();
-                        let trailer_id2 = trailer.TrailerID.clone();
+                    if trailer.schedule.is_hot {
+                        let trailer_id = trailer.trailer_id.clone();
+                        let trailer_id1 = trailer.trailer_id.clone();
+                        let trailer_id2 = trailer.trailer_id.clone();
                         let tr = trailer.clone();
                         let tr1 = trailer.clone();
                         let user = app_state.user.as_ref().unwrap();
                         html! { 
                             <tr style="background-color: red; text-align: center;">
+                                { select_cell(&app_state, &trailer.trailer_id) }
                                 <td>{index + 1}</td>
-                                <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                <td>{trailer.schedule.request_date.to_string()}</td>
+                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                <td>{trailer.schedule.carrier_code.clone()}</td>
+                                <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                { lfd_cell(trailer, app_state.demurrage()) }
+                                <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                     html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                 } else {
-                                    html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                    html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                 }}
-                                <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                <td>{trailer.schedule.door_number.clone()}</td>
                                 <td><button style="background-color: #4CAF50; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Not Hot"}</button></td>
-                                { if user.role.clone() == "write".to_string() {
+                                { if user.can(Permission::ScheduleTrailer) {
                                     html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                 } else {
                                     html! {<></>}
@@ -352,32 +240,33 @@ pub fn todays_schedule() -> Html {
                             </tr>
                         }
                     } else {
-                        let trailer_id = trailer.TrailerID.clone();
-                        let trailer_id1 = trailer.TrailerID.clone();
-                        let trailer_id2 = trailer.TrailerID.clone();
+                        let trailer_id = trailer.trailer_id.clone();
+                        let trailer_id1 = trailer.trailer_id.clone();
+                        let trailer_id2 = trailer.trailer_id.clone();
                         let tr = trailer.clone();
                         let tr1 = trailer.clone();
                         let user = app_state.user.as_ref().unwrap();
                         html! {
                             <tr style="text-align: center;">
+                                { select_cell(&app_state, &trailer.trailer_id) }
                                 <td>{index + 1}</td>
-                                <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                <td>{trailer.schedule.request_date.to_string()}</td>
+                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                <td>{trailer.schedule.carrier_code.clone()}</td>
+                                <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                { lfd_cell(trailer, app_state.demurrage()) }
+                                <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                     html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                 } else {
-                                    html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                    html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                 }}
-                                <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                <td>{trailer.schedule.door_number.clone()}</td>
                                 <td><button style="background-color: #F44336; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Hot"}</button></td>
-                                { if user.role.clone() == "write".to_string() {
+                                { if user.can(Permission::ScheduleTrailer) {
                                     html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                 } else {
                                     html! {<></>}
@@ -1,137 +1,114 @@
-use serde_json::json;
 use web_sys::{HtmlInputElement, KeyboardEvent};
 use yew::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use reqwest::Client;
-use gloo::console::log;
-use crate::{models::*, state::AppStateContext, AppStateAction, float_button::FloatingIcon};
-use chrono::prelude::*;
-
-fn time() -> String {
-    let now = Local::now();
-    format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second())
-}
-
-fn get_background(status: &str) -> String {
-    match status {
-        "PICKING" => "yellow".to_string(),
-        "READY TO LOAD" => "blue".to_string(),
-        "LOADING" => "green".to_string(),
-        "COMPLETE" => "gray".to_string(),
-        "VERIFICATION" => "orange".to_string(),
-        "NOT STARTED" => "aqua".to_string(),
-        _ => "".to_string(),
-    }
-}
-
-fn format_current_date() -> String {
-    let local: DateTime<Local> = Local::now();
-    let year = local.year();
-    let month = format!("{:02}", local.month());
-    let day = format!("{:02}", local.day());
-    format!("{}-{}-{}", year, month, day)
-}
+use crate::{api::Api, export::{CsvExport, CsvOptions}, xlsx::{download_xlsx, Cell, XlsxExport}, plants::PlantRegistry, state::AppStateContext, AppStateAction, float_button::FloatingIcon, route::Route, permissions::{Permission, RequirePermission}, shipment_action::ActionButton};
+use models::*;
+use models::time::CalendarDate;
+use models::shipment_status::ShipmentStatus;
+use models::ws_event::WsEvent;
+use rust_xlsxwriter::Color;
 
 fn total_expected(shipments: &Vec<Shipment>) -> (u32, u32, u32, u32, u32, u32, u32) {
     let (mut ns, mut p, mut rtl, mut ld, mut cmp, mut hld, mut vl) = (0,0,0,0,0,0,0);
     for shipment in shipments {
-        match (shipment.Status.as_str(), shipment.IsHold) {
-            ("NOT STARTED", false) => ns += 1,
-            ("READY TO LOAD", false) => rtl += 1,
-            ("LOADING", false) => ld += 1,
-            ("COMPLETE", false) => cmp += 1,
-            ("VERIFICATION", false) => vl += 1,
-            ("PICKING",  false) => p += 1,
-            _ => hld += 1,
+        match (shipment.status, shipment.is_hold) {
+            (ShipmentStatus::NotStarted, false) => ns += 1,
+            (ShipmentStatus::ReadyToLoad, false) => rtl += 1,
+            (ShipmentStatus::Loading, false) => ld += 1,
+            (ShipmentStatus::Complete, false) => cmp += 1,
+            (ShipmentStatus::Verification, false) => vl += 1,
+            (ShipmentStatus::Picking, false) => p += 1,
+            (ShipmentStatus::Unknown, false) => {},
+            (_, true) => hld += 1,
         }
     }
     (ns, p, rtl, ld, cmp, vl, hld)
 }
 
-fn total_by_plant(shipments: &Vec<Shipment>) -> (u32, u32) {
-    let (mut ar, mut sh) = (0, 0);
-    for shipment in shipments {
-        match shipment.Dock.to_lowercase().as_str() {
-            "73y" => sh += 1,
-            "74y" => sh += 1,
-            "75y" => sh += 1,
-            "uuu" => ar += 1,
-            "vaa" => ar += 1,
-            _ => todo!(),
-        }
-    }
-    (ar, sh)
+// Loads per shipping plant, by the dock they're built on. Loads on a dock we don't know aren't counted.
+fn total_by_plant(shipments: &Vec<Shipment>, plants: &PlantRegistry) -> Vec<(String, usize)> {
+    plants.shipping_plants().map(|plant| {
+        let count = shipments.iter()
+            .filter(|shipment| plants.dock(&shipment.dock).is_some_and(|(p, _)| p.code == plant.code))
+            .count();
+        (plant.short_name().to_string(), count)
+    }).collect()
 }
 
-fn parse_time(time_str: &str) -> (u32, u32) {
-    let time_str = if time_str.is_empty() { "00:00" } else { time_str };
-    
-    // Split the string by ':'
-    let parts: Vec<&str> = time_str.split(':').collect();
-    
-    // Initialize hours and minutes to 0
-    let mut hours = 0;
-    let mut minutes = 0;
+impl CsvExport for Shipment {
+    type Context = ();
 
-    // Parse hours if available
-    if let Some(hour_str) = parts.get(0) {
-        hours = hour_str.parse::<u32>().unwrap_or(0);
+    const COLUMNS: &'static [&'static str] = &[
+        "Load ID", "Status", "Scheduled Date", "Scheduled Time", "Arrival Time", "Departure Time", "Door", "Dock",
+        "Trailer Number", "Load Number", "Picker", "Pick Start Time", "Pick Finish Time", "Verified By",
+    ];
+
+    fn fields(&self, _: &()) -> Vec<String> {
+        self.cells(&()).iter().map(Cell::to_string).collect()
     }
+}
 
-    // Parse minutes if available
-    if let Some(minute_str) = parts.get(1) {
-        minutes = minute_str.parse::<u32>().unwrap_or(0);
+impl XlsxExport for Shipment {
+    fn cells(&self, _: &()) -> Vec<Cell> {
+        let status = if self.is_hold { "HOLD" } else { self.status.as_str() };
+        vec![
+            self.load_id.clone().into(),
+            status.into(),
+            self.schedule_date.into(),
+            self.schedule_time.into(),
+            self.arrival_time.into(),
+            self.depart_time.into(),
+            self.door.clone().into(),
+            self.dock.clone().into(),
+            self.trailer_num.clone().into(),
+            self.load_num.clone().into(),
+            self.picker.clone().into(),
+            self.pick_start_time.into(),
+            self.pick_finish_time.into(),
+            self.verified_by.clone().into(),
+        ]
     }
 
-    (hours, minutes)
+    // The status column is filled the way the table colours it, in lighter shades so it prints
+    fn fill(&self, column: &str, _: &()) -> Option<Color> {
+        if column != "Status" {
+            return None;
+        }
+        if self.is_hold {
+            return Some(Color::RGB(0xFFC7CE));
+        }
+        Some(Color::RGB(match self.status {
+            ShipmentStatus::NotStarted => 0xCCFFFF,
+            ShipmentStatus::Picking => 0xFFFF99,
+            ShipmentStatus::Verification => 0xFFCC99,
+            ShipmentStatus::ReadyToLoad => 0x99CCFF,
+            ShipmentStatus::Loading => 0xC6EFCE,
+            ShipmentStatus::Complete => 0xD9D9D9,
+            ShipmentStatus::Unknown => 0xFFFFFF,
+        }))
+    }
 }
 
-
 #[function_component(TodaysShipments)]
 pub fn shipments() -> Html {
 
     let app_state = use_context::<AppStateContext>().expect("no state found");
     {
         let app_state = app_state.clone();
-        use_effect_with((), move |_| {
+        use_effect_with(app_state.sync_generation, move |_| {
             let app_state = app_state.clone();
 
             spawn_local(async move {
-                let client = Client::new();
                 let request = TodaysTrucksRequest {
-                    date: format_current_date(),
+                    date: CalendarDate::today(),
                 };
-                if let Some(user) = &app_state.user {
-                    match client.post("http://localhost:8000/api/get_todays_shipments")
-                        .json(&request)
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<Shipment>>().await {
-                                    Ok(shipments) => {
-                                        let mut sh = shipments.clone();
-                                        sh.sort_by(|a, b| {
-                                            let (hours_a, minutes_a) = parse_time(&a.ScheduleTime);
-                                            let (hours_b, minutes_b) = parse_time(&b.ScheduleTime);
-                                            if hours_a == hours_b && minutes_a == minutes_b {
-                                                a.Dock.cmp(&b.Dock)
-                                            } else if hours_a == hours_b && minutes_a != minutes_b {
-                                                minutes_a.cmp(&minutes_b)
-                                            } else {
-                                                hours_a.cmp(&hours_b)
-                                            }
-                                        });
-                                        app_state.dispatch(AppStateAction::SetShipments(sh));
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    }
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                match Api::new(&app_state).get_todays_shipments(&request).await {
+                    Ok(shipments) => {
+                        let mut sh = shipments.clone();
+                        sh.sort_by(|a, b| a.schedule_time.cmp(&b.schedule_time).then(a.dock.cmp(&b.dock)));
+                        app_state.dispatch(AppStateAction::SetShipments(sh));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
         });
@@ -140,8 +117,9 @@ pub fn shipments() -> Html {
     let set_picker = {
         let app_state = app_state.clone();
         Callback::from(move |shipment: Shipment| {
+            let route = Route::SetPicker(shipment.load_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentShipment(shipment));
-            app_state.dispatch(AppStateAction::SetCurrentView("set_picker".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
@@ -151,39 +129,17 @@ pub fn shipments() -> Html {
             let app_state = app_state.clone();
             let shipment = shipment.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = ShipmentLoadingRequest {
-                        LoadId: shipment.LoadId.clone(),
-                    };
-                    match client.post("http://localhost:8000/api/shipment_hold")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Shipment>().await {
-                                    Ok(shipment) => {
-                                        let msg = ShipmentLoadingMessage {
-                                            LoadId: shipment.LoadId,
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "shipment_hold",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(e) => {
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                        log!(format!("{:?}", e));
-                                    },
-                                }
-                            },
-                            Err(e) => log!(format!("{:?}", e)),
-                        }
+                let request = ShipmentRef {
+                    load_id: shipment.load_id.clone(),
+                };
+                match Api::new(&app_state).shipment_hold(&request).await {
+                    Ok(shipment) => {
+                        let msg = ShipmentRef {
+                            load_id: shipment.load_id,
+                        };
+                        app_state.send_ws_message(WsEvent::ShipmentHold(msg));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             })
         })
@@ -191,28 +147,38 @@ pub fn shipments() -> Html {
 
     let set_trailer = {
         let app_state = app_state.clone();
-        Callback::from(move |obj: (String, Shipment)| {
+        Callback::from(move |obj: (Route, Shipment)| {
             app_state.dispatch(AppStateAction::SetCurrentShipment(obj.1));
-            app_state.dispatch(AppStateAction::SetCurrentView(obj.0));
+            app_state.dispatch(AppStateAction::Navigate(obj.0));
+        })
+    };
+
+    let download_report = {
+        let data = app_state.shipments.clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx("shipments.xlsx", "Today's Shipments", &data, &(), &CsvOptions::default());
         })
     };
 
     let change = {
         let app_state = app_state.clone();
-        Callback::from(move |view: String| {
-            app_state.dispatch(AppStateAction::SetCurrentView(view));
+        Callback::from(move |route: Route| {
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     html! {
         <div style="margin-top: 7vh; width: 90vw;">
             <h1 style="text-align: center;">{ "Today's Shipments" }</h1>
-            <div style="text-align: center; color: blue; text-decoration: underline;" onclick={change.clone().reform(move |_| "shipments".to_string())}><h5>{"Recent"}</h5></div>
+            <div style="text-align: center; color: blue; text-decoration: underline;" onclick={change.clone().reform(move |_| Route::Shipments)}><h5>{"Recent"}</h5></div>
+            <div style="text-align: center; color: blue; text-decoration: underline;" onclick={download_report}><h5>{"Download Report"}</h5></div>
             <div style="margin: 3%; display: flex; width: 100%; flex-direction: row; justify-content: space-evenly;">
             <h3 style="text-align: center;">{"Total: "} {app_state.shipments.len()}</h3><h3 style="text-align: center;">{"Complete: "} {total_expected(&app_state.shipments).4}</h3>
             </div>
             <div style="margin: 3%; display: flex; width: 100%; flex-direction: row; justify-content: space-evenly;">
-            <h3 style="text-align: center;">{"AR: "} {total_by_plant(&app_state.shipments).0}</h3><h3 style="text-align: center;">{"SH: "} {total_by_plant(&app_state.shipments).1}</h3>
+            { total_by_plant(&app_state.shipments, app_state.plants()).into_iter().map(|(plant, count)| html! {
+                <h3 style="text-align: center;">{format!("{}: ", plant)} {count}</h3>
+            }).collect::<Html>() }
             </div>
             <div style="margin: 3%; display: flex; width: 100%; flex-direction: row; justify-content: space-evenly;">
             <h3>{"Not Started: "} {total_expected(&app_state.shipments).0}</h3> <h3>{"Picking: "} {total_expected(&app_state.shipments).1}</h3>
@@ -253,9 +219,9 @@ pub fn shipments() -> Html {
                     html! {
                         <tr style="text-align: center;">
                             <td>{index + 1}</td>
-                            <td><a onclick={set_trailer.clone().reform(move |_| ("shipment_details".to_string(), ship5.clone()))}>{shipment.LoadId.clone()}</a></td>
+                            <td><a onclick={set_trailer.clone().reform(move |_| (Route::ShipmentDetails(ship5.load_id.clone()), ship5.clone()))}>{shipment.load_id.clone()}</a></td>
                             {
-                                if shipment.IsHold {
+                                if shipment.is_hold {
                                     html! {
                                         <td style="background-color: red;">
                                             {"HOLD"}
@@ -263,69 +229,71 @@ pub fn shipments() -> Html {
                                     } 
                                 } else {
                                         html! {
-                                            <td style={format!("background-color: {};", get_background(shipment.Status.as_str()))}>{shipment.Status.clone()}</td>
+                                            <td style={format!("background-color: {};", shipment.status.color())}>{shipment.status.as_str()}</td>
                                         }
                                 }
                             }
-                            <td>{shipment.ScheduleDate.clone()}</td>
-                            <td>{shipment.ScheduleTime.clone()}</td>
+                            <td>{shipment.schedule_date.to_string()}</td>
+                            <td>{shipment.schedule_time.to_string()}</td>
                             {
-                                if user.is_authorized() && shipment.ArrivalTime.len() == 0 {
-                                    html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={set_trailer.clone().reform(move |_| ("set_trailer".to_string(), ship2.clone()))}>{ "Set Trailer" }</button></td>}
+                                if user.can(Permission::ManageItems) && shipment.arrival_time.is_empty() {
+                                    html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={set_trailer.clone().reform(move |_| (Route::SetTrailer(ship2.load_id.clone()), ship2.clone()))}>{ "Set Trailer" }</button></td>}
                                 } else {
-                                    html! {<td>{shipment.ArrivalTime.clone()}</td>}
+                                    html! {<td>{shipment.arrival_time.to_string()}</td>}
                                 }
                             }
-                            <td>{shipment.DepartTime.clone()}</td>
+                            <td>{shipment.depart_time.to_string()}</td>
                             {
-                                if user.is_authorized() && shipment.Door.len() == 0 {
-                                    html! {<td><a onclick={set_trailer.clone().reform(move |_| ("set_door".to_string(), ship.clone()))}>{ "Set Door" }</a></td>}
-                                } else if user.is_authorized() && shipment.Status.as_str() != "COMPLETE" {
-                                    html! {<td><a onclick={set_trailer.clone().reform(move |_| ("set_door".to_string(), ship.clone()))}>{shipment.Door.clone()}</a></td>}
+                                if user.can(Permission::ManageItems) && shipment.door.len() == 0 {
+                                    html! {<td><a onclick={set_trailer.clone().reform(move |_| (Route::SetDoor(ship.load_id.clone()), ship.clone()))}>{ "Set Door" }</a></td>}
+                                } else if user.can(Permission::ManageItems) && shipment.status != ShipmentStatus::Complete {
+                                    html! {<td><a onclick={set_trailer.clone().reform(move |_| (Route::SetDoor(ship.load_id.clone()), ship.clone()))}>{shipment.door.clone()}</a></td>}
                                 } else {
-                                    html! {<td>{shipment.Door.clone()}</td>}
+                                    html! {<td>{shipment.door.clone()}</td>}
                                 }
                             }
-                            <td>{shipment.Dock.clone()}</td>
-                            <td>{shipment.TrailerNum.clone()}</td>
-                            <td>{shipment.LoadNum.clone()}</td>
+                            <td>{shipment.dock.clone()}</td>
+                            <td>{shipment.trailer_num.clone()}</td>
+                            <td>{shipment.load_num.clone()}</td>
                             {
-                                if shipment.Status == "PICKING" && user.is_authorized() {
+                                if shipment.status == ShipmentStatus::Picking && user.can(Permission::ManageItems) {
                                     html! {
                                         <td>
-                                            <a onclick={set_picker.clone().reform(move |_| ship4.clone())}>{shipment.Picker.clone()}</a>
+                                            <a onclick={set_picker.clone().reform(move |_| ship4.clone())}>{shipment.picker.clone()}</a>
                                         </td>
                                     }
                                 } else {
                                     html! {
                                         <td>
-                                            {shipment.Picker.clone()}
+                                            {shipment.picker.clone()}
                                         </td>
                                     }
                                 }
                             }
-                            <td>{shipment.PickStartTime.clone()}</td>
-                            <td>{shipment.PickFinishTime.clone()}</td>
-                            <td>{shipment.VerifiedBy.clone()}</td>
+                            <td>{shipment.pick_start_time.to_string()}</td>
+                            <td>{shipment.pick_finish_time.to_string()}</td>
+                            <td>{shipment.verified_by.clone()}</td>
                             <td>
                                 <ActionButton user={user.clone()} shipment={shipment.clone()} />
                             </td>
                             {
-                                if user.role == "admin".to_string() && shipment.Status != "COMPLETE".to_string() {
+                                if shipment.status != ShipmentStatus::Complete {
                                     html! {
-                                        <td>
-                                            <button style="
-                                            background-color: yellow; 
-                                            color: black; 
-                                            padding: 14px 20px; 
-                                            border: none; 
-                                            cursor: pointer; 
-                                            border-radius: 4px;
-                                            " 
-                                            onclick={hold_load.clone().reform(move |_| ship3.clone())}>
-                                                { "HOLD" }
-                                            </button>
-                                        </td>
+                                        <RequirePermission permission={Permission::HoldShipment}>
+                                            <td>
+                                                <button style="
+                                                background-color: yellow; 
+                                                color: black; 
+                                                padding: 14px 20px; 
+                                                border: none; 
+                                                cursor: pointer; 
+                                                border-radius: 4px;
+                                                " 
+                                                onclick={hold_load.clone().reform(move |_| ship3.clone())}>
+                                                    { "HOLD" }
+                                                </button>
+                                            </td>
+                                        </RequirePermission>
                                     }
                                 } else {
                                     html! {<></>}
@@ -340,192 +308,3 @@ pub fn shipments() -> Html {
         </div>
     }
 }
-
-#[derive(Properties, PartialEq)]
-struct PropTypes {
-    user: User,
-    shipment: Shipment,
-}
-
-#[function_component(ActionButton)]
-fn action_button(props: &PropTypes) -> Html {
-
-    let app_state = use_context::<AppStateContext>().expect("no state found");
-
-    let finish_picking = {
-        let app_state = app_state.clone();
-        let shipment = props.shipment.clone();
-        Callback::from(move |_| {
-            let app_state = app_state.clone();
-            let shipment = shipment.clone();
-            spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = ShipmentPickFinishRequest {
-                        LoadId: shipment.LoadId.clone(),
-                        FinishTime: time(),
-                    };
-                    log!(format!("{:?}",request.clone()));
-                    match client.post("http://localhost:8000/api/shipment_pick_finish")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Shipment>().await {
-                                    Ok(shipment) => {
-                                        let msg = PickFinishMessage {
-                                            LoadId: shipment.LoadId,
-                                            FinishTime: shipment.PickFinishTime,
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "finish_shipment_pick",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(e) => {
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                        log!(format!("{:?}", e));
-                                    },
-                                }
-                            },
-                            Err(e) => log!(format!("{:?}", e)),
-                        }
-                }
-            })
-        })
-    };
-
-    let start_loading = {
-        let app_state = app_state.clone();
-        let shipment = props.shipment.clone();
-        Callback::from(move |_| {
-            let app_state = app_state.clone();
-            let shipment = shipment.clone();
-            spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = ShipmentLoadingRequest {
-                        LoadId: shipment.LoadId.clone(),
-                    };
-                    match client.post("http://localhost:8000/api/shipment_begin_loading")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Shipment>().await {
-                                    Ok(shipment) => {
-                                        let msg = ShipmentLoadingMessage {
-                                            LoadId: shipment.LoadId,
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "shipment_start_loading",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(e) => {
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                        log!(format!("{:?}", e));
-                                    },
-                                }
-                            },
-                            Err(e) => log!(format!("{:?}", e)),
-                        }
-                }
-            })
-        })
-    };
-
-    let on_action = {
-        let app_state = app_state.clone();
-        let shipment = props.shipment.clone();
-        Callback::from(move |_| {
-            app_state.dispatch(AppStateAction::SetCurrentShipment(shipment.clone()));
-            
-            match shipment.Status.as_str() {
-                "NOT STARTED" => app_state.dispatch(AppStateAction::SetCurrentView("set_picker".to_string())),
-                "PICKING" => {
-                    finish_picking.emit(());
-                },
-                "READY TO LOAD" => {
-                    start_loading.emit(());
-                },
-                "LOADING" => {
-                    app_state.dispatch(AppStateAction::SetCurrentView("depart".to_string()));
-                },
-                "VERIFICATION" => app_state.dispatch(AppStateAction::SetCurrentView("verified_by".to_string())),
-                _ => todo!(),
-            }
-        })
-    };
-
-    match props.shipment.Status.as_str() {
-        "NOT STARTED" if props.user.is_authorized() => html! {
-            <button style="background-color: red; 
-                    color: black; 
-                    padding: 14px 20px; 
-                    border: none; 
-                    cursor: pointer; 
-                    border-radius: 4px;" 
-                    onclick={on_action}>
-                        {"Add Picker"}
-            </button>
-        },
-        "PICKING" if props.user.is_authorized() => html! {
-            <button style="background-color: orange; 
-                    color: black; 
-                    padding: 14px 20px; 
-                    border: none; 
-                    cursor: pointer; 
-                    border-radius: 4px;" 
-                    onclick={on_action}>
-                        {"Finish Pick"}
-            </button>
-        },
-        "READY TO LOAD" if props.user.is_authorized() && props.shipment.ArrivalTime.len() > 0 => html! {
-            <button style="background-color: green; 
-                    color: black; 
-                    padding: 14px 20px; 
-                    border: none; 
-                    cursor: pointer; 
-                    border-radius: 4px;" 
-                    onclick={on_action}>
-                        {"Start Loading"}
-            </button>
-        },
-        "LOADING" if props.user.is_authorized() => html! {
-            <button style="background-color: blue; 
-                    color: white; 
-                    padding: 14px 20px; 
-                    border: none; 
-                    cursor: pointer; 
-                    border-radius: 4px;" 
-                    onclick={on_action}>
-                        {"Depart"}
-            </button>
-        },
-        "VERIFICATION" if props.user.is_authorized() => html! {
-            <button style="background-color: teal; 
-                    color: black; 
-                    padding: 14px 20px; 
-                    border: none; 
-                    cursor: pointer; 
-                    border-radius: 4px;" 
-                    onclick={on_action}>
-                        {"Validate"}
-            </button>
-        },
-        _ => html! {
-            <></>
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,325 @@
+use std::cmp::Ordering;
+use web_sys::{window, wasm_bindgen::JsValue, UrlSearchParams};
+use models::TrailerResponse;
+use crate::plants::PlantRegistry;
+
+// The columns of the All Trailers table that can be sorted on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailerSort {
+    RequestDate,
+    TrailerId,
+    Scac,
+    Plant,
+    LastFreeDate,
+    ScheduleDate,
+    ScheduleTime,
+    ArrivalTime,
+    Door,
+}
+
+impl TrailerSort {
+    const ALL: [TrailerSort; 9] = [
+        TrailerSort::RequestDate,
+        TrailerSort::TrailerId,
+        TrailerSort::Scac,
+        TrailerSort::Plant,
+        TrailerSort::LastFreeDate,
+        TrailerSort::ScheduleDate,
+        TrailerSort::ScheduleTime,
+        TrailerSort::ArrivalTime,
+        TrailerSort::Door,
+    ];
+
+    // What goes in the URL
+    fn key(self) -> &'static str {
+        match self {
+            TrailerSort::RequestDate => "requested",
+            TrailerSort::TrailerId => "trailer",
+            TrailerSort::Scac => "scac",
+            TrailerSort::Plant => "plant",
+            TrailerSort::LastFreeDate => "lfd",
+            TrailerSort::ScheduleDate => "date",
+            TrailerSort::ScheduleTime => "time",
+            TrailerSort::ArrivalTime => "arrived",
+            TrailerSort::Door => "door",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<TrailerSort> {
+        TrailerSort::ALL.into_iter().find(|sort| sort.key() == key)
+    }
+
+    fn compare(self, a: &TrailerResponse, b: &TrailerResponse, plants: &PlantRegistry) -> Ordering {
+        match self {
+            TrailerSort::RequestDate => a.schedule.request_date.cmp(&b.schedule.request_date),
+            TrailerSort::TrailerId => a.trailer_id.cmp(&b.trailer_id),
+            TrailerSort::Scac => a.schedule.carrier_code.cmp(&b.schedule.carrier_code),
+            TrailerSort::Plant => plants.locations(&a.cisco_ids).cmp(&plants.locations(&b.cisco_ids)),
+            TrailerSort::LastFreeDate => a.schedule.last_free_date.cmp(&b.schedule.last_free_date),
+            TrailerSort::ScheduleDate => a.schedule.schedule_date.cmp(&b.schedule.schedule_date)
+                .then(a.schedule.schedule_time.cmp(&b.schedule.schedule_time)),
+            TrailerSort::ScheduleTime => a.schedule.schedule_time.cmp(&b.schedule.schedule_time),
+            TrailerSort::ArrivalTime => a.schedule.arrival_time.cmp(&b.schedule.arrival_time),
+            // Doors are numbered, so door 9 comes before door 10
+            TrailerSort::Door => door_number(&a.schedule.door_number).cmp(&door_number(&b.schedule.door_number))
+                .then(a.schedule.door_number.cmp(&b.schedule.door_number)),
+        }
+    }
+}
+
+const QUERY_KEYS: [&str; 9] = ["q", "scac", "plant", "hot", "stat6", "scheduled", "arrived", "sort", "order"];
+
+fn door_number(door: &str) -> Option<u32> {
+    door.trim().parse().ok()
+}
+
+// What the All Trailers table is narrowed down to and how it's ordered. Lives in AppState so it
+// survives moving between screens, and in the query string so it survives a reload or a link.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct TrailerFilter {
+    // Part of a trailer ID, any case
+    pub search: String,
+    pub scac: String,
+    pub plant: String,
+    pub hot: Option<bool>,
+    pub stat6: Option<bool>,
+    pub scheduled: Option<bool>,
+    pub arrived: Option<bool>,
+    pub sort: Option<TrailerSort>,
+    pub descending: bool,
+}
+
+impl TrailerFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == TrailerFilter::default()
+    }
+
+    pub fn matches(&self, trailer: &TrailerResponse, plants: &PlantRegistry) -> bool {
+        let search = self.search.trim().to_lowercase();
+        (search.is_empty() || trailer.trailer_id.to_lowercase().contains(&search))
+            && (self.scac.is_empty() || trailer.schedule.carrier_code.eq_ignore_ascii_case(&self.scac))
+            && (self.plant.is_empty() || trailer.cisco_ids.iter().any(|id| plants.location(id) == self.plant))
+            && self.hot.is_none_or(|hot| trailer.schedule.is_hot == hot)
+            && self.stat6.is_none_or(|stat6| trailer.schedule.is_stat6 == stat6)
+            && self.scheduled.is_none_or(|scheduled| trailer.schedule.schedule_date.is_empty() != scheduled)
+            && self.arrived.is_none_or(|arrived| trailer.schedule.arrival_time.is_empty() != arrived)
+    }
+
+    // The trailers to show, in the order to show them
+    pub fn apply<'a>(&self, trailers: &'a [TrailerResponse], plants: &PlantRegistry) -> Vec<&'a TrailerResponse> {
+        let mut shown: Vec<&TrailerResponse> = trailers.iter().filter(|trailer| self.matches(trailer, plants)).collect();
+        if let Some(sort) = self.sort {
+            shown.sort_by(|a, b| {
+                let order = sort.compare(a, b, plants);
+                if self.descending { order.reverse() } else { order }
+            });
+        }
+        shown
+    }
+
+    // Clicking the column already sorted on flips it, any other column sorts ascending
+    pub fn sort_by(&self, sort: TrailerSort) -> TrailerFilter {
+        TrailerFilter {
+            descending: self.sort == Some(sort) && !self.descending,
+            sort: Some(sort),
+            ..self.clone()
+        }
+    }
+
+    pub fn from_query(search: &str) -> TrailerFilter {
+        let Ok(params) = UrlSearchParams::new_with_str(search) else {
+            return TrailerFilter::default();
+        };
+        TrailerFilter::from_params(|key| params.get(key))
+    }
+
+    // Built from whatever holds the query, so it can be checked without a browser
+    fn from_params(get: impl Fn(&str) -> Option<String>) -> TrailerFilter {
+        let flag = |key: &str| match get(key).as_deref() {
+            Some("yes") => Some(true),
+            Some("no") => Some(false),
+            _ => None,
+        };
+        TrailerFilter {
+            search: get("q").unwrap_or_default(),
+            scac: get("scac").unwrap_or_default(),
+            plant: get("plant").unwrap_or_default(),
+            hot: flag("hot"),
+            stat6: flag("stat6"),
+            scheduled: flag("scheduled"),
+            arrived: flag("arrived"),
+            sort: get("sort").and_then(|key| TrailerSort::from_key(&key)),
+            descending: get("order").as_deref() == Some("desc"),
+        }
+    }
+
+    // The filter's own keys and values, leaving out anything at its default
+    fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+        for (key, value) in [("q", &self.search), ("scac", &self.scac), ("plant", &self.plant)] {
+            if !value.is_empty() {
+                pairs.push((key, value.clone()));
+            }
+        }
+        for (key, value) in [("hot", self.hot), ("stat6", self.stat6), ("scheduled", self.scheduled), ("arrived", self.arrived)] {
+            if let Some(value) = value {
+                pairs.push((key, if value { "yes" } else { "no" }.to_string()));
+            }
+        }
+        if let Some(sort) = self.sort {
+            pairs.push(("sort", sort.key().to_string()));
+            if self.descending {
+                pairs.push(("order", "desc".to_string()));
+            }
+        }
+        pairs
+    }
+
+    // The filter's own keys in the query string, leaving anything else there (like the config
+    // profile) alone
+    fn write_query(&self, params: &UrlSearchParams) {
+        for key in QUERY_KEYS {
+            params.delete(key);
+        }
+        for (key, value) in self.query_pairs() {
+            params.append(key, &value);
+        }
+    }
+
+    // The filter from the address bar, if it has one
+    pub fn current() -> Option<TrailerFilter> {
+        let search = window()?.location().search().ok()?;
+        let filter = TrailerFilter::from_query(&search);
+        if filter.is_empty() { None } else { Some(filter) }
+    }
+
+    // Puts the filter in the address bar without adding a history entry for every keystroke
+    pub fn replace_url(&self) {
+        let Some(window) = window() else {
+            return;
+        };
+        let location = window.location();
+        let path = location.pathname().unwrap_or_else(|_| "/".to_string());
+        let Ok(params) = UrlSearchParams::new_with_str(&location.search().unwrap_or_default()) else {
+            return;
+        };
+        self.write_query(&params);
+        let query = String::from(params.to_string());
+        let url = if query.is_empty() { path } else { format!("{}?{}", path, query) };
+        if let Ok(history) = window.history() {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use models::{Schedule, TrailerResponse};
+    use crate::plants::{Plant, PlantRegistry};
+    use super::*;
+
+    fn plants() -> PlantRegistry {
+        PlantRegistry {
+            plants: vec![
+                Plant { code: "AR".to_string(), cisco_ids: vec!["18008".to_string()], ..Plant::default() },
+                Plant { code: "40".to_string(), cisco_ids: vec!["22010".to_string()], ..Plant::default() },
+            ],
+        }
+    }
+
+    fn trailer(trailer_id: &str, scac: &str, cisco_id: &str, hot: bool, door: &str, schedule_date: &str, arrival_time: &str) -> TrailerResponse {
+        TrailerResponse {
+            trailer_id: trailer_id.to_string(),
+            schedule: Schedule {
+                carrier_code: scac.to_string(),
+                is_hot: hot,
+                door_number: door.to_string(),
+                schedule_date: schedule_date.parse().unwrap(),
+                arrival_time: arrival_time.parse().unwrap(),
+                ..Schedule::default()
+            },
+            cisco_ids: vec![cisco_id.to_string()],
+        }
+    }
+
+    fn trailers() -> Vec<TrailerResponse> {
+        vec![
+            trailer("TRL1001", "JBHT", "18008", true, "10", "2024-05-01", "2024-05-01 07:12:00"),
+            trailer("trl1002", "ODFL", "22010", false, "9", "2024-05-02", ""),
+            trailer("BOX2000", "jbht", "22010", false, "", "", ""),
+        ]
+    }
+
+    fn shown(filter: &TrailerFilter) -> Vec<String> {
+        filter.apply(&trailers(), &plants()).iter().map(|trailer| trailer.trailer_id.clone()).collect()
+    }
+
+    #[test]
+    fn matches_on_every_facet() {
+        assert_eq!(shown(&TrailerFilter::default()).len(), 3);
+        assert_eq!(shown(&TrailerFilter { search: " Trl ".to_string(), ..TrailerFilter::default() }), vec!["TRL1001", "trl1002"]);
+        assert_eq!(shown(&TrailerFilter { scac: "JBHT".to_string(), ..TrailerFilter::default() }), vec!["TRL1001", "BOX2000"]);
+        assert_eq!(shown(&TrailerFilter { plant: "40".to_string(), ..TrailerFilter::default() }), vec!["trl1002", "BOX2000"]);
+        assert_eq!(shown(&TrailerFilter { hot: Some(true), ..TrailerFilter::default() }), vec!["TRL1001"]);
+        assert_eq!(shown(&TrailerFilter { hot: Some(false), ..TrailerFilter::default() }), vec!["trl1002", "BOX2000"]);
+        assert_eq!(shown(&TrailerFilter { scheduled: Some(false), ..TrailerFilter::default() }), vec!["BOX2000"]);
+        assert_eq!(shown(&TrailerFilter { scheduled: Some(true), arrived: Some(false), ..TrailerFilter::default() }), vec!["trl1002"]);
+        assert!(shown(&TrailerFilter { stat6: Some(true), ..TrailerFilter::default() }).is_empty());
+    }
+
+    #[test]
+    fn sorts_doors_by_number_and_flips_on_a_second_click() {
+        let by_door = TrailerFilter::default().sort_by(TrailerSort::Door);
+        assert_eq!(shown(&by_door), vec!["BOX2000", "trl1002", "TRL1001"]);
+        let flipped = by_door.sort_by(TrailerSort::Door);
+        assert!(flipped.descending);
+        assert_eq!(shown(&flipped), vec!["TRL1001", "trl1002", "BOX2000"]);
+        assert!(!flipped.sort_by(TrailerSort::Scac).descending);
+    }
+
+    fn round_trip(filter: &TrailerFilter) -> TrailerFilter {
+        let pairs = filter.query_pairs();
+        TrailerFilter::from_params(|key| pairs.iter().find(|(k, _)| *k == key).map(|(_, value)| value.clone()))
+    }
+
+    #[test]
+    fn round_trips_through_the_query() {
+        let filter = TrailerFilter {
+            search: "trl 1".to_string(),
+            scac: "JBHT".to_string(),
+            plant: "AR".to_string(),
+            hot: Some(true),
+            stat6: Some(false),
+            scheduled: None,
+            arrived: Some(false),
+            sort: Some(TrailerSort::LastFreeDate),
+            descending: true,
+        };
+        assert_eq!(round_trip(&filter), filter);
+        for sort in TrailerSort::ALL {
+            let filter = TrailerFilter::default().sort_by(sort);
+            assert_eq!(round_trip(&filter), filter);
+        }
+    }
+
+    #[test]
+    fn leaves_defaults_out_of_the_query() {
+        assert!(TrailerFilter::default().query_pairs().is_empty());
+        let pairs = TrailerFilter { hot: Some(false), ..TrailerFilter::default() }.query_pairs();
+        assert_eq!(pairs, vec![("hot", "no".to_string())]);
+        assert!(pairs.iter().all(|(key, _)| QUERY_KEYS.contains(key)));
+    }
+
+    #[test]
+    fn ignores_values_it_does_not_know() {
+        let filter = TrailerFilter::from_params(|key| match key {
+            "hot" => Some("maybe".to_string()),
+            "sort" => Some("colour".to_string()),
+            "order" => Some("desc".to_string()),
+            _ => None,
+        });
+        assert_eq!(filter.hot, None);
+        assert_eq!(filter.sort, None);
+        assert!(filter.descending);
+    }
+}
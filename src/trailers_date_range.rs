@@ -1,46 +1,17 @@
-use serde_json::json;
-use web_sys::{js_sys, window, HtmlInputElement};
+use web_sys::HtmlInputElement;
 use yew:: prelude::*;
-use crate::{models::*, state::*};
-use reqwest::Client;
+use crate::{api::Api, export::{download_csv, CsvOptions}, xlsx::download_xlsx, outbox::{self, Mutation}, state::*, route::Route, permissions::Permission, demurrage::lfd_cell, bulk_schedule::{bulk_schedule_bar, select_all_cell, select_cell}};
+use models::*;
+use models::time::Timestamp;
+use models::ws_event::WsEvent;
 use gloo::console::log;
 use wasm_bindgen_futures::spawn_local;
-use chrono::prelude::*;
-use std::fmt::Write;
 
-fn parse_time(time_str: &str) -> (u32, u32) {
-    let time_str = if time_str.is_empty() { "00:00" } else { time_str };
-    let parts: Vec<&str> = time_str.split(':').collect();
-    let hours = parts[0].parse::<u32>().unwrap_or(0);
-    let minutes = parts[1].parse::<u32>().unwrap_or(0);
-    (hours, minutes)
-}
-
-fn render_locations(locations: &Vec<String>) -> String {
-    let mut txt = String::new();
-    for location in locations {
-        match location.as_str() {
-            "18008" => txt.push_str(" AR"),
-            "18044" => txt.push_str(" FF"),
-            "22010" => txt.push_str(" 40"),
-            _ => {}
-        }
-    }
-    txt.trim().to_string()
-}
-
-fn create_csv(data: &Vec<TrailerResponse>) -> String {
-    let mut csv_string = String::new();
-    let _ = writeln!(csv_string, "Container ID, Request Date, SCAC Code, Plant Code, Schedule Date, Schedule Time, Arrival Time, Door Number, Contact Email");
-    for trailer in data {
-        let _ = writeln!(csv_string, "{},{},{},{},{},{},{},{},{}", trailer.TrailerID, trailer.Schedule.RequestDate, trailer.Schedule.CarrierCode ,render_locations(&trailer.CiscoIDs), trailer.Schedule.ScheduleDate, trailer.Schedule.ScheduleTime, trailer.Schedule.ArrivalTime, trailer.Schedule.DoorNumber, trailer.Schedule.ContactEmail);
-    }
-    csv_string
-}
-
-fn time() -> String {
-    let now = Local::now();
-    format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second())
+// A range spans several days, so lead with the day each trailer was scheduled for
+fn export_options() -> CsvOptions {
+    CsvOptions::default()
+        .columns(&["Schedule Date", "Schedule Time", "Container ID", "SCAC Code", "Plant Code", "Arrival Time", "Door Number", "Request Date", "Contact Email"])
+        .header("SCAC Code", "Carrier")
 }
 
 #[function_component(TrailersDateRange)]
@@ -55,68 +26,54 @@ pub fn trailers_date_range() -> Html {
         let date1 = date1.clone();
         let date2 = date2.clone();
         let searched = searched.clone();
-        Callback::from(move |_| {
+        Callback::from(move |_: ()| {
             let app_state = app_state.clone();
             let date1 = date1.clone();
             let date2 = date2.clone();
             let searched = searched.clone();
             spawn_local(async move {
-                let client = Client::new();
                 let request = DateRangeTruckRequest {
-                    date1: date1.to_string(),
-                    date2: date2.to_string()
+                    date1: date1.parse().unwrap_or_default(),
+                    date2: date2.parse().unwrap_or_default(),
                 };
-                if let Some(user) = &app_state.user {
-                    match client.post("http://192.168.4.172:8000/api/trucks_date_range")
-                        .json(&request)
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerResponse>>().await {
-                                    Ok(trailer_response) => {
-                                        let mut trailers = trailer_response.clone();
-                                        trailers.sort_by(|a, b| {
-                                            let (hours_a, minutes_a) = parse_time(&a.Schedule.ScheduleTime);
-                                            let (hours_b, minutes_b) = parse_time(&b.Schedule.ScheduleTime);
-                                            if hours_a == hours_b {
-                                                minutes_a.cmp(&minutes_b)
-                                            } else {
-                                                hours_a.cmp(&hours_b)
-                                            }
-                                        });
-                                        app_state.dispatch(AppStateAction::SetTrailers(trailers));
-                                        searched.set(true);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    }
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                match Api::new(&app_state).trucks_date_range(&request).await {
+                    Ok(trailer_response) => {
+                        let mut trailers = trailer_response.clone();
+                        trailers.sort_by(|a, b| a.schedule.schedule_time.cmp(&b.schedule.schedule_time));
+                        app_state.dispatch(AppStateAction::SetTrailers(trailers));
+                        searched.set(true);
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             })
         })
     };
 
+    // Re-run the last search after the socket missed events
+    {
+        let get_trailers = get_trailers.clone();
+        let searched = searched.clone();
+        use_effect_with(app_state.sync_generation, move |_| {
+            if *searched {
+                get_trailers.emit(());
+            }
+            || ()
+        });
+    }
+
     let download_csv = {
         let data = app_state.trailers.clone();
+        let plants = app_state.plants().clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data);
-            let filename = "date_range.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_csv("date_range.csv", &data, &plants, &export_options().with_bom());
+        })
+    };
+
+    let download_xlsx = {
+        let data = app_state.trailers.clone();
+        let plants = app_state.plants().clone();
+        Callback::from(move |_: MouseEvent| {
+            download_xlsx("date_range.xlsx", "Trailers", &data, &plants, &export_options());
         })
     };
 
@@ -125,41 +82,12 @@ pub fn trailers_date_range() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: "".to_string(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: "".to_string(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::default(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -170,42 +98,12 @@ pub fn trailers_date_range() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                let now = time();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: now.clone(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: now.clone(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::now(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -216,37 +114,15 @@ pub fn trailers_date_range() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = HotTrailerRequest {
-                        TrailerID: trailer_id.clone(),
-                    };
-
-                    match client.post("http://192.168.4.172:8000/api/hot_trailer")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(trailer_response) => {
-                                        log!(format!("{:?}", trailer_response));
-                                        let message = json!({
-                                            "type": "hot_trailer",
-                                            "data": {
-                                                "message": trailer_id.clone()
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => log!(format!("{:?}", error))
-                        }
+                let request = HotTrailer {
+                    trailer_id: trailer_id.clone(),
+                };
+                match Api::new(&app_state).hot_trailer(&request).await {
+                    Ok(trailer_response) => {
+                        log!(format!("{:?}", trailer_response));
+                        app_state.send_ws_message(WsEvent::HotTrailer(HotTrailer { trailer_id: trailer_id.clone() }));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
         })
@@ -255,16 +131,18 @@ pub fn trailers_date_range() -> Html {
     let load = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::LoadDetails(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetCurrentView("load_details".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     let edit = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::EditTrailer(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetCurrentView("edit_trailer".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
@@ -299,10 +177,13 @@ pub fn trailers_date_range() -> Html {
             margin-bottom: 3%">
                 <input style="text-align: center;" type="date" value={(*date1).clone()} oninput={on_date1} />
                 <input style="text-align: center;" type="date" value={(*date2).clone()} oninput={on_date2} />
-                <button style="margin-top: 3%; background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;"  onclick={get_trailers}>{ "Search" }</button>
+                <button style="margin-top: 3%; background-color: green; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;"  onclick={get_trailers.reform(|_| ())}>{ "Search" }</button>
                 { if *searched {
                     html! {
+                        <>
                         <a style="margin-top: 3%;" onclick={download_csv}>{"Download CSV"}</a>
+                        <a style="margin-top: 3%;" onclick={download_xlsx}>{"Download Excel"}</a>
+                        </>
                     }
                     } else {
                     html! {<></>}
@@ -310,9 +191,12 @@ pub fn trailers_date_range() -> Html {
                 }
             </div>
             <div style="width: 90vw;">
+                { bulk_schedule_bar(&app_state) }
                 <table>
                     <thead>
                         <tr style="text-align: center;">
+                            { select_all_cell(&app_state, app_state.trailers.iter().map(|trailer| trailer.trailer_id.clone()).collect()) }
+                            <th>{"#"}</th>
                             <th>{"Request Date"}</th>
                             <th>{"Trailer ID"}</th>
                             <th>{"SCAC"}</th>
@@ -327,33 +211,34 @@ pub fn trailers_date_range() -> Html {
                     </thead>
                     <tbody>
                     { app_state.trailers.iter().enumerate().map(|(index, trailer)| {
-                        if trailer.Schedule.IsHot {
-                            let trailer_id = trailer.TrailerID.clone();
-                            let trailer_id1 = trailer.TrailerID.clone();
-                            let trailer_id2 = trailer.TrailerID.clone();
+                        if trailer.schedule.is_hot {
+                            let trailer_id = trailer.trailer_id.clone();
+                            let trailer_id1 = trailer.trailer_id.clone();
+                            let trailer_id2 = trailer.trailer_id.clone();
                             let tr = trailer.clone();
                             let tr1 = trailer.clone();
                             let user = app_state.user.as_ref().unwrap();
                             html! { 
                                 <tr style="background-color: red; text-align: center;">
+                                    { select_cell(&app_state, &trailer.trailer_id) }
                                     <td>{index + 1}</td>
-                                    <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                    <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                    <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                    <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                    <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                    <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                    <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                    { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                    <td>{trailer.schedule.request_date.to_string()}</td>
+                                    <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                    <td>{trailer.schedule.carrier_code.clone()}</td>
+                                    <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                    { lfd_cell(trailer, app_state.demurrage()) }
+                                    <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                    <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                    { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                         html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                    } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                        html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                    } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                        html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                     } else {
-                                        html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                        html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                     }}
-                                    <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                    <td>{trailer.schedule.door_number.clone()}</td>
                                     <td><button style="background-color: #4CAF50; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Not Hot"}</button></td>
-                                    { if user.role.clone() == "write".to_string() {
+                                    { if user.can(Permission::ScheduleTrailer) {
                                         html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                     } else {
                                         html! {<></>}
@@ -361,32 +246,33 @@ pub fn trailers_date_range() -> Html {
                                 </tr>
                             }
                         } else {
-                            let trailer_id = trailer.TrailerID.clone();
-                            let trailer_id1 = trailer.TrailerID.clone();
-                            let trailer_id2 = trailer.TrailerID.clone();
+                            let trailer_id = trailer.trailer_id.clone();
+                            let trailer_id1 = trailer.trailer_id.clone();
+                            let trailer_id2 = trailer.trailer_id.clone();
                             let tr = trailer.clone();
                             let tr1 = trailer.clone();
                             let user = app_state.user.as_ref().unwrap();
                             html! {
                                 <tr style="text-align: center;">
+                                    { select_cell(&app_state, &trailer.trailer_id) }
                                     <td>{index + 1}</td>
-                                    <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                    <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                    <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                    <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                    <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                    <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                    <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                    { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                    <td>{trailer.schedule.request_date.to_string()}</td>
+                                    <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                    <td>{trailer.schedule.carrier_code.clone()}</td>
+                                    <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                    { lfd_cell(trailer, app_state.demurrage()) }
+                                    <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                    <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                    { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                         html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                    } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                        html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                    } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                        html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                     } else {
-                                        html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                        html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                     }}
-                                    <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                    <td>{trailer.schedule.door_number.clone()}</td>
                                     <td><button style="background-color: #F44336; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Hot"}</button></td>
-                                    { if user.role.clone() == "write".to_string() {
+                                    { if user.can(Permission::ScheduleTrailer) {
                                         html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                     } else {
                                         html! {<></>}
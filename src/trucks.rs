@@ -1,31 +1,14 @@
-use crate::{models::*, state::*};
-use chrono::prelude::*;
-use serde_json::json;
+use crate::{api::Api, outbox::{self, Mutation}, state::*, route::Route, permissions::Permission, demurrage::lfd_cell, bulk_schedule::{bulk_schedule_bar, select_all_cell, select_cell}, trailer_filter::{TrailerFilter, TrailerSort}};
+use models::*;
+use models::time::Timestamp;
+use models::ws_event::WsEvent;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
-use reqwest::Client;
 use gloo::console::log;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 
 use crate::AppStateContext;
 
-fn render_locations(locations: &Vec<String>) -> String {
-    let mut txt = String::new();
-    for location in locations {
-        match location.as_str() {
-            "18008" => txt.push_str(" AR"),
-            "18044" => txt.push_str(" FF"),
-            "22010" => txt.push_str(" 40"),
-            _ => {}
-        }
-    }
-    txt.trim().to_string()
-}
-
-fn time() -> String {
-    let now = Local::now();
-    format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second())
-}
-
 #[function_component(Trucks)]
 pub fn trucks() -> Html {
     let app_state = use_context::<AppStateContext>().expect("no state found");
@@ -45,6 +28,36 @@ pub fn trucks() -> Html {
     }
 }
 
+// A yes/no/either facet. The option values are what the filter's query string uses.
+fn flag_select(any: &str, yes: &str, no: &str, value: Option<bool>, onchange: Callback<Option<bool>>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        onchange.emit(match select.value().as_str() {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        });
+    });
+    html! {
+        <select onchange={onchange}>
+            <option value="" selected={value.is_none()}>{any}</option>
+            <option value="yes" selected={value == Some(true)}>{yes}</option>
+            <option value="no" selected={value == Some(false)}>{no}</option>
+        </select>
+    }
+}
+
+fn sort_header(title: &str, sort: TrailerSort, filter: &TrailerFilter, on_sort: &Callback<TrailerSort>) -> Html {
+    let arrow = match (filter.sort == Some(sort), filter.descending) {
+        (true, false) => " \u{25B2}",
+        (true, true) => " \u{25BC}",
+        _ => "",
+    };
+    html! {
+        <th style="cursor: pointer;" onclick={on_sort.reform(move |_| sort)}>{format!("{}{}", title, arrow)}</th>
+    }
+}
+
 #[function_component(RenderTrucks)]
 fn render_trucks() -> Html {
     let app_state = use_context::<AppStateContext>().expect("no app state found");
@@ -54,40 +67,15 @@ fn render_trucks() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = HotTrailerRequest {
-                        TrailerID: trailer_id.clone(),
-                    };
-
-                    match client.post("http://192.168.4.172:8000/api/hot_trailer")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(trailer_response) => {
-                                        log!(format!("{:?}", trailer_response));
-                                        let message = json!({
-                                            "type": "hot_trailer",
-                                            "data": {
-                                                "message": trailer_id.clone()
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => {
-                                log!(format!("{:?}", error));
-                                app_state.dispatch(AppStateAction::ClearUser);
-                            },
-                        }
+                let request = HotTrailer {
+                    trailer_id: trailer_id.clone(),
+                };
+                match Api::new(&app_state).hot_trailer(&request).await {
+                    Ok(trailer_response) => {
+                        log!(format!("{:?}", trailer_response));
+                        app_state.send_ws_message(WsEvent::HotTrailer(HotTrailer { trailer_id: trailer_id.clone() }));
+                    },
+                    Err(error) => error.handle(&app_state),
                 }
             });
         })
@@ -98,44 +86,12 @@ fn render_trucks() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: "".to_string(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: "".to_string(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => {
-                                log!(format!("{:?}", error));
-                                app_state.dispatch(AppStateAction::ClearUser);
-                            },
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::default(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -146,45 +102,12 @@ fn render_trucks() -> Html {
         Callback::from(move |trailer_id: String| {
             let app_state = app_state.clone();
             spawn_local(async move {
-                let client = Client::new();
-                let now = time();
-                if let Some(user) = &app_state.user {
-                    let request = SetArrivalTimeRequest {
-                        TrailerID: trailer_id.clone(),
-                        ArrivalTime: now.clone(),
-                    };
-                    match client.post("http://192.168.4.172:8000/api/set_arrivalTime")
-                        .header("Authorization", format!("Bearer {}", user.token))
-                        .json(&request)
-                        .send()
-                        .await {
-                            Ok(resp) => {
-                                match resp.json::<Vec<TrailerSchedule>>().await {
-                                    Ok(_trailer_response) => {
-                                        let msg = ArrivalMessage {
-                                            TrailerID: trailer_id,
-                                            ArrivalTime: now.clone(),
-                                        };
-                                        let json_string = serde_json::to_string(&msg).unwrap();
-                                        let message = json!({
-                                            "type": "trailer_arrived",
-                                            "data": {
-                                                "message": json_string
-                                            }
-                                        }).to_string();
-                                        app_state.send_ws_message(&message);
-                                    },
-                                    Err(error) => {
-                                        log!(format!("{:?}", error));
-                                        app_state.dispatch(AppStateAction::ClearUser);
-                                    },
-                                }
-                            },
-                            Err(error) => {
-                                log!(format!("{:?}", error));
-                                app_state.dispatch(AppStateAction::ClearUser);
-                            },
-                        }
+                let request = Arrival {
+                    trailer_id: trailer_id,
+                    arrival_time: Timestamp::now(),
+                };
+                if let Err(error) = outbox::submit(&app_state, Mutation::SetArrivalTime(request)).await {
+                    error.handle(&app_state);
                 }
             })
         })
@@ -193,103 +116,179 @@ fn render_trucks() -> Html {
     let load = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::LoadDetails(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetCurrentView("load_details".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     let edit = {
         let app_state = app_state.clone();
         Callback::from(move |trailer: TrailerResponse| {
+            let route = Route::EditTrailer(trailer.trailer_id.clone());
             app_state.dispatch(AppStateAction::SetCurrentTrailer(trailer));
-            app_state.dispatch(AppStateAction::SetLastView(app_state.current_view.clone()));
-            app_state.dispatch(AppStateAction::SetCurrentView("edit_trailer".to_string()));
+            app_state.dispatch(AppStateAction::Navigate(route));
         })
     };
 
     {
         let app_state = app_state.clone();
-        {
+        use_effect_with(app_state.sync_generation, move |_| {
             let app_state = app_state.clone();
-    
-            use_effect_with((), move |_| {
-                let app_state = app_state.clone();
-    
-                spawn_local(async move {
-                    let client = Client::new();
-                    if let Some(user) = &app_state.user {
-                        match client.get("http://192.168.4.172:8000/api/schedule_trailer")
-                            .header("Authorization", format!("Bearer {}", user.token))
-                            .send()
-                            .await {
-                                Ok(resp) => {
-                                    match resp.json::<Vec<TrailerResponse>>().await {
-                                        Ok(trailer_response) => app_state.dispatch(AppStateAction::SetTrailers(trailer_response)),
-                                        Err(error) => {
-                                            log!(format!("{:?}", error));
-                                            app_state.dispatch(AppStateAction::ClearUser);
-                                        },
-                                    }
-                                },
-                                Err(error) => log!(format!("{:?}", error))
-                            }
-                    }
-                });
-    
-                || ()
+
+            spawn_local(async move {
+                match Api::new(&app_state).schedule_trailer().await {
+                    Ok(trailer_response) => app_state.dispatch(AppStateAction::SetTrailers(trailer_response)),
+                    Err(error) => error.handle(&app_state),
+                }
             });
-        }
+
+            || ()
+        });
+    }
+
+    // A filter in the URL wins, so a shared link shows what it was shared for. Otherwise pick
+    // up where this screen was left.
+    let filter = {
+        let app_state = app_state.clone();
+        use_state(move || TrailerFilter::current().unwrap_or_else(|| app_state.trailer_filter.clone()))
+    };
+
+    {
+        let app_state = app_state.clone();
+        use_effect_with((*filter).clone(), move |filter| {
+            filter.replace_url();
+            if *filter != app_state.trailer_filter {
+                app_state.dispatch(AppStateAction::SetTrailerFilter(filter.clone()));
+            }
+            || ()
+        });
     }
 
+    let update = {
+        let filter = filter.clone();
+        move |change: fn(&mut TrailerFilter, String)| {
+            let filter = filter.clone();
+            Callback::from(move |value: String| {
+                let mut new_filter = (*filter).clone();
+                change(&mut new_filter, value);
+                filter.set(new_filter);
+            })
+        }
+    };
+    let update_flag = {
+        let filter = filter.clone();
+        move |change: fn(&mut TrailerFilter, Option<bool>)| {
+            let filter = filter.clone();
+            Callback::from(move |value: Option<bool>| {
+                let mut new_filter = (*filter).clone();
+                change(&mut new_filter, value);
+                filter.set(new_filter);
+            })
+        }
+    };
+
+    let on_search = update(|filter, value| filter.search = value)
+        .reform(|e: InputEvent| e.target_unchecked_into::<HtmlInputElement>().value());
+    let on_scac = update(|filter, value| filter.scac = value)
+        .reform(|e: Event| e.target_unchecked_into::<HtmlSelectElement>().value());
+    let on_plant = update(|filter, value| filter.plant = value)
+        .reform(|e: Event| e.target_unchecked_into::<HtmlSelectElement>().value());
+
+    let on_sort = {
+        let filter = filter.clone();
+        Callback::from(move |sort: TrailerSort| filter.set(filter.sort_by(sort)))
+    };
+
+    let clear = {
+        let filter = filter.clone();
+        Callback::from(move |_: MouseEvent| filter.set(TrailerFilter::default()))
+    };
+
     let app_state = app_state.clone();
 
+    let mut carriers: Vec<String> = app_state.trailers.iter().map(|trailer| trailer.schedule.carrier_code.clone()).collect();
+    carriers.sort();
+    carriers.dedup();
+    carriers.retain(|carrier| !carrier.is_empty());
+    let shown = filter.apply(&app_state.trailers, app_state.plants());
+
     html! {
         <div style="margin-top: 7vh; width: 90vw;">
             <h1 style="text-align: center;">{ "All Trailers" }</h1>
+            <div style="margin: 2%; display: flex; flex-direction: row; flex-wrap: wrap; justify-content: space-evenly; align-items: center;">
+                <input type="search" placeholder="Trailer ID" value={filter.search.clone()} oninput={on_search} />
+                <select onchange={on_scac}>
+                    <option value="" selected={filter.scac.is_empty()}>{"Any SCAC"}</option>
+                    { for carriers.iter().map(|carrier| html! {
+                        <option value={carrier.clone()} selected={filter.scac == *carrier}>{carrier.clone()}</option>
+                    }) }
+                </select>
+                <select onchange={on_plant}>
+                    <option value="" selected={filter.plant.is_empty()}>{"Any Plant"}</option>
+                    { for app_state.plants().plants.iter().map(|plant| html! {
+                        <option value={plant.code.clone()} selected={filter.plant == plant.code}>{plant.short_name()}</option>
+                    }) }
+                </select>
+                { flag_select("Hot or Not", "Hot", "Not Hot", filter.hot, update_flag(|filter, value| filter.hot = value)) }
+                { flag_select("Stat6 or Not", "Stat6", "Not Stat6", filter.stat6, update_flag(|filter, value| filter.stat6 = value)) }
+                { flag_select("Scheduled or Not", "Scheduled", "Unscheduled", filter.scheduled, update_flag(|filter, value| filter.scheduled = value)) }
+                { flag_select("Arrived or Not", "Arrived", "Not Arrived", filter.arrived, update_flag(|filter, value| filter.arrived = value)) }
+                { if filter.is_empty() {
+                    html! {<></>}
+                } else {
+                    html! { <a onclick={clear}>{"Clear"}</a> }
+                }}
+            </div>
+            <p style="text-align: center;">{format!("Showing {} of {}", shown.len(), app_state.trailers.len())}</p>
+            { bulk_schedule_bar(&app_state) }
             <table>
                 <thead>
                     <tr style="text-align: center;">
-                        <th>{"Request Date"}</th>
-                        <th>{"Trailer ID"}</th>
-                        <th>{"SCAC"}</th>
-                        <th>{"Plant"}</th>
-                        <th>{"Last Free Day"}</th>
-                        <th>{"Scheduled Date"}</th>
-                        <th>{"Scheduled Time"}</th>
-                        <th>{"Arrival Time"}</th>
-                        <th>{"Door"}</th>
+                        { select_all_cell(&app_state, shown.iter().map(|trailer| trailer.trailer_id.clone()).collect()) }
+                        <th>{"#"}</th>
+                        { sort_header("Request Date", TrailerSort::RequestDate, &filter, &on_sort) }
+                        { sort_header("Trailer ID", TrailerSort::TrailerId, &filter, &on_sort) }
+                        { sort_header("SCAC", TrailerSort::Scac, &filter, &on_sort) }
+                        { sort_header("Plant", TrailerSort::Plant, &filter, &on_sort) }
+                        { sort_header("Last Free Day", TrailerSort::LastFreeDate, &filter, &on_sort) }
+                        { sort_header("Scheduled Date", TrailerSort::ScheduleDate, &filter, &on_sort) }
+                        { sort_header("Scheduled Time", TrailerSort::ScheduleTime, &filter, &on_sort) }
+                        { sort_header("Arrival Time", TrailerSort::ArrivalTime, &filter, &on_sort) }
+                        { sort_header("Door", TrailerSort::Door, &filter, &on_sort) }
                         <th>{"Hot?"}</th>
                     </tr>
                 </thead>
                 <tbody>
-                { app_state.trailers.iter().enumerate().map(|(index, trailer)| {
-                    if trailer.Schedule.IsHot {
-                        let trailer_id = trailer.TrailerID.clone();
-                        let trailer_id1 = trailer.TrailerID.clone();
-                        let trailer_id2 = trailer.TrailerID.clone();
+                { shown.into_iter().enumerate().map(|(index, trailer)| {
+                    if trailer.schedule.is_hot {
+                        let trailer_id = trailer.trailer_id.clone();
+                        let trailer_id1 = trailer.trailer_id.clone();
+                        let trailer_id2 = trailer.trailer_id.clone();
                         let tr = trailer.clone();
                         let tr1 = trailer.clone();
                         let user = app_state.user.as_ref().unwrap();
                         html! { 
                             <tr style="background-color: red; text-align: center;">
+                                { select_cell(&app_state, &trailer.trailer_id) }
                                 <td>{index + 1}</td>
-                                <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                <td>{trailer.schedule.request_date.to_string()}</td>
+                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                <td>{trailer.schedule.carrier_code.clone()}</td>
+                                <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                { lfd_cell(trailer, app_state.demurrage()) }
+                                <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                     html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                 } else {
-                                    html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                    html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                 }}
-                                <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                <td>{trailer.schedule.door_number.clone()}</td>
                                 <td><button style="background-color: #4CAF50; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Not Hot"}</button></td>
-                                { if user.role.clone() == "write".to_string() {
+                                { if user.can(Permission::ScheduleTrailer) {
                                     html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                 } else {
                                     html! {<></>}
@@ -297,32 +296,33 @@ fn render_trucks() -> Html {
                             </tr>
                         }
                     } else {
-                        let trailer_id = trailer.TrailerID.clone();
-                        let trailer_id1 = trailer.TrailerID.clone();
-                        let trailer_id2 = trailer.TrailerID.clone();
+                        let trailer_id = trailer.trailer_id.clone();
+                        let trailer_id1 = trailer.trailer_id.clone();
+                        let trailer_id2 = trailer.trailer_id.clone();
                         let tr = trailer.clone();
                         let tr1 = trailer.clone();
                         let user = app_state.user.as_ref().unwrap();
                         html! {
                             <tr style="text-align: center;">
+                                { select_cell(&app_state, &trailer.trailer_id) }
                                 <td>{index + 1}</td>
-                                <td>{trailer.Schedule.RequestDate.clone()}</td>
-                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.TrailerID.clone()}</a></td>
-                                <td>{trailer.Schedule.CarrierCode.clone()}</td>
-                                <td>{render_locations(&trailer.CiscoIDs)}</td>
-                                <td>{trailer.Schedule.LastFreeDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleDate.clone()}</td>
-                                <td>{trailer.Schedule.ScheduleTime.clone()}</td>
-                                { if trailer.Schedule.ScheduleDate.len() > 0 && trailer.Schedule.ArrivalTime.len() < 1 && user.role.clone() == "write".to_string() {
+                                <td>{trailer.schedule.request_date.to_string()}</td>
+                                <td><a onclick={load.clone().reform(move |_| tr.clone())}>{trailer.trailer_id.clone()}</a></td>
+                                <td>{trailer.schedule.carrier_code.clone()}</td>
+                                <td>{app_state.plants().locations(&trailer.cisco_ids)}</td>
+                                { lfd_cell(trailer, app_state.demurrage()) }
+                                <td>{trailer.schedule.schedule_date.to_string()}</td>
+                                <td>{trailer.schedule.schedule_time.to_string()}</td>
+                                { if !trailer.schedule.schedule_date.is_empty() && trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
                                     html! { <td><button onclick={arrived.clone().reform(move |_| trailer_id1.clone())}>{"Arrived"}</button></td> }
-                                } else if trailer.Schedule.ArrivalTime.len() > 0 && user.role.clone() == "write".to_string() {
-                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.Schedule.ArrivalTime.clone()}</a></td>}
+                                } else if !trailer.schedule.arrival_time.is_empty() && user.can(Permission::MarkArrival) {
+                                    html! { <td><a onclick={un_arrived.clone().reform(move |_| trailer_id2.clone())}>{trailer.schedule.arrival_time.to_string()}</a></td>}
                                 } else {
-                                    html! { <td>{trailer.Schedule.ArrivalTime.clone()}</td> }
+                                    html! { <td>{trailer.schedule.arrival_time.to_string()}</td> }
                                 }}
-                                <td>{trailer.Schedule.DoorNumber.clone()}</td>
+                                <td>{trailer.schedule.door_number.clone()}</td>
                                 <td><button style="background-color: #F44336; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={toggle_hot.clone().reform(move |_| trailer_id.clone())}>{"Mark Hot"}</button></td>
-                                { if user.role.clone() == "write".to_string() {
+                                { if user.can(Permission::ScheduleTrailer) {
                                     html! {<td><button style="background-color: blue; color: white; padding: 14px 20px; border: none; cursor: pointer; border-radius: 4px;" onclick={edit.clone().reform(move |_| tr1.clone())}>{"Edit"}</button></td>}
                                 } else {
                                     html! {<></>}
@@ -1,22 +1,11 @@
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, FileReader, HtmlInputElement, js_sys, window};
+use web_sys::{wasm_bindgen::{prelude::*, JsCast}, Event, FileReader, HtmlInputElement, js_sys};
 use yew::prelude::*;
-use reqwest::Client;
 use gloo::console::log;
-use std::{fmt::Write, rc::Rc};
-use crate::{models::Item, gmap::Gmap, fix_parts::FixParts};
-use csv::{ReaderBuilder, StringRecord};
+use crate::{api::Api, import::{CsvImport, CsvImportWizard}, export::{download_csv, CsvExport, CsvOptions}, xlsx::{download_xlsx, Cell, XlsxExport}, fix_parts::ITEM_MASTER_COLUMNS, plants::PlantRegistry, gmap::Gmap, fix_parts::FixParts, state::AppStateContext};
+use models::Item;
 use std::collections::HashSet;
 
-fn plant_code(code: &str) -> String {
-    match code {
-        "AR" => "ARLINGTON".to_string(),
-        "FF" => "FAIRFAX".to_string(),
-        "40" => "SPRING HILL".to_string(),
-        _ => "MULTI".to_string(),
-    }
-}
-
 fn pallet_size(size: f64) -> String {
     match size {
         40.0..=49.99 => "MD".to_string(), // Match size in the range [40.0, 49.99]
@@ -80,28 +69,79 @@ fn filter_primary_length(items: &Vec<Item>) -> Vec<Item> {
 
 
 
-fn create_csv(data: &Vec<Item>) -> String {
-    let mut csv_string = String::new();
-    let filtered = filter_primary_length(data);
-    for item in filtered {
-        let _ = writeln!(
-            csv_string,
-            "{},{},GM,,{},,A,{},{},{},1,1,1,1,1,{},{},{},{},{},{},{},{},{},{},",
-            item.part, item.part_name,
-            pallet_class(item.warehouse_stack.unwrap_or(0), item.secondary_length_in.unwrap_or(0.0)),
-            plant_code(&item.plant), pallet_wide(item.secondary_width_in.unwrap_or(0.0)),
-            pallet_size(item.secondary_length_in.unwrap_or(0.0)), item.std_pk,
-            item.primary_length_in.unwrap_or(0.0), item.primary_width_in.unwrap_or(0.0), item.primary_height_in.unwrap_or(0.0),
-            item.primary_container_weight_lbs.unwrap_or(0.0), item.pieces_per_pallet.unwrap_or(0), item.secondary_length_in.unwrap_or(0.0),
-            item.secondary_width_in.unwrap_or(0.0), item.secondary_height_in.unwrap_or(0.0),
-            item.pallet_weight.unwrap_or(0.0)
-        );
+// Laid out like fix_parts' ItemMaster export, straight from the parts master
+impl CsvExport for Item {
+    type Context = PlantRegistry;
+
+    const COLUMNS: &'static [&'static str] = ITEM_MASTER_COLUMNS;
+
+    fn fields(&self, plants: &PlantRegistry) -> Vec<String> {
+        let secondary_length = self.secondary_length_in.unwrap_or(0.0);
+        vec![
+            self.part.clone(), self.part_name.clone(), "GM".to_string(), String::new(),
+            pallet_class(self.warehouse_stack.unwrap_or(0), secondary_length), String::new(), "A".to_string(),
+            plants.plant_name(&self.plant), pallet_wide(self.secondary_width_in.unwrap_or(0.0)), pallet_size(secondary_length),
+            "1".to_string(), "1".to_string(), "1".to_string(), "1".to_string(), "1".to_string(),
+            self.std_pk.to_string(), self.primary_length_in.unwrap_or(0.0).to_string(), self.primary_width_in.unwrap_or(0.0).to_string(),
+            self.primary_height_in.unwrap_or(0.0).to_string(), self.primary_container_weight_lbs.unwrap_or(0.0).to_string(),
+            self.pieces_per_pallet.unwrap_or(0).to_string(), secondary_length.to_string(), self.secondary_width_in.unwrap_or(0.0).to_string(),
+            self.secondary_height_in.unwrap_or(0.0).to_string(), self.pallet_weight.unwrap_or(0.0).to_string(),
+            String::new(),
+        ]
+    }
+}
+
+impl XlsxExport for Item {
+    fn cells(&self, plants: &PlantRegistry) -> Vec<Cell> {
+        let secondary_length = self.secondary_length_in.unwrap_or(0.0);
+        vec![
+            self.part.clone().into(), self.part_name.clone().into(), "GM".into(), "".into(),
+            pallet_class(self.warehouse_stack.unwrap_or(0), secondary_length).into(), "".into(), "A".into(),
+            plants.plant_name(&self.plant).into(), pallet_wide(self.secondary_width_in.unwrap_or(0.0)).into(), pallet_size(secondary_length).into(),
+            Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0), Cell::Number(1.0),
+            self.std_pk.into(), self.primary_length_in.unwrap_or(0.0).into(), self.primary_width_in.unwrap_or(0.0).into(),
+            self.primary_height_in.unwrap_or(0.0).into(), self.primary_container_weight_lbs.unwrap_or(0.0).into(),
+            self.pieces_per_pallet.unwrap_or(0).into(), secondary_length.into(), self.secondary_width_in.unwrap_or(0.0).into(),
+            self.secondary_height_in.unwrap_or(0.0).into(), self.pallet_weight.unwrap_or(0.0).into(),
+            "".into(),
+        ]
+    }
+}
+
+impl CsvImport for Item {
+    const FIELDS: &'static [&'static str] = &[
+        "Part", "Part Name", "Plant", "Country", "Std Pk", "Primary Length (IN)", "Primary Width  (IN)",
+        "Primary Height (IN)", "Primary Container Weight (LBS)", "Secondary Std Pk (Boxes/Pallet)",
+        "Secondary Length (IN)", "Secondary Width (IN)", "Secondary Height (IN)",
+        "Secondary Container Weight (LBS)", "Part Weight", "UL L", "UL W", "UL H", "Primary per Layer",
+        "Layers per UL", "Pieces per Pallet", "Pallet Weight", "2-20 Week Avg Releases",
+        "ESTIMATED STOCK LEVEL", "Pallets Weekly", "Min Pallets In Stock", "Average Pallets In Stock",
+        "Max Pallets In Stock", "Rack or Floor?", "Rack Pick Face Length or Width",
+        "Pallets per 9' Rack Level", "Rack levels", "Warehouse Stack", "Stacks in Floor Storage",
+        "Floor Pick Face Width", "Storage Lane Width", "Storage Lane Length", "Stacks per Lane",
+        "Num of Storage Lanes", "Pallet depth dim", "Trailer Stack", "UL per 53'", "UL per 40'",
+        "Average IB 40' Weekly", "Average OB 53' Weekly", "area",
+    ];
+
+    // The parts master writes this one with thousands separators
+    fn clean(field: &str, value: &str) -> String {
+        match field {
+            "Pieces per Pallet" => value.trim().replace(',', ""),
+            _ => value.trim().to_string(),
+        }
+    }
+
+    fn validate(&self) -> Vec<(&'static str, String)> {
+        if self.part.is_empty() {
+            return vec![("Part", "no part number".to_string())];
+        }
+        vec![]
     }
-    csv_string
 }
 
 #[function_component(Upload)]
 pub fn upload() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
     let file = use_state(|| None);
     let file_name = use_state(|| None);
     let is_error = use_state(|| false);
@@ -123,8 +163,10 @@ pub fn upload() -> Html {
     let on_submit = {
         let file = file.clone();
         let file_name = file_name.clone();
+        let app_state = app_state.clone();
         Callback::from(move |_| {
             if let Some(file) = (*file).clone() {
+                let api = Api::new(&app_state);
                 let file_name = (*file_name).clone().unwrap();
                 let reader = FileReader::new().unwrap();
                 let reader_clone = reader.clone();
@@ -136,22 +178,11 @@ pub fn upload() -> Html {
                     let bytes = uint8_array.to_vec();
 
                     let file_name_clone = file_name_clone.clone(); // Clone here to avoid move
+                    let api = api.clone();
                     spawn_local(async move {
-                        let client = Client::new();
-                        let part = reqwest::multipart::Part::bytes(bytes)
-                            .file_name(file_name_clone.clone());
-                        let form = reqwest::multipart::Form::new()
-                            .part("file", part);
-
-                        let response = client
-                            .post("http://192.168.4.172:8888/upload")
-                            .multipart(form)
-                            .send()
-                            .await;
-
-                        match response {
-                            Ok(res) => log!(format!("Response: {:?}", res.text().await.unwrap())),
-                            Err(err) => log!(format!("Error: {:?}", err)),
+                        match api.upload(file_name_clone, bytes).await {
+                            Ok(text) => log!(format!("Response: {:?}", text)),
+                            Err(err) => log!(format!("Error: {}", err)),
                         }
                     });
                 }) as Box<dyn FnMut(_)>);
@@ -186,93 +217,36 @@ pub fn upload() -> Html {
 
 #[function_component(PartsUpload)]
 pub fn parts_upload() -> Html {
+    let app_state = use_context::<AppStateContext>().expect("no state found");
     let data = use_state(|| vec![]);
 
-    let on_file_change = {
+    let on_import = {
         let data = data.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Some(files) = input.files() {
-                let infile = files.get(0).unwrap();
-                let file = Rc::new(infile);
-                
-                let onload = {
-                    let data = data.clone();
-                    Callback::from(move |file_content: String| {
-                        let mut rdr = ReaderBuilder::new()
-                            .has_headers(true) // Skip the headers row
-                            .from_reader(file_content.as_bytes()); // Reading as bytes
-
-                        let mut new_data = Vec::<Item>::new();
-                        for result in rdr.records() {
-                            match result {
-                                Ok(record) => {
-                                    let mut modified_values: Vec<String> = record.iter().map(|v| v.to_string()).collect();
-
-                                    // Clean the 21st column (index 20)
-                                    if let Some(value) = modified_values.get_mut(20) { // Mutable reference to the 21st column
-                                        *value = value.replace(',', ""); // Remove commas
-                                    }
-
-                                    // Convert back to a StringRecord
-                                    let modified_record = StringRecord::from(modified_values);
-                                    match modified_record.deserialize::<Item>(None) {
-                                        Ok(item) => new_data.push(item),
-                                        Err(error) => log!(format!("Error deserializing row: {:?}", error)),
-                                    }
-                                }, // Correct type deserialization
-                                Err(err) => log!(format!("Error deserializing row: {:?}", err)),
-                            }
-                        }
-
-                        data.set(new_data); // Store parsed data in state
-                    })
-                };
+        Callback::from(move |rows: Vec<Item>| data.set(rows))
+    };
 
-                let file = Rc::clone(&file);
-                spawn_local(async move {
-                    let file_reader = FileReader::new().unwrap();
-                    let onload_clone = onload.clone();
-                    
-                    // Set up the file reader to read as text
-                    let reader_clone = file_reader.clone();
-                    file_reader.set_onload(Some(Closure::once_into_js(move || {
-                        let content = reader_clone.result().unwrap();
-                        if let Some(text) = content.as_string() {
-                            onload_clone.emit(text);
-                        }
-                    }).unchecked_ref()));
-                    
-                    file_reader.read_as_text(&file).unwrap();
-                });
-            }
+    let download_csv = {
+        let data = data.clone();
+        let plants = app_state.plants().clone();
+        Callback::from(move |_: MouseEvent| {
+            download_csv("ITEMS.csv", &filter_primary_length(&data), &plants, &CsvOptions::default().without_header());
         })
     };
 
-    let download_csv = {
+    let download_xlsx = {
         let data = data.clone();
+        let plants = app_state.plants().clone();
         Callback::from(move |_: MouseEvent| {
-            let csv_string = create_csv(&data);
-            let filename = "ITEMS.csv";
-            let window = window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.create_element("a").unwrap();
-            element.set_attribute("href", &format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv_string))).unwrap();
-            element.set_attribute("download", filename).unwrap();
-            let body = document.body().unwrap();
-            body.append_child(&element).unwrap();
-            let event = document.create_event("MouseEvent").unwrap();
-            event.init_event("click");
-            element.dispatch_event(&event).unwrap();
-            body.remove_child(&element).unwrap();
+            download_xlsx("ITEMS.xlsx", "Items", &filter_primary_length(&data), &plants, &CsvOptions::default());
         })
     };
 
     html! {
         <div>
             <h1>{ "Download Parts CSV" }</h1>
-            <input type="file" accept=".csv" onchange={on_file_change} />
+            <CsvImportWizard<Item> title="Parts Master" on_import={on_import} />
             <button onclick={download_csv}>{ "Download CSV" }</button>
+            <button onclick={download_xlsx}>{ "Download XLSX" }</button>
             <ul>
                 { for data.iter().map(|item| html! {
                     <li>{ format!("Part: {}, Part Name: {}, Plant: {}, Country: {}, Std Pk: {}, Primary Length: {}",
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+// The logged in user and their tokens, as kept in local storage. Only the app needs this, so it
+// isn't in the shared models.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct User {
+    pub username: String,
+    pub role: String,
+    pub token: String,
+    pub refresh_token: Option<String>,
+}
@@ -1,7 +1,7 @@
 use web_sys::window;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Error};
-use crate::models::User;
+use crate::user::User;
 
 const USER_KEY: &str = "user";
 
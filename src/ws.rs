@@ -0,0 +1,234 @@
+use std::{cell::RefCell, rc::{Rc, Weak}};
+use gloo::{console::log, timers::callback::{Interval, Timeout}};
+use web_sys::{js_sys::{self, Date}, wasm_bindgen::{closure::Closure, JsCast}, window, CloseEvent, ErrorEvent, Event, MessageEvent, WebSocket};
+use yew::prelude::*;
+use crate::state::{AppState, AppStateAction};
+use models::ws_event::{WsEvent, WsFrame};
+
+const INITIAL_BACKOFF_MS: u32 = 1_000;
+const MAX_BACKOFF_MS: u32 = 30_000;
+const HEARTBEAT_MS: u32 = 20_000;
+// Two missed heartbeats and we assume the socket is dead even if the browser hasn't noticed
+const HEARTBEAT_TIMEOUT_MS: f64 = (HEARTBEAT_MS * 2) as f64;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WsStatus {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Offline,
+}
+
+impl WsStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WsStatus::Connecting => "Connecting",
+            WsStatus::Connected => "Live",
+            WsStatus::Reconnecting => "Reconnecting",
+            WsStatus::Offline => "Offline",
+        }
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            WsStatus::Connected => "limegreen",
+            WsStatus::Connecting | WsStatus::Reconnecting => "orange",
+            WsStatus::Offline => "red",
+        }
+    }
+}
+
+// Owns the socket for the lifetime of a config. When the socket drops it reconnects with
+// exponential backoff, and a heartbeat ping catches connections that die without a close frame.
+pub struct ReconnectingSocket {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    url: String,
+    dispatcher: UseReducerDispatcher<AppState>,
+    socket: Option<WebSocket>,
+    attempt: u32,
+    last_seen: f64,
+    closed: bool,
+    reconnect: Option<Timeout>,
+    heartbeat: Option<Interval>,
+    onopen: Option<Closure<dyn FnMut(Event)>>,
+    onmessage: Option<Closure<dyn FnMut(MessageEvent)>>,
+    onerror: Option<Closure<dyn FnMut(ErrorEvent)>>,
+    onclose: Option<Closure<dyn FnMut(CloseEvent)>>,
+}
+
+impl ReconnectingSocket {
+    pub fn open(url: &str, dispatcher: UseReducerDispatcher<AppState>) -> Self {
+        let inner = Rc::new(RefCell::new(Inner {
+            url: url.to_string(),
+            dispatcher,
+            socket: None,
+            attempt: 0,
+            last_seen: Date::now(),
+            closed: false,
+            reconnect: None,
+            heartbeat: None,
+            onopen: None,
+            onmessage: None,
+            onerror: None,
+            onclose: None,
+        }));
+        inner.borrow().dispatcher.dispatch(AppStateAction::SetWsStatus(WsStatus::Connecting));
+        connect(&inner);
+        ReconnectingSocket { inner }
+    }
+
+    // Stops reconnecting and closes the socket for good
+    pub fn close(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.closed = true;
+        inner.reconnect = None;
+        inner.heartbeat = None;
+        if let Some(socket) = inner.socket.take() {
+            detach(&socket);
+            let _ = socket.close();
+        }
+        inner.dispatcher.dispatch(AppStateAction::DisconnectWebSocket);
+    }
+}
+
+fn connect(inner: &Rc<RefCell<Inner>>) {
+    let url = inner.borrow().url.clone();
+    let socket = match WebSocket::new(&url) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log!(format!("Failed to open {}: {:?}", url, e));
+            schedule_reconnect(inner);
+            return;
+        }
+    };
+    let weak = Rc::downgrade(inner);
+
+    let onopen = {
+        let weak = weak.clone();
+        Closure::wrap(Box::new(move |_: Event| {
+            if let Some(inner) = weak.upgrade() {
+                on_open(&inner);
+            }
+        }) as Box<dyn FnMut(Event)>)
+    };
+    let onmessage = {
+        let weak = weak.clone();
+        Closure::wrap(Box::new(move |e: MessageEvent| {
+            if let Some(inner) = weak.upgrade() {
+                inner.borrow_mut().last_seen = Date::now();
+                if let Ok(txt) = e.data().dyn_into::<js_sys::JsString>() {
+                    dispatch_message(&inner.borrow().dispatcher, &String::from(txt));
+                }
+            }
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    let onerror = Closure::wrap(Box::new(move |e: ErrorEvent| {
+        log!(&format!("Error: {:?}", e));
+    }) as Box<dyn FnMut(ErrorEvent)>);
+    let onclose = Closure::wrap(Box::new(move |e: CloseEvent| {
+        log!(format!("WebSocket closed: {} {}", e.code(), e.reason()));
+        if let Some(inner) = weak.upgrade() {
+            on_close(&inner);
+        }
+    }) as Box<dyn FnMut(CloseEvent)>);
+
+    socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    socket.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+
+    let mut state = inner.borrow_mut();
+    state.socket = Some(socket);
+    state.onopen = Some(onopen);
+    state.onmessage = Some(onmessage);
+    state.onerror = Some(onerror);
+    state.onclose = Some(onclose);
+}
+
+fn on_open(inner: &Rc<RefCell<Inner>>) {
+    let weak = Rc::downgrade(inner);
+    let mut state = inner.borrow_mut();
+    state.attempt = 0;
+    state.last_seen = Date::now();
+    state.heartbeat = Some(Interval::new(HEARTBEAT_MS, move || {
+        if let Some(inner) = weak.upgrade() {
+            heartbeat(&inner);
+        }
+    }));
+    if let Some(socket) = &state.socket {
+        log!(format!("Connected to {}", state.url));
+        state.dispatcher.dispatch(AppStateAction::ConnectWebSocket(socket.clone()));
+    }
+}
+
+fn on_close(inner: &Rc<RefCell<Inner>>) {
+    inner.borrow_mut().heartbeat = None;
+    drop_socket(inner);
+    schedule_reconnect(inner);
+}
+
+fn heartbeat(inner: &Rc<RefCell<Inner>>) {
+    let (socket, last_seen) = {
+        let state = inner.borrow();
+        match &state.socket {
+            Some(socket) => (socket.clone(), state.last_seen),
+            None => return,
+        }
+    };
+    if Date::now() - last_seen > HEARTBEAT_TIMEOUT_MS {
+        // A dead connection can take minutes to fire onclose, so don't wait for it.
+        // The interval is replaced on the next open rather than dropped from inside its own tick.
+        log!("WebSocket heartbeat missed, reconnecting");
+        let _ = socket.close();
+        drop_socket(inner);
+        schedule_reconnect(inner);
+        return;
+    }
+    let _ = socket.send_with_str(&WsFrame::new(WsEvent::Ping).to_json());
+}
+
+fn drop_socket(inner: &Rc<RefCell<Inner>>) {
+    let mut state = inner.borrow_mut();
+    if let Some(socket) = state.socket.take() {
+        detach(&socket);
+    }
+    state.dispatcher.dispatch(AppStateAction::DisconnectWebSocket);
+}
+
+fn schedule_reconnect(inner: &Rc<RefCell<Inner>>) {
+    let weak: Weak<RefCell<Inner>> = Rc::downgrade(inner);
+    let mut state = inner.borrow_mut();
+    if state.closed {
+        return;
+    }
+    let delay = INITIAL_BACKOFF_MS.saturating_mul(2u32.saturating_pow(state.attempt)).min(MAX_BACKOFF_MS);
+    state.attempt += 1;
+    let online = window().map(|w| w.navigator().on_line()).unwrap_or(true);
+    let status = if online { WsStatus::Reconnecting } else { WsStatus::Offline };
+    state.dispatcher.dispatch(AppStateAction::SetWsStatus(status));
+    log!(format!("Reconnecting in {}ms (attempt {})", delay, state.attempt));
+    state.reconnect = Some(Timeout::new(delay, move || {
+        if let Some(inner) = weak.upgrade() {
+            connect(&inner);
+        }
+    }));
+}
+
+fn detach(socket: &WebSocket) {
+    socket.set_onopen(None);
+    socket.set_onmessage(None);
+    socket.set_onerror(None);
+    socket.set_onclose(None);
+}
+
+fn dispatch_message(dispatcher: &UseReducerDispatcher<AppState>, txt: &str) {
+    match WsFrame::parse(txt) {
+        // Heartbeat reply, last_seen is already updated
+        Ok(WsFrame { event: WsEvent::Pong, .. }) => {},
+        Ok(frame) => dispatcher.dispatch(AppStateAction::HandleWsFrame(frame)),
+        Err(rejected) => dispatcher.dispatch(AppStateAction::RejectWsFrame(rejected)),
+    }
+}
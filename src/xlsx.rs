@@ -0,0 +1,246 @@
+use std::fmt;
+use gloo::console::log;
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, Worksheet, XlsxError};
+use models::time::{CalendarDate, TimeOfDay, Timestamp};
+use crate::export::{download, CsvExport, CsvOptions};
+
+const XLSX_MIME: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+// One value as Excel should see it, so dates sort as dates and quantities add up
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Date(CalendarDate),
+    Time(TimeOfDay),
+    Stamp(Timestamp),
+}
+
+// How a cell reads in a CSV, for exports that build their cells first
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Text(text) => f.write_str(text),
+            Cell::Number(number) => write!(f, "{}", number),
+            Cell::Date(date) => write!(f, "{}", date),
+            Cell::Time(time) => write!(f, "{}", time),
+            Cell::Stamp(stamp) => write!(f, "{}", stamp),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Number(value.into())
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(value: u32) -> Self {
+        Cell::Number(value.into())
+    }
+}
+
+impl From<f32> for Cell {
+    fn from(value: f32) -> Self {
+        Cell::Number(value.into())
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<CalendarDate> for Cell {
+    fn from(value: CalendarDate) -> Self {
+        Cell::Date(value)
+    }
+}
+
+impl From<TimeOfDay> for Cell {
+    fn from(value: TimeOfDay) -> Self {
+        Cell::Time(value)
+    }
+}
+
+impl From<Timestamp> for Cell {
+    fn from(value: Timestamp) -> Self {
+        Cell::Stamp(value)
+    }
+}
+
+// A CSV export that can also be a worksheet. Same columns, same options, but typed cells and
+// a fill colour where a row wants to stand out.
+pub trait XlsxExport: CsvExport {
+    // One cell per entry in COLUMNS, in the same order
+    fn cells(&self, context: &Self::Context) -> Vec<Cell>;
+
+    // Background for one of this row's cells, e.g. a status or a variance
+    fn fill(&self, _column: &str, _context: &Self::Context) -> Option<Color> {
+        None
+    }
+}
+
+pub fn add_sheet<T: XlsxExport>(workbook: &mut Workbook, name: &str, rows: &[T], context: &T::Context, options: &CsvOptions) -> Result<(), XlsxError> {
+    let positions = options.positions::<T>();
+    let taken: Vec<String> = workbook.worksheets().iter().map(Worksheet::name).collect();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet_name(name, &taken))?;
+
+    let header = Format::new().set_bold().set_border_bottom(FormatBorder::Thin);
+    for (col, i) in positions.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, options.header_text(T::COLUMNS[*i]), &header)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    for (row, item) in rows.iter().enumerate() {
+        let cells = item.cells(context);
+        for (col, i) in positions.iter().enumerate() {
+            let mut format = Format::new();
+            if let Some(color) = item.fill(T::COLUMNS[*i], context) {
+                format = format.set_background_color(color);
+            }
+            match cells.get(*i) {
+                Some(cell) => write_cell(worksheet, row as u32 + 1, col as u16, cell, format)?,
+                // A row short a cell still gets the column's fill
+                None => {
+                    worksheet.write_blank(row as u32 + 1, col as u16, &format)?;
+                },
+            }
+        }
+    }
+    worksheet.autofit();
+    Ok(())
+}
+
+fn write_cell(worksheet: &mut Worksheet, row: u32, col: u16, cell: &Cell, format: Format) -> Result<(), XlsxError> {
+    match cell {
+        Cell::Text(text) => worksheet.write_string_with_format(row, col, text, &format)?,
+        Cell::Number(number) => worksheet.write_number_with_format(row, col, *number, &format)?,
+        Cell::Date(CalendarDate(Some(date))) => {
+            worksheet.write_datetime_with_format(row, col, date, &format.set_num_format("yyyy-mm-dd"))?
+        },
+        Cell::Time(TimeOfDay(Some(time))) => {
+            worksheet.write_datetime_with_format(row, col, time, &format.set_num_format("hh:mm"))?
+        },
+        Cell::Stamp(Timestamp::At(stamp)) => {
+            worksheet.write_datetime_with_format(row, col, stamp, &format.set_num_format("yyyy-mm-dd hh:mm:ss"))?
+        },
+        Cell::Stamp(Timestamp::Clock(time)) => {
+            worksheet.write_datetime_with_format(row, col, time, &format.set_num_format("hh:mm:ss"))?
+        },
+        // Nothing to write, but the fill still applies
+        Cell::Date(_) | Cell::Time(_) | Cell::Stamp(_) => worksheet.write_blank(row, col, &format)?,
+    };
+    Ok(())
+}
+
+// Excel won't take a sheet name over 31 characters, with any of []:*?/\ in it, or the same as
+// another sheet's ignoring case. Names that only differ past the 31st character get " (2)",
+// " (3)" and so on in place of their last few.
+fn sheet_name(name: &str, taken: &[String]) -> String {
+    let name: String = name.chars().filter(|c| !"[]:*?/\\".contains(*c)).collect();
+    let name = if name.is_empty() { "Sheet1".to_string() } else { name };
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 { String::new() } else { format!(" ({})", n) };
+            let kept: String = name.chars().take(31 - suffix.len()).collect();
+            kept + &suffix
+        })
+        .find(|candidate| !taken.iter().any(|sheet| sheet.to_lowercase() == candidate.to_lowercase()))
+        .unwrap_or_default()
+}
+
+pub fn download_workbook(filename: &str, workbook: &mut Workbook) {
+    match workbook.save_to_buffer() {
+        Ok(contents) => download(filename, XLSX_MIME, &contents),
+        Err(error) => log!(format!("Failed to build {}: {}", filename, error)),
+    }
+}
+
+// The single-sheet case, for exports that are just the table on screen
+pub fn download_xlsx<T: XlsxExport>(filename: &str, sheet: &str, rows: &[T], context: &T::Context, options: &CsvOptions) {
+    let mut workbook = Workbook::new();
+    match add_sheet(&mut workbook, sheet, rows, context, options) {
+        Ok(()) => download_workbook(filename, &mut workbook),
+        Err(error) => log!(format!("Failed to build {}: {}", filename, error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(Vec<Cell>);
+
+    impl CsvExport for Row {
+        type Context = ();
+
+        const COLUMNS: &'static [&'static str] = &["Part", "Qty", "Note"];
+
+        fn fields(&self, _: &()) -> Vec<String> {
+            self.0.iter().map(Cell::to_string).collect()
+        }
+    }
+
+    impl XlsxExport for Row {
+        fn cells(&self, _: &()) -> Vec<Cell> {
+            self.0.clone()
+        }
+    }
+
+    fn names(workbook: &mut Workbook) -> Vec<String> {
+        workbook.worksheets().iter().map(Worksheet::name).collect()
+    }
+
+    #[test]
+    fn cleans_and_shortens_sheet_names() {
+        assert_eq!(sheet_name("Dock 2/3: [East]", &[]), "Dock 23 East");
+        assert_eq!(sheet_name("?*", &[]), "Sheet1");
+        assert_eq!(sheet_name(&"A".repeat(40), &[]).len(), 31);
+    }
+
+    #[test]
+    fn numbers_sheets_whose_names_clash() {
+        let plant = "ARLINGTON ASSEMBLY AND STAMPING";
+        let mut workbook = Workbook::new();
+        for name in [format!("{} NORTH", plant), format!("{} SOUTH", plant), format!("{} WEST", plant)] {
+            add_sheet::<Row>(&mut workbook, &name, &[], &(), &CsvOptions::default()).unwrap();
+        }
+        add_sheet::<Row>(&mut workbook, "sheet1", &[], &(), &CsvOptions::default()).unwrap();
+        add_sheet::<Row>(&mut workbook, "Sheet1", &[], &(), &CsvOptions::default()).unwrap();
+        assert_eq!(names(&mut workbook), vec![
+            plant.to_string(),
+            "ARLINGTON ASSEMBLY AND STAM (2)".to_string(),
+            "ARLINGTON ASSEMBLY AND STAM (3)".to_string(),
+            "sheet1".to_string(),
+            "Sheet1 (2)".to_string(),
+        ]);
+        assert!(workbook.save_to_buffer().is_ok());
+    }
+
+    #[test]
+    fn blanks_cells_a_row_is_missing() {
+        let rows = vec![
+            Row(vec![Cell::Text("7100-001".to_string()), Cell::Number(4.0), Cell::Text("ok".to_string())]),
+            Row(vec![Cell::Text("7100-002".to_string())]),
+        ];
+        let mut workbook = Workbook::new();
+        add_sheet(&mut workbook, "Parts", &rows, &(), &CsvOptions::default()).unwrap();
+        assert!(workbook.save_to_buffer().is_ok());
+    }
+}